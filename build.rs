@@ -0,0 +1,42 @@
+//! Embeds build metadata for `ck version --full`.
+
+use std::process::Command;
+
+/// First line of a command's stdout, if it ran successfully
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let text = String::from_utf8(output.stdout).ok()?;
+    text.lines().next().map(|l| l.trim().to_string())
+}
+
+fn main() {
+    let git_hash = command_output("git", &["rev-parse", "--short=12", "HEAD"])
+        .unwrap_or_else(|| "unknown".to_string());
+
+    // Honor SOURCE_DATE_EPOCH so reproducible builds get a stable date
+    let build_date = std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|secs| secs.parse::<i64>().ok())
+        .and_then(|secs| chrono::DateTime::from_timestamp(secs, 0))
+        .unwrap_or_else(chrono::Utc::now)
+        .format("%Y-%m-%d")
+        .to_string();
+
+    let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let rustc_version =
+        command_output(&rustc, &["--version"]).unwrap_or_else(|| "unknown".to_string());
+
+    let target = std::env::var("TARGET").unwrap_or_else(|_| "unknown".to_string());
+
+    println!("cargo:rustc-env=CK_GIT_HASH={}", git_hash);
+    println!("cargo:rustc-env=CK_BUILD_DATE={}", build_date);
+    println!("cargo:rustc-env=CK_RUSTC_VERSION={}", rustc_version);
+    println!("cargo:rustc-env=CK_TARGET={}", target);
+
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/refs/heads");
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+}
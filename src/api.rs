@@ -2,16 +2,54 @@
 
 use anyhow::{Context, Result};
 use reqwest::{Client, StatusCode};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
 use crate::config;
+use crate::daemon;
+use crate::request_log;
+
+/// Per-invocation settings used to build an `ApiClient`, taken from global CLI flags
+#[derive(Debug, Clone, Default)]
+pub struct ClientOptions {
+    /// Session ID from `--session`
+    pub session: Option<String>,
+    /// `--no-session`: disable session tracking entirely
+    pub no_session: bool,
+    /// `--session-file`: session file to use instead of ~/.candlekeep/session
+    pub session_file: Option<std::path::PathBuf>,
+    /// `--base-url`: overrides the env var and config file
+    pub base_url: Option<String>,
+    /// `--api-version`: overrides `[api] version` in config
+    pub api_version: Option<String>,
+    /// `--timeout`: request timeout in seconds, overrides `[api] timeout_secs`
+    pub timeout_secs: Option<u64>,
+    /// `--connect-timeout`: seconds allowed to connect, overrides `[api] connect_timeout_secs`
+    pub connect_timeout_secs: Option<u64>,
+    /// Sent as `TRACE_ID_HEADER` on every API request
+    pub trace_id: Option<String>,
+    /// `--daemon`: send requests through `ck daemon` when it's running
+    pub daemon: bool,
+    /// `--max-response-size`: largest response body to read, in bytes
+    /// (`DEFAULT_MAX_RESPONSE_SIZE` when unset)
+    pub max_response_size: Option<u64>,
+}
 
 /// API client for CandleKeep
 pub struct ApiClient {
     client: Client,
     base_url: String,
+    api_version: String,
     api_key: String,
     session: Option<String>,
+    trace_id: Option<String>,
+    /// Daemon socket to forward requests through (`--daemon`)
+    daemon_socket: Option<std::path::PathBuf>,
+    /// Overall request timeout, passed along to the daemon
+    timeout: Option<Duration>,
+    /// Responses with a larger body fail instead of being buffered
+    max_response_size: u64,
 }
 
 // Response types
@@ -28,6 +66,39 @@ pub struct WhoamiResponse {
     pub item_count: i32,
 }
 
+impl WhoamiResponse {
+    /// True when the library can't take another item. A non-positive limit means unlimited.
+    pub fn at_limit(&self) -> bool {
+        self.item_limit > 0 && self.item_count >= self.item_limit
+    }
+
+    /// True when usage has reached `threshold` (0.0-1.0) of the item limit
+    pub fn near_limit(&self, threshold: f64) -> bool {
+        self.item_limit > 0 && f64::from(self.item_count) >= threshold * f64::from(self.item_limit)
+    }
+
+    /// A free-tier user with `item_count` of `item_limit` items, for tests
+    #[cfg(test)]
+    pub fn with_usage(item_count: i32, item_limit: i32) -> Self {
+        Self {
+            id: "usr_1".to_string(),
+            email: "a@b.c".to_string(),
+            name: None,
+            tier: "free".to_string(),
+            item_limit,
+            item_count,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RefreshKeyResponse {
+    #[serde(rename = "apiKey")]
+    pub api_key: String,
+    #[serde(rename = "expiresAt")]
+    pub expires_at: Option<String>,
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct EnrichmentQueueItem {
     pub id: String,
@@ -64,6 +135,8 @@ pub struct Item {
     #[serde(rename = "pageCount")]
     pub page_count: i32,
     pub status: String,  // "DRAFT" | "PROCESSING" | "READY" | "FAILED"
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -99,6 +172,13 @@ pub struct ItemWithPages {
     pub page_count: i32,
     pub pages: Vec<Page>,
     pub status: String,  // "DRAFT" | "PROCESSING" | "READY" | "FAILED"
+    /// Pages left out by `items read --skip-empty`; filled in locally
+    #[serde(
+        rename = "skippedEmptyPages",
+        skip_deserializing,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub skipped_empty_pages: Option<usize>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -108,6 +188,14 @@ pub struct Page {
     pub page_num: i32,
     pub content: Option<String>,
     pub metadata: Option<serde_json::Value>,
+    /// The TOC entry this page falls under; filled in locally by
+    /// `items read --toc-context`
+    #[serde(skip_deserializing, skip_serializing_if = "Option::is_none")]
+    pub chapter: Option<String>,
+    /// `content` rendered as plain text; filled in locally by
+    /// `items read --strip-markdown` for structured output
+    #[serde(rename = "plainText", skip_deserializing, skip_serializing_if = "Option::is_none")]
+    pub plain_text: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -155,6 +243,10 @@ pub struct ConfirmResponse {
 pub struct ConfirmItem {
     pub id: String,
     pub title: String,
+    #[serde(default)]
+    pub author: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -179,6 +271,148 @@ pub struct ApiError {
     pub error: String,
 }
 
+/// A non-success HTTP response, as produced by `handle_error`.
+/// Callers can `downcast_ref` to this to branch on the status.
+#[derive(Debug)]
+pub struct ApiStatusError {
+    pub status: StatusCode,
+    pub message: String,
+}
+
+impl std::fmt::Display for ApiStatusError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.status {
+            StatusCode::UNAUTHORIZED => write!(f, "Authentication failed: {}", self.message),
+            StatusCode::FORBIDDEN => write!(f, "Access denied: {}", self.message),
+            StatusCode::NOT_FOUND => write!(f, "Not found: {}", self.message),
+            StatusCode::BAD_REQUEST => write!(f, "Bad request: {}", self.message),
+            status => write!(f, "API error ({}): {}", status, self.message),
+        }
+    }
+}
+
+impl std::error::Error for ApiStatusError {}
+
+/// The HTTP status behind `err`, if it came from an API response
+pub fn error_status(err: &anyhow::Error) -> Option<StatusCode> {
+    err.downcast_ref::<ApiStatusError>().map(|e| e.status)
+}
+
+/// True for failures worth retrying: the server couldn't be reached, the
+/// request timed out, or the server returned a 5xx
+pub fn is_transient_error(err: &anyhow::Error) -> bool {
+    if let Some(status) = error_status(err) {
+        return status.is_server_error();
+    }
+    if let Some(e) = err.chain().find_map(|cause| cause.downcast_ref::<daemon::ForwardError>()) {
+        return e.transient;
+    }
+    err.chain()
+        .filter_map(|cause| cause.downcast_ref::<reqwest::Error>())
+        .any(|e| e.is_connect() || e.is_timeout() || e.is_request())
+}
+
+/// Describe a request that got no response: connect failures (including a
+/// connect timeout) mean the host is unreachable, while a plain timeout means
+/// it was reached but didn't answer in time
+pub fn transport_error_message(err: &reqwest::Error, host: &str) -> String {
+    if err.is_connect() {
+        format!("Couldn't reach {}", host)
+    } else if err.is_timeout() {
+        format!("Timed out waiting for a response from {}", host)
+    } else {
+        "Failed to connect to API".to_string()
+    }
+}
+
+/// Header carrying a client-generated key so the server can drop duplicate
+/// deliveries of the same mutating request
+pub const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+
+/// Header tying every request of one CLI invocation together in server logs
+pub const TRACE_ID_HEADER: &str = "X-Trace-Id";
+
+/// A random trace ID for this invocation, used unless `--trace-id` is given
+pub fn new_trace_id() -> String {
+    uuid::Uuid::new_v4().simple().to_string()
+}
+
+/// Check a `--trace-id` value: 1-128 printable ASCII characters, no spaces
+pub fn parse_trace_id(value: &str) -> std::result::Result<String, String> {
+    if value.is_empty() || value.len() > 128 || !value.bytes().all(|b| b.is_ascii_graphic()) {
+        return Err("expected 1-128 printable ASCII characters without spaces".to_string());
+    }
+    Ok(value.to_string())
+}
+
+/// Attempts made for calls retried with `retry_transient`
+pub const RETRY_ATTEMPTS: u32 = 3;
+/// Delay before the first retry; doubles on each further attempt
+pub const RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// A fresh key for `IDEMPOTENCY_KEY_HEADER`. Reuse it across retries of one
+/// logical request; never share it between different requests.
+pub fn new_idempotency_key() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
+/// Run `call` up to `attempts` times, retrying after transient failures
+/// (see `is_transient_error`) with a doubling `delay`. Mutating calls must
+/// either replace state wholesale (like `put_content`) or carry an
+/// idempotency key, so a retry after a lost response is harmless.
+pub async fn retry_transient<T, F, Fut>(attempts: u32, delay: Duration, call: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Result<T>>,
+{
+    retry_transient_with(attempts, delay, |_, _| {}, call).await
+}
+
+/// `retry_transient`, calling `on_retry` with the number of the attempt
+/// about to be made and the error that caused it
+pub async fn retry_transient_with<T, F, Fut, R>(
+    attempts: u32,
+    mut delay: Duration,
+    mut on_retry: R,
+    mut call: F,
+) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Result<T>>,
+    R: FnMut(u32, &anyhow::Error),
+{
+    let mut attempt = 1;
+    loop {
+        match call().await {
+            Err(e) if attempt < attempts && is_transient_error(&e) => {
+                on_retry(attempt + 1, &e);
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Metadata fields to change via `enrich_item`; `None` fields are left as-is.
+/// `Some(None)` sends an explicit null, which clears the field.
+#[derive(Debug, Default, Serialize)]
+pub struct MetadataUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author: Option<Option<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<Option<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub toc: Option<Vec<TocEntry>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct EnrichResponse {
     pub item: EnrichedItem,
@@ -196,6 +430,8 @@ pub struct EnrichedItem {
     pub enrichment_confidence: Option<f64>,
     #[serde(rename = "enrichedAt")]
     pub enriched_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -226,6 +462,8 @@ pub struct CreateMarkdownResponse {
     pub created_at: String,
     #[serde(rename = "updatedAt")]
     pub updated_at: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -239,6 +477,10 @@ pub struct GetContentResponse {
     pub page_count: i32,
     #[serde(rename = "updatedAt")]
     pub updated_at: String,
+    /// `content` rendered as plain text; filled in locally by
+    /// `items get --strip-markdown` for structured output
+    #[serde(rename = "plainText", skip_deserializing, skip_serializing_if = "Option::is_none")]
+    pub plain_text: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -289,6 +531,25 @@ pub struct SourcesResponse {
     pub total: i64,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct SourceResponse {
+    pub source: Source,
+}
+
+/// Fields for a new source; at least one of `source_url` and `content` is needed
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewSource {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author_handle: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct SourceDeleteResponse {
     pub deleted: Vec<String>,
@@ -313,8 +574,96 @@ pub struct SessionCompleteResponse {
     pub status: String,
 }
 
+/// `body` argument of `ApiClient::request` for calls without one
+const NO_BODY: Option<&()> = None;
+
+/// Largest response body read unless `--max-response-size` says otherwise
+pub const DEFAULT_MAX_RESPONSE_SIZE: u64 = 256 * 1024 * 1024;
+/// `--max-response-size` value parser: a size as `config::parse_size` takes
+/// it, but never 0, which would refuse every response
+pub fn parse_max_response_size(value: &str) -> Result<u64> {
+    match config::parse_size(value)? {
+        0 => Err(anyhow::anyhow!("--max-response-size must be greater than 0")),
+        size => Ok(size),
+    }
+}
+
+/// Largest error response body read when looking for the error message
+const MAX_ERROR_BODY_SIZE: u64 = 1024 * 1024;
+
+fn response_too_large(limit: u64) -> anyhow::Error {
+    let limit = match limit {
+        n if n % (1024 * 1024) == 0 => format!("{}MB", n / (1024 * 1024)),
+        n if n % 1024 == 0 => format!("{}KB", n / 1024),
+        n => format!("{} bytes", n),
+    };
+    anyhow::anyhow!(
+        "Response too large: the server sent more than {} (raise the limit with --max-response-size)",
+        limit
+    )
+}
+
+/// Characters of a response body quoted in parse errors
+const BODY_SNIPPET_CHARS: usize = 200;
+
+/// Hide the API key and anything else shaped like a key or bearer token
+fn scrub_secrets(text: &str, api_key: &str) -> String {
+    static SECRETS: std::sync::OnceLock<regex::Regex> = std::sync::OnceLock::new();
+    let secrets = SECRETS.get_or_init(|| {
+        regex::Regex::new(r"(?i)\bck_[a-z0-9_-]{8,}|\bbearer\s+[^\s\x22',;]+").unwrap()
+    });
+
+    let text = if api_key.is_empty() {
+        text.to_string()
+    } else {
+        text.replace(api_key, &config::redact_api_key(api_key))
+    };
+    secrets
+        .replace_all(&text, |caps: &regex::Captures| {
+            if caps[0].to_ascii_lowercase().starts_with("bearer") {
+                "Bearer ****".to_string()
+            } else {
+                "ck_****".to_string()
+            }
+        })
+        .into_owned()
+}
+
+/// The start of a body on one line, quoted, with … when cut short
+fn body_snippet(text: &str) -> String {
+    let flat: String = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if flat.is_empty() {
+        return "(empty)".to_string();
+    }
+    let mut snippet: String = flat.chars().take(BODY_SNIPPET_CHARS).collect();
+    if snippet.len() < flat.len() {
+        snippet.push('…');
+    }
+    format!("{:?}", snippet)
+}
+
+/// Read a response body, failing as soon as it passes `limit` bytes rather
+/// than buffering all of it
+pub async fn read_capped(response: reqwest::Response, limit: u64) -> Result<Vec<u8>> {
+    use futures_util::StreamExt;
+
+    if response.content_length().is_some_and(|len| len > limit) {
+        return Err(response_too_large(limit));
+    }
+    let mut body = Vec::new();
+    let mut stream = response.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.context("Failed to read response")?;
+        if (body.len() + chunk.len()) as u64 > limit {
+            return Err(response_too_large(limit));
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(body)
+}
+
 /// Request type for reading items with optional per-item page ranges
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ItemReadRequest {
     pub id: String,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -323,64 +672,108 @@ pub struct ItemReadRequest {
 
 impl ApiClient {
     /// Create a new API client with the configured API key
-    pub fn new(session: Option<String>, no_session: bool) -> Result<Self> {
+    pub fn new(opts: &ClientOptions) -> Result<Self> {
         let api_key = config::get_api_key()?
             .context("Not authenticated. Run 'ck auth login' first.")?;
-        let base_url = config::get_api_url()?;
+        let base_url = config::resolve_api_url(opts.base_url.as_deref())?;
 
         // Resolve session: --no-session > --session flag > file > none
-        let resolved_session = if no_session {
+        let resolved_session = if opts.no_session {
             None
-        } else if let Some(s) = session {
-            Some(s)
+        } else if let Some(ref s) = opts.session {
+            Some(s.clone())
         } else {
-            Self::read_session_file()
+            Self::read_session_file(opts)
         };
 
-        let client = Client::builder()
-            .user_agent(format!("ck-cli/{}", env!("CARGO_PKG_VERSION")))
-            .build()
-            .context("Failed to create HTTP client")?;
+        let client = Self::http_client(opts)?;
 
         Ok(Self {
             client,
             base_url,
+            api_version: config::resolve_api_version(opts.api_version.as_deref())?,
             api_key,
             session: resolved_session,
+            trace_id: opts.trace_id.clone(),
+            daemon_socket: Self::daemon_socket(opts),
+            timeout: Self::timeout(opts)?,
+            max_response_size: opts.max_response_size.unwrap_or(DEFAULT_MAX_RESPONSE_SIZE),
         })
     }
 
     /// Create a new API client with a specific API key (for validation)
-    pub fn with_key(api_key: &str, session: Option<String>, no_session: bool) -> Result<Self> {
-        let base_url = config::get_api_url()?;
+    pub fn with_key(api_key: &str, opts: &ClientOptions) -> Result<Self> {
+        let base_url = config::resolve_api_url(opts.base_url.as_deref())?;
 
-        let resolved_session = if no_session {
+        let resolved_session = if opts.no_session {
             None
-        } else if let Some(s) = session {
-            Some(s)
+        } else if let Some(ref s) = opts.session {
+            Some(s.clone())
         } else {
-            Self::read_session_file()
+            Self::read_session_file(opts)
         };
 
-        let client = Client::builder()
-            .user_agent(format!("ck-cli/{}", env!("CARGO_PKG_VERSION")))
-            .build()
-            .context("Failed to create HTTP client")?;
+        let client = Self::http_client(opts)?;
 
         Ok(Self {
             client,
             base_url,
+            api_version: config::resolve_api_version(opts.api_version.as_deref())?,
             api_key: api_key.to_string(),
             session: resolved_session,
+            trace_id: opts.trace_id.clone(),
+            daemon_socket: Self::daemon_socket(opts),
+            timeout: Self::timeout(opts)?,
+            max_response_size: opts.max_response_size.unwrap_or(DEFAULT_MAX_RESPONSE_SIZE),
         })
     }
 
+    /// Build the underlying HTTP client; `--timeout` and `--connect-timeout`
+    /// win over the config file
+    pub fn http_client(opts: &ClientOptions) -> Result<Client> {
+        let mut builder = Client::builder()
+            .user_agent(format!("ck-cli/{}", env!("CARGO_PKG_VERSION")));
+        if let Some(timeout) = Self::timeout(opts)? {
+            builder = builder.timeout(timeout);
+        }
+        let connect_timeout = match opts.connect_timeout_secs {
+            Some(secs) => Some(secs),
+            None => config::get_connect_timeout_secs()?,
+        };
+        if let Some(secs) = connect_timeout {
+            builder = builder.connect_timeout(Duration::from_secs(secs));
+        }
+        builder.build().context("Failed to create HTTP client")
+    }
+
+    /// Overall request timeout: `--timeout`, else `[api] timeout_secs`
+    fn timeout(opts: &ClientOptions) -> Result<Option<Duration>> {
+        let secs = match opts.timeout_secs {
+            Some(secs) => Some(secs),
+            None => config::get_timeout_secs()?,
+        };
+        Ok(secs.map(Duration::from_secs))
+    }
+
+    fn daemon_socket(opts: &ClientOptions) -> Option<std::path::PathBuf> {
+        if opts.daemon {
+            daemon::socket_path().ok()
+        } else {
+            None
+        }
+    }
+
     fn api_url(&self, path: &str) -> String {
-        format!("{}/api/v1{}", self.base_url, path)
+        format!(
+            "{}/api/{}{}",
+            self.base_url.trim_end_matches('/'),
+            self.api_version,
+            path
+        )
     }
 
     /// Build a request with auth and optional session headers
-    fn request(&self, method: reqwest::Method, path: &str) -> reqwest::RequestBuilder {
+    fn builder(&self, method: reqwest::Method, path: &str) -> reqwest::RequestBuilder {
         let url = self.api_url(path);
         let mut builder = self
             .client
@@ -390,88 +783,183 @@ impl ApiClient {
         if let Some(ref session) = self.session {
             builder = builder.header("X-CK-Session", session.as_str());
         }
+        if let Some(ref trace_id) = self.trace_id {
+            builder = builder.header(TRACE_ID_HEADER, trace_id.as_str());
+        }
 
         builder
     }
 
-    /// Read session ID from ~/.candlekeep/session file
-    pub fn read_session_file() -> Option<String> {
-        let path = dirs::home_dir()?.join(".candlekeep").join("session");
+    /// Send an API request, recording it in the request log when one is enabled
+    async fn send(&self, builder: reqwest::RequestBuilder) -> Result<reqwest::Response> {
+        let request = builder.build().context("Failed to build request")?;
+        let method = request.method().to_string();
+        let path = request.url().path().to_string();
+        let host = request.url().host_str().unwrap_or_default().to_string();
+
+        let started = std::time::Instant::now();
+        if let Some(ref socket) = self.daemon_socket {
+            let forwarded =
+                daemon::forward(socket, &request, self.timeout, self.max_response_size).await;
+            if let Some(result) = forwarded {
+                let (status, error) = match &result {
+                    Ok(response) => (Some(response.status().as_u16()), None),
+                    Err(e) => (None, Some(format!("{:#}", e))),
+                };
+                request_log::record(&method, &path, status, started.elapsed(), error.as_deref());
+                return result;
+            }
+        }
+
+        let result = self.client.execute(request).await;
+
+        let (status, error) = match &result {
+            Ok(response) => (Some(response.status().as_u16()), None),
+            Err(e) => (
+                None,
+                Some(
+                    e.to_string()
+                        .replace(&self.api_key, &config::redact_api_key(&self.api_key)),
+                ),
+            ),
+        };
+        request_log::record(&method, &path, status, started.elapsed(), error.as_deref());
+
+        result.map_err(|e| {
+            let context = transport_error_message(&e, &host);
+            anyhow::Error::new(e).context(context)
+        })
+    }
+
+    /// Send a built request and parse its JSON response, turning non-success
+    /// statuses into `ApiStatusError`s
+    async fn call<T: DeserializeOwned>(&self, builder: reqwest::RequestBuilder) -> Result<T> {
+        let response = self.send(builder).await?;
+        if !response.status().is_success() {
+            return Err(Self::handle_error(response).await);
+        }
+        self.parse_json(response).await
+    }
+
+    /// Call `method path` with an optional JSON body (`NO_BODY` for none)
+    async fn request<T: DeserializeOwned, B: Serialize + ?Sized>(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        body: Option<&B>,
+    ) -> Result<T> {
+        let mut builder = self.builder(method, path);
+        if let Some(body) = body {
+            builder = builder.json(body);
+        }
+        self.call(builder).await
+    }
+
+    /// The session file: `--session-file` if given, else ~/.candlekeep/session
+    pub fn session_file_path(opts: &ClientOptions) -> Result<std::path::PathBuf> {
+        match opts.session_file {
+            Some(ref path) => Ok(path.clone()),
+            None => config::session_path(),
+        }
+    }
+
+    /// Read the session ID from the session file
+    pub fn read_session_file(opts: &ClientOptions) -> Option<String> {
+        let path = Self::session_file_path(opts).ok()?;
         std::fs::read_to_string(&path)
             .ok()
             .map(|s| s.trim().to_string())
             .filter(|s| !s.is_empty())
     }
 
-    /// Write session ID to ~/.candlekeep/session file
-    pub fn write_session_file(session_id: &str) -> Result<()> {
-        let dir = dirs::home_dir()
-            .ok_or_else(|| anyhow::anyhow!("No home directory"))?
-            .join(".candlekeep");
-        std::fs::create_dir_all(&dir)?;
-        std::fs::write(dir.join("session"), session_id)?;
+    /// Write the session ID to the session file, creating its directory
+    pub fn write_session_file(session_id: &str, opts: &ClientOptions) -> Result<()> {
+        let path = Self::session_file_path(opts)?;
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, session_id)?;
         Ok(())
     }
 
-    /// Delete ~/.candlekeep/session file
-    pub fn delete_session_file() {
-        if let Some(path) = dirs::home_dir().map(|h| h.join(".candlekeep").join("session")) {
+    /// Delete the session file
+    pub fn delete_session_file(opts: &ClientOptions) {
+        if let Ok(path) = Self::session_file_path(opts) {
             let _ = std::fs::remove_file(path);
         }
     }
 
+    /// Parse a successful response's JSON body, reading at most
+    /// `max_response_size` bytes of it. On failure the error quotes the
+    /// start of the body, with secrets scrubbed.
+    async fn parse_json<T: DeserializeOwned>(
+        &self,
+        response: reqwest::Response,
+    ) -> Result<T> {
+        let path = response.url().path().to_string();
+        let body = read_capped(response, self.max_response_size).await?;
+        serde_json::from_slice(&body).map_err(|e| {
+            let text = String::from_utf8_lossy(&body);
+            let mut context = format!(
+                "Failed to parse response from {}; body: {}",
+                path,
+                body_snippet(&scrub_secrets(&text, &self.api_key))
+            );
+            if text.trim_start().starts_with('<') {
+                context.push_str(" (an HTML page: check the API URL and any proxy in between)");
+            }
+            anyhow::Error::new(e).context(context)
+        })
+    }
+
     /// Handle API error responses
     async fn handle_error(response: reqwest::Response) -> anyhow::Error {
         let status = response.status();
-        let error_text = response
-            .json::<ApiError>()
+        // Only the error message is wanted, so a huge error page isn't read whole
+        let error_text = read_capped(response, MAX_ERROR_BODY_SIZE)
             .await
+            .ok()
+            .and_then(|body| serde_json::from_slice::<ApiError>(&body).ok())
             .map(|e| e.error)
-            .unwrap_or_else(|_| format!("HTTP {}", status));
+            .unwrap_or_else(|| format!("HTTP {}", status));
 
-        match status {
-            StatusCode::UNAUTHORIZED => anyhow::anyhow!("Authentication failed: {}", error_text),
-            StatusCode::FORBIDDEN => anyhow::anyhow!("Access denied: {}", error_text),
-            StatusCode::NOT_FOUND => anyhow::anyhow!("Not found: {}", error_text),
-            StatusCode::BAD_REQUEST => anyhow::anyhow!("Bad request: {}", error_text),
-            _ => anyhow::anyhow!("API error ({}): {}", status, error_text),
-        }
+        anyhow::Error::new(ApiStatusError {
+            status,
+            message: error_text,
+        })
     }
 
     /// GET /api/v1/auth/whoami
     pub async fn whoami(&self) -> Result<WhoamiResponse> {
-        let response = self
-            .request(reqwest::Method::GET, "/auth/whoami")
-            .send()
-            .await
-            .context("Failed to connect to API")?;
+        self.whoami_with_date().await.map(|(user, _)| user)
+    }
+
+    /// `whoami`, plus the server's `Date` header (for clock-skew checks)
+    pub async fn whoami_with_date(&self) -> Result<(WhoamiResponse, Option<String>)> {
+        let request = self.builder(reqwest::Method::GET, "/auth/whoami");
+        let response = self.send(request).await?;
 
         if !response.status().is_success() {
             return Err(Self::handle_error(response).await);
         }
 
-        response
-            .json()
-            .await
-            .context("Failed to parse response")
+        let date = response
+            .headers()
+            .get(reqwest::header::DATE)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        let user = self.parse_json(response).await?;
+        Ok((user, date))
+    }
+
+    /// POST /api/v1/auth/refresh - Exchange the current key for a new one
+    pub async fn refresh_key(&self) -> Result<RefreshKeyResponse> {
+        self.request(reqwest::Method::POST, "/auth/refresh", NO_BODY).await
     }
 
     /// GET /api/v1/items
     pub async fn list_items(&self) -> Result<ItemsResponse> {
-        let response = self
-            .request(reqwest::Method::GET, "/items")
-            .send()
-            .await
-            .context("Failed to connect to API")?;
-
-        if !response.status().is_success() {
-            return Err(Self::handle_error(response).await);
-        }
-
-        response
-            .json()
-            .await
-            .context("Failed to parse response")
+        self.request(reqwest::Method::GET, "/items", NO_BODY).await
     }
 
     /// POST /api/v1/items/batch - Get multiple items with their pages
@@ -482,21 +970,7 @@ impl ApiClient {
             items: Vec<ItemReadRequest>,
         }
 
-        let response = self
-            .request(reqwest::Method::POST, "/items/batch")
-            .json(&Body { items })
-            .send()
-            .await
-            .context("Failed to connect to API")?;
-
-        if !response.status().is_success() {
-            return Err(Self::handle_error(response).await);
-        }
-
-        response
-            .json()
-            .await
-            .context("Failed to parse response")
+        self.request(reqwest::Method::POST, "/items/batch", Some(&Body { items })).await
     }
 
     /// POST /api/v1/items/batch/toc - Get table of contents for multiple items
@@ -506,21 +980,7 @@ impl ApiClient {
             ids: Vec<String>,
         }
 
-        let response = self
-            .request(reqwest::Method::POST, "/items/batch/toc")
-            .json(&Body { ids })
-            .send()
-            .await
-            .context("Failed to connect to API")?;
-
-        if !response.status().is_success() {
-            return Err(Self::handle_error(response).await);
-        }
-
-        response
-            .json()
-            .await
-            .context("Failed to parse response")
+        self.request(reqwest::Method::POST, "/items/batch/toc", Some(&Body { ids })).await
     }
 
     /// POST /api/v1/upload - Get presigned URL for file upload
@@ -538,25 +998,32 @@ impl ApiClient {
             content_type: &'a str,
         }
 
+        let body = Body {
+            filename,
+            size,
+            content_type,
+        };
+        self.request(reqwest::Method::POST, "/upload", Some(&body)).await
+    }
+
+    /// GET an arbitrary URL without API credentials (for `items add --from-url`)
+    pub async fn download(&self, url: &str) -> Result<reqwest::Response> {
         let response = self
-            .request(reqwest::Method::POST, "/upload")
-            .json(&Body {
-                filename,
-                size,
-                content_type,
-            })
+            .client
+            .get(url)
             .send()
             .await
-            .context("Failed to connect to API")?;
+            .with_context(|| format!("Failed to download {}", url))?;
 
         if !response.status().is_success() {
-            return Err(Self::handle_error(response).await);
+            return Err(anyhow::anyhow!(
+                "Download failed ({}): {}",
+                response.status(),
+                url
+            ));
         }
 
-        response
-            .json()
-            .await
-            .context("Failed to parse response")
+        Ok(response)
     }
 
     /// Upload file to presigned URL
@@ -579,31 +1046,32 @@ impl ApiClient {
         Ok(())
     }
 
-    /// POST /api/v1/upload/confirm - Confirm upload and create processing job
-    pub async fn confirm_upload(&self, item_id: &str, storage_key: &str) -> Result<ConfirmResponse> {
+    /// POST /api/v1/upload/confirm - Confirm upload and create processing job.
+    /// Any fields set in `metadata` are sent as the item's initial metadata.
+    pub async fn confirm_upload(
+        &self,
+        item_id: &str,
+        storage_key: &str,
+        metadata: &MetadataUpdate,
+        idempotency_key: Option<&str>,
+    ) -> Result<ConfirmResponse> {
         #[derive(Serialize)]
         struct Body<'a> {
             #[serde(rename = "itemId")]
             item_id: &'a str,
             #[serde(rename = "storageKey")]
             storage_key: &'a str,
+            #[serde(flatten)]
+            metadata: &'a MetadataUpdate,
         }
 
-        let response = self
-            .request(reqwest::Method::POST, "/upload/confirm")
-            .json(&Body { item_id, storage_key })
-            .send()
-            .await
-            .context("Failed to connect to API")?;
-
-        if !response.status().is_success() {
-            return Err(Self::handle_error(response).await);
+        let mut request = self
+            .builder(reqwest::Method::POST, "/upload/confirm")
+            .json(&Body { item_id, storage_key, metadata });
+        if let Some(key) = idempotency_key {
+            request = request.header(IDEMPOTENCY_KEY_HEADER, key);
         }
-
-        response
-            .json()
-            .await
-            .context("Failed to parse response")
+        self.call(request).await
     }
 
     /// DELETE /api/v1/items - Delete multiple items
@@ -613,71 +1081,21 @@ impl ApiClient {
             ids: Vec<String>,
         }
 
-        let response = self
-            .request(reqwest::Method::DELETE, "/items")
-            .json(&Body { ids })
-            .send()
-            .await
-            .context("Failed to connect to API")?;
-
-        if !response.status().is_success() {
-            return Err(Self::handle_error(response).await);
-        }
-
-        response
-            .json()
-            .await
-            .context("Failed to parse response")
+        self.request(reqwest::Method::DELETE, "/items", Some(&Body { ids })).await
     }
 
     /// PATCH /api/v1/items/enrich - Enrich item metadata
-    pub async fn enrich_item(
-        &self,
-        item_id: &str,
-        title: Option<&str>,
-        author: Option<&str>,
-        description: Option<&str>,
-        confidence: Option<f64>,
-        toc: Option<Vec<TocEntry>>,
-    ) -> Result<EnrichResponse> {
+    pub async fn enrich_item(&self, item_id: &str, update: &MetadataUpdate) -> Result<EnrichResponse> {
         #[derive(Serialize)]
         struct Body<'a> {
             #[serde(rename = "itemId")]
             item_id: &'a str,
-            #[serde(skip_serializing_if = "Option::is_none")]
-            title: Option<&'a str>,
-            #[serde(skip_serializing_if = "Option::is_none")]
-            author: Option<&'a str>,
-            #[serde(skip_serializing_if = "Option::is_none")]
-            description: Option<&'a str>,
-            #[serde(skip_serializing_if = "Option::is_none")]
-            confidence: Option<f64>,
-            #[serde(skip_serializing_if = "Option::is_none")]
-            toc: Option<Vec<TocEntry>>,
+            #[serde(flatten)]
+            update: &'a MetadataUpdate,
         }
 
-        let response = self
-            .request(reqwest::Method::PATCH, "/items/enrich")
-            .json(&Body {
-                item_id,
-                title,
-                author,
-                description,
-                confidence,
-                toc,
-            })
-            .send()
-            .await
-            .context("Failed to connect to API")?;
-
-        if !response.status().is_success() {
-            return Err(Self::handle_error(response).await);
-        }
-
-        response
-            .json()
-            .await
-            .context("Failed to parse response")
+        let body = Body { item_id, update };
+        self.request(reqwest::Method::PATCH, "/items/enrich", Some(&body)).await
     }
 
     /// POST /api/v1/items/flag - Flag item as needing enrichment
@@ -688,21 +1106,7 @@ impl ApiClient {
             item_id: &'a str,
         }
 
-        let response = self
-            .request(reqwest::Method::POST, "/items/flag")
-            .json(&Body { item_id })
-            .send()
-            .await
-            .context("Failed to connect to API")?;
-
-        if !response.status().is_success() {
-            return Err(Self::handle_error(response).await);
-        }
-
-        response
-            .json()
-            .await
-            .context("Failed to parse response")
+        self.request(reqwest::Method::POST, "/items/flag", Some(&Body { item_id })).await
     }
 
     /// POST /api/v1/items/markdown - Create a new markdown document
@@ -711,6 +1115,8 @@ impl ApiClient {
         title: &str,
         description: Option<&str>,
         content: Option<&str>,
+        tags: &[String],
+        idempotency_key: Option<&str>,
     ) -> Result<CreateMarkdownResponse> {
         #[derive(Serialize)]
         struct Body<'a> {
@@ -719,92 +1125,76 @@ impl ApiClient {
             description: Option<&'a str>,
             #[serde(skip_serializing_if = "Option::is_none")]
             content: Option<&'a str>,
+            #[serde(skip_serializing_if = "<[String]>::is_empty")]
+            tags: &'a [String],
         }
 
-        let response = self
-            .request(reqwest::Method::POST, "/items/markdown")
+        let mut request = self
+            .builder(reqwest::Method::POST, "/items/markdown")
             .json(&Body {
                 title,
                 description,
                 content,
-            })
-            .send()
-            .await
-            .context("Failed to connect to API")?;
-
-        if !response.status().is_success() {
-            return Err(Self::handle_error(response).await);
+                tags,
+            });
+        if let Some(key) = idempotency_key {
+            request = request.header(IDEMPOTENCY_KEY_HEADER, key);
         }
-
-        response
-            .json()
-            .await
-            .context("Failed to parse response")
+        self.call(request).await
     }
 
     /// GET /api/v1/items/:id/content - Get full document content
     pub async fn get_content(&self, item_id: &str) -> Result<GetContentResponse> {
-        let response = self
-            .request(reqwest::Method::GET, &format!("/items/{}/content", item_id))
-            .send()
-            .await
-            .context("Failed to connect to API")?;
-
-        if !response.status().is_success() {
-            return Err(Self::handle_error(response).await);
-        }
-
-        response
-            .json()
-            .await
-            .context("Failed to parse response")
+        let path = format!("/items/{}/content", item_id);
+        self.request(reqwest::Method::GET, &path, NO_BODY).await
     }
 
-    /// PUT /api/v1/items/:id/content - Replace document content
+    /// PUT /api/v1/items/:id/content - Replace document content.
+    /// A full replace, so it's safe to retry without an idempotency key.
     pub async fn put_content(&self, item_id: &str, content: &str) -> Result<PutContentResponse> {
         #[derive(Serialize)]
         struct Body<'a> {
             content: &'a str,
         }
 
-        let response = self
-            .request(reqwest::Method::PUT, &format!("/items/{}/content", item_id))
-            .json(&Body { content })
-            .send()
-            .await
-            .context("Failed to connect to API")?;
+        let path = format!("/items/{}/content", item_id);
+        self.request(reqwest::Method::PUT, &path, Some(&Body { content })).await
+    }
 
-        if !response.status().is_success() {
-            return Err(Self::handle_error(response).await);
+    /// PATCH /api/v1/items/:id/pages/reorder - Reorder pages; `order` lists
+    /// the current page numbers in their new order
+    pub async fn reorder_pages(&self, item_id: &str, order: &[i32]) -> Result<PutContentResponse> {
+        #[derive(Serialize)]
+        struct Body<'a> {
+            order: &'a [i32],
         }
 
-        response
-            .json()
-            .await
-            .context("Failed to parse response")
+        let path = format!("/items/{}/pages/reorder", item_id);
+        self.request(reqwest::Method::PATCH, &path, Some(&Body { order })).await
     }
 
-    /// GET /api/v1/sources - List sources
-    pub async fn list_sources(&self, limit: u32, cursor: Option<&str>) -> Result<SourcesResponse> {
-        let mut path = format!("/sources?limit={}", limit);
+    /// GET /api/v1/sources - List sources, optionally filtered by author handle
+    pub async fn list_sources(
+        &self,
+        limit: u32,
+        cursor: Option<&str>,
+        author: Option<&str>,
+    ) -> Result<SourcesResponse> {
+        let mut query: Vec<(&str, String)> = vec![("limit", limit.to_string())];
         if let Some(c) = cursor {
-            path.push_str(&format!("&cursor={}", c));
+            query.push(("cursor", c.to_string()));
         }
-
-        let response = self
-            .request(reqwest::Method::GET, &path)
-            .send()
-            .await
-            .context("Failed to connect to API")?;
-
-        if !response.status().is_success() {
-            return Err(Self::handle_error(response).await);
+        if let Some(a) = author {
+            query.push(("author", a.to_string()));
         }
 
-        response
-            .json()
-            .await
-            .context("Failed to parse response")
+        let request = self.builder(reqwest::Method::GET, "/sources").query(&query);
+        self.call(request).await
+    }
+
+    /// POST /api/v1/sources - Save a source
+    pub async fn create_source(&self, source: &NewSource) -> Result<SourceResponse> {
+        self.request(reqwest::Method::POST, "/sources", Some(source)).await
     }
 
     /// DELETE /api/v1/sources - Delete multiple sources
@@ -814,21 +1204,7 @@ impl ApiClient {
             ids: Vec<String>,
         }
 
-        let response = self
-            .request(reqwest::Method::DELETE, "/sources")
-            .json(&Body { ids })
-            .send()
-            .await
-            .context("Failed to connect to API")?;
-
-        if !response.status().is_success() {
-            return Err(Self::handle_error(response).await);
-        }
-
-        response
-            .json()
-            .await
-            .context("Failed to parse response")
+        self.request(reqwest::Method::DELETE, "/sources", Some(&Body { ids })).await
     }
 
     /// POST /api/v1/access/session - Create a new access session
@@ -839,45 +1215,372 @@ impl ApiClient {
             intent: Option<&'a str>,
         }
 
-        let response = self
-            .request(reqwest::Method::POST, "/access/session")
-            .json(&Body { intent })
-            .send()
-            .await
-            .context("Failed to connect to API")?;
-
-        if !response.status().is_success() {
-            return Err(Self::handle_error(response).await);
-        }
-
-        response
-            .json()
-            .await
-            .context("Failed to parse response")
+        self.request(reqwest::Method::POST, "/access/session", Some(&Body { intent })).await
     }
 
-    /// POST /api/v1/access/session/complete - Complete an access session
-    pub async fn complete_session(&self, session_id: &str) -> Result<SessionCompleteResponse> {
+    /// POST /api/v1/access/session/complete - Complete an access session,
+    /// recording `summary` as its outcome
+    pub async fn complete_session(
+        &self,
+        session_id: &str,
+        summary: Option<&str>,
+    ) -> Result<SessionCompleteResponse> {
         #[derive(Serialize)]
         struct Body<'a> {
             #[serde(rename = "sessionId")]
             session_id: &'a str,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            summary: Option<&'a str>,
         }
 
-        let response = self
-            .request(reqwest::Method::POST, "/access/session/complete")
-            .json(&Body { session_id })
-            .send()
-            .await
-            .context("Failed to connect to API")?;
+        let body = Body { session_id, summary };
+        self.request(reqwest::Method::POST, "/access/session/complete", Some(&body)).await
+    }
+}
 
-        if !response.status().is_success() {
-            return Err(Self::handle_error(response).await);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client_with_base_url(base_url: &str) -> ApiClient {
+        ApiClient {
+            client: Client::new(),
+            base_url: config::normalize_base_url(base_url),
+            api_version: config::DEFAULT_API_VERSION.to_string(),
+            api_key: "ck_test".to_string(),
+            session: None,
+            trace_id: Some("trace_test".to_string()),
+            daemon_socket: None,
+            timeout: None,
+            max_response_size: DEFAULT_MAX_RESPONSE_SIZE,
         }
+    }
 
-        response
-            .json()
-            .await
-            .context("Failed to parse response")
+    #[test]
+    fn test_api_url_without_trailing_slash() {
+        let client = client_with_base_url("https://host");
+        assert_eq!(client.api_url("/items"), "https://host/api/v1/items");
+    }
+
+    #[test]
+    fn test_api_url_strips_trailing_slashes() {
+        let client = client_with_base_url("https://host/");
+        assert_eq!(client.api_url("/items"), "https://host/api/v1/items");
+
+        let client = client_with_base_url("http://localhost:3000//");
+        assert_eq!(client.api_url("/auth/whoami"), "http://localhost:3000/api/v1/auth/whoami");
+    }
+
+    #[test]
+    fn test_api_url_uses_configured_version() {
+        let mut client = client_with_base_url("https://host");
+        client.api_version = "v2".to_string();
+        assert_eq!(client.api_url("/items"), "https://host/api/v2/items");
+    }
+
+    #[test]
+    fn test_api_url_hardened_against_unnormalized_base() {
+        let mut client = client_with_base_url("https://host");
+        client.base_url = "https://host/".to_string();
+        assert_eq!(client.api_url("/items"), "https://host/api/v1/items");
+    }
+
+    #[test]
+    fn test_item_limit_checks() {
+        let user = WhoamiResponse::with_usage;
+
+        assert!(user(10, 10).at_limit());
+        assert!(!user(9, 10).at_limit());
+        assert!(user(9, 10).near_limit(0.9));
+        assert!(!user(8, 10).near_limit(0.9));
+        assert!(!user(500, 0).at_limit());
+    }
+
+    #[test]
+    fn test_metadata_update_distinguishes_clear_from_omit() {
+        let update = MetadataUpdate {
+            author: Some(None),
+            description: Some(Some("New".to_string())),
+            ..Default::default()
+        };
+        assert_eq!(
+            serde_json::to_value(&update).unwrap(),
+            serde_json::json!({"author": null, "description": "New"})
+        );
+    }
+
+    /// Serve one canned `(status, body)` response per connection, in order,
+    /// on a local port. The task yields each request's head (request line
+    /// and headers) and body once all responses are sent.
+    async fn serve_responses(
+        responses: Vec<(u16, &'static str)>,
+    ) -> (String, tokio::task::JoinHandle<Vec<(String, String)>>) {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+
+        let server = tokio::spawn(async move {
+            let mut requests = Vec::new();
+            for (status, body) in responses {
+                let (mut socket, _) = listener.accept().await.unwrap();
+
+                let mut request = Vec::new();
+                let mut buf = [0u8; 4096];
+                let head_end = loop {
+                    let n = socket.read(&mut buf).await.unwrap();
+                    request.extend_from_slice(&buf[..n]);
+                    if let Some(pos) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+                        break pos + 4;
+                    }
+                };
+                let head = String::from_utf8_lossy(&request[..head_end]).to_string();
+                let content_length = head
+                    .lines()
+                    .find_map(|line| {
+                        let (name, value) = line.split_once(':')?;
+                        name.eq_ignore_ascii_case("content-length")
+                            .then(|| value.trim().parse::<usize>().ok())?
+                    })
+                    .unwrap_or(0);
+                while request.len() < head_end + content_length {
+                    let n = socket.read(&mut buf).await.unwrap();
+                    request.extend_from_slice(&buf[..n]);
+                }
+                let request_body = String::from_utf8_lossy(&request[head_end..]).to_string();
+                requests.push((head, request_body));
+
+                let response = format!(
+                    "HTTP/1.1 {} X\r\nContent-Type: application/json\r\n\
+                     Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+            requests
+        });
+
+        (base_url, server)
+    }
+
+    fn header_of<'a>(head: &'a str, header: &str) -> Option<&'a str> {
+        head.lines().find_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            name.eq_ignore_ascii_case(header).then(|| value.trim())
+        })
+    }
+
+    const CONFIRM_BODY: &str = r#"{"item":{"id":"itm_1","title":"Doc"},"job":{"id":"job_1","type":"EXTRACT","status":"PENDING"}}"#;
+
+    #[tokio::test]
+    async fn test_confirm_retry_reuses_idempotency_key() {
+        let (base_url, server) =
+            serve_responses(vec![(503, r#"{"error":"busy"}"#), (200, CONFIRM_BODY)]).await;
+        let client = client_with_base_url(&base_url);
+
+        let key = new_idempotency_key();
+        let metadata = MetadataUpdate::default();
+        let confirm = retry_transient(RETRY_ATTEMPTS, Duration::from_millis(1), || {
+            client.confirm_upload("itm_1", "uploads/itm_1", &metadata, Some(&key))
+        })
+        .await
+        .unwrap();
+        assert_eq!(confirm.item.id, "itm_1");
+
+        let heads = server.await.unwrap();
+        assert_eq!(heads.len(), 2);
+        for (head, _) in &heads {
+            assert_eq!(header_of(head, IDEMPOTENCY_KEY_HEADER), Some(key.as_str()));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_every_request_carries_the_trace_id() {
+        let body = r#"{"id":"u","email":"a@b.c","name":null,"tier":"free","itemLimit":10,"itemCount":1}"#;
+        let (base_url, server) = serve_responses(vec![(200, body), (200, body)]).await;
+        let client = client_with_base_url(&base_url);
+
+        client.whoami().await.unwrap();
+        client.whoami().await.unwrap();
+        for (head, _) in &server.await.unwrap() {
+            assert_eq!(header_of(head, TRACE_ID_HEADER), Some("trace_test"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_request_sends_auth_and_json_body() {
+        let (base_url, server) = serve_responses(vec![(200, r#"{"ok":true}"#), (200, "{}")]).await;
+        let client = client_with_base_url(&base_url);
+
+        let body = serde_json::json!({"ids": ["itm_1"]});
+        let reply: serde_json::Value =
+            client.request(reqwest::Method::DELETE, "/items", Some(&body)).await.unwrap();
+        assert_eq!(reply, serde_json::json!({"ok": true}));
+        let _: serde_json::Value =
+            client.request(reqwest::Method::GET, "/items", NO_BODY).await.unwrap();
+
+        let requests = server.await.unwrap();
+        let (head, sent) = &requests[0];
+        assert!(head.starts_with("DELETE /api/v1/items "), "{}", head);
+        assert_eq!(header_of(head, "authorization"), Some("Bearer ck_test"));
+        assert_eq!(header_of(head, "content-type"), Some("application/json"));
+        assert_eq!(sent, r#"{"ids":["itm_1"]}"#);
+
+        let (head, sent) = &requests[1];
+        assert!(head.starts_with("GET /api/v1/items "), "{}", head);
+        assert_eq!(header_of(head, "content-type"), None);
+        assert!(sent.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_complete_session_sends_the_summary_only_when_given() {
+        let body = r#"{"sessionId":"ses_1","status":"COMPLETED"}"#;
+        let (base_url, server) = serve_responses(vec![(200, body), (200, body)]).await;
+        let client = client_with_base_url(&base_url);
+
+        client.complete_session("ses_1", Some("Found it")).await.unwrap();
+        client.complete_session("ses_1", None).await.unwrap();
+
+        let requests = server.await.unwrap();
+        assert_eq!(requests[0].1, r#"{"sessionId":"ses_1","summary":"Found it"}"#);
+        assert_eq!(requests[1].1, r#"{"sessionId":"ses_1"}"#);
+    }
+
+    #[tokio::test]
+    async fn test_request_turns_error_statuses_into_api_errors() {
+        let (base_url, server) =
+            serve_responses(vec![(404, r#"{"error":"Item not found"}"#), (500, "oops")]).await;
+        let client = client_with_base_url(&base_url);
+
+        let err = client.get_content("itm_1").await.unwrap_err();
+        assert_eq!(error_status(&err), Some(StatusCode::NOT_FOUND));
+        assert_eq!(err.to_string(), "Not found: Item not found");
+
+        // A body without an error message falls back to the status
+        let err = client.list_items().await.unwrap_err();
+        assert_eq!(error_status(&err), Some(StatusCode::INTERNAL_SERVER_ERROR));
+        assert!(err.to_string().ends_with("HTTP 500 Internal Server Error"), "{}", err);
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_put_content_retry_writes_the_content() {
+        let body = r#"{"id":"itm_1","title":"Doc","version":2,"pageCount":3,"updatedAt":"x"}"#;
+        let (base_url, server) =
+            serve_responses(vec![(502, r#"{"error":"bad gateway"}"#), (200, body)]).await;
+        let client = client_with_base_url(&base_url);
+
+        let mut retries = Vec::new();
+        let response = retry_transient_with(
+            RETRY_ATTEMPTS,
+            Duration::from_millis(1),
+            |attempt, _| retries.push(attempt),
+            || client.put_content("itm_1", "# Big document"),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.version, 2);
+        assert_eq!(retries, vec![2]);
+
+        let requests = server.await.unwrap();
+        assert_eq!(requests.len(), 2);
+        for (head, body) in &requests {
+            assert!(head.starts_with("PUT /api/v1/items/itm_1/content "), "{}", head);
+            assert_eq!(body, r##"{"content":"# Big document"}"##);
+        }
+    }
+
+    #[test]
+    fn test_scrub_secrets_and_snippet() {
+        let text = "key ck_secret12345 and ck_abcdefghij, auth: Bearer tok.en-1 in\n<html>";
+        assert_eq!(
+            scrub_secrets(text, "ck_secret12345"),
+            "key ck_**** and ck_****, auth: Bearer **** in\n<html>"
+        );
+
+        assert_eq!(body_snippet("  <html>\n  <body>  "), r#""<html> <body>""#);
+        assert_eq!(body_snippet(""), "(empty)");
+        let long = "x".repeat(BODY_SNIPPET_CHARS + 10);
+        assert!(body_snippet(&long).ends_with("…\""));
+    }
+
+    #[tokio::test]
+    async fn test_unparseable_response_quotes_the_body() {
+        let page = "<!DOCTYPE html><p>Gateway</p>";
+        let (base_url, server) = serve_responses(vec![(200, page)]).await;
+        let client = client_with_base_url(&base_url);
+
+        let err = client.list_items().await.unwrap_err();
+        let message = format!("{:#}", err);
+        assert!(message.contains("/api/v1/items"), "{}", message);
+        assert!(message.contains(page), "{}", message);
+        assert!(message.contains("an HTML page"), "{}", message);
+        assert!(message.contains("line 1 column 1"), "{}", message);
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_oversized_response_is_refused() {
+        let body = r#"{"items":[],"enrichmentQueue":[],"padding":"0123456789012345678901234567890123456789"}"#;
+        let (base_url, server) = serve_responses(vec![(200, body)]).await;
+        let mut client = client_with_base_url(&base_url);
+        client.max_response_size = 32;
+
+        let err = client.list_items().await.unwrap_err();
+        assert!(err.to_string().contains("Response too large"), "{:#}", err);
+        assert!(err.to_string().contains("32 bytes"), "{:#}", err);
+        server.await.unwrap();
+    }
+
+    #[test]
+    fn test_parse_trace_id() {
+        assert_eq!(parse_trace_id("req-42").unwrap(), "req-42");
+        assert!(parse_trace_id("").is_err());
+        assert!(parse_trace_id("has space").is_err());
+        assert_eq!(new_trace_id().len(), 32);
+    }
+
+    #[tokio::test]
+    async fn test_retry_gives_up_on_client_errors() {
+        let (base_url, server) = serve_responses(vec![(400, r#"{"error":"bad key"}"#)]).await;
+        let client = client_with_base_url(&base_url);
+
+        let metadata = MetadataUpdate::default();
+        let err = retry_transient(RETRY_ATTEMPTS, Duration::from_millis(1), || {
+            client.confirm_upload("itm_1", "uploads/itm_1", &metadata, Some("key_1"))
+        })
+        .await
+        .unwrap_err();
+        assert_eq!(error_status(&err), Some(StatusCode::BAD_REQUEST));
+        assert_eq!(server.await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_transport_errors_tell_unreachable_from_slow() {
+        // Nothing listens on the port once the listener is dropped
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let closed = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+        let err = client_with_base_url(&closed).whoami().await.unwrap_err();
+        assert!(err.to_string().starts_with("Couldn't reach 127.0.0.1"), "{}", err);
+        assert!(is_transient_error(&err));
+
+        // Accepts the connection but never answers
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let silent = format!("http://{}", listener.local_addr().unwrap());
+        let _server = tokio::spawn(async move {
+            let (_socket, _) = listener.accept().await.unwrap();
+            std::future::pending::<()>().await;
+        });
+        let mut client = client_with_base_url(&silent);
+        client.client = Client::builder().timeout(Duration::from_millis(200)).build().unwrap();
+        let err = client.whoami().await.unwrap_err();
+        assert!(
+            err.to_string().starts_with("Timed out waiting for a response from 127.0.0.1"),
+            "{}",
+            err
+        );
     }
 }
@@ -1,18 +1,26 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::path::Path;
 
-use crate::api::ApiClient;
+use crate::api::{ApiClient, ClientOptions, SessionCompleteResponse};
+use crate::output::{self, OutputFormat};
+use crate::prompt;
+
+/// Report a tracking failure as a structured `{error: ...}` object
+fn print_error_object(message: &str, format: OutputFormat) {
+    let _ = output::print_serialized(&serde_json::json!({ "error": message }), format);
+}
 
 pub async fn start(
     intent: Option<&str>,
-    json: bool,
-    session: Option<String>,
-    no_session: bool,
+    format: OutputFormat,
+    opts: &ClientOptions,
 ) -> Result<()> {
-    let client = match ApiClient::new(session, no_session) {
+    let client = match ApiClient::new(opts) {
         Ok(c) => c,
         Err(e) => {
-            if json {
-                println!("{{\"error\":\"{}\"}}", e);
+            if format.is_structured() {
+                print_error_object(&e.to_string(), format);
             } else {
                 eprintln!("Warning: {}", e);
             }
@@ -22,21 +30,22 @@ pub async fn start(
 
     match client.create_session(intent).await {
         Ok(resp) => {
+            crate::interrupt::track_session(&resp.session_id);
             // Write session ID to file
-            if let Err(e) = ApiClient::write_session_file(&resp.session_id) {
+            if let Err(e) = ApiClient::write_session_file(&resp.session_id, opts) {
                 eprintln!("Warning: Failed to write session file: {}", e);
             }
 
-            if json {
-                println!("{}", serde_json::to_string_pretty(&resp)?);
+            if format.is_structured() {
+                output::print_serialized(&resp, format)?;
             } else {
                 println!("Session started: {}", resp.session_id);
             }
         }
         Err(e) => {
             // Tracking failure must NEVER block research
-            if json {
-                println!("{{\"error\":\"{}\"}}", e);
+            if format.is_structured() {
+                print_error_object(&e.to_string(), format);
             } else {
                 eprintln!("Warning: Failed to start session (continuing without tracking)");
                 eprintln!("  {}", e);
@@ -47,20 +56,56 @@ pub async fn start(
     Ok(())
 }
 
+/// The `--summary` text as given (stdin for `-`), or `--summary-file`'s
+/// contents; `None` when neither was given or it's blank
+fn read_summary(summary: Option<&str>, summary_file: Option<&Path>) -> Result<Option<String>> {
+    let text = match (summary, summary_file) {
+        (Some("-"), _) => prompt::read_stdin("stdin is a terminal; pipe the summary in")
+            .context("Failed to read the summary from stdin")?,
+        (Some(text), _) => text.to_string(),
+        (None, Some(path)) => std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?,
+        (None, None) => return Ok(None),
+    };
+    let text = text.trim();
+    Ok((!text.is_empty()).then(|| text.to_string()))
+}
+
 pub async fn complete(
-    json: bool,
-    session: Option<String>,
-    no_session: bool,
+    summary: Option<&str>,
+    summary_file: Option<&Path>,
+    format: OutputFormat,
+    opts: &ClientOptions,
 ) -> Result<()> {
-    // Resolve session ID: --session flag > file
-    let session_id = if let Some(ref s) = session {
+    #[derive(Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Completed<'a> {
+        #[serde(flatten)]
+        response: &'a SessionCompleteResponse,
+        /// Only with --summary/--summary-file
+        #[serde(skip_serializing_if = "Option::is_none")]
+        summary_attached: Option<bool>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        summary_error: Option<String>,
+    }
+
+    // A summary that can't be read or attached never stops the completion
+    let wants_summary = summary.is_some() || summary_file.is_some();
+    let mut summary_error = None;
+    let summary = read_summary(summary, summary_file).unwrap_or_else(|e| {
+        summary_error = Some(format!("{:#}", e));
+        None
+    });
+
+    // Resolve session ID: --session flag > file (--session-file or the default)
+    let session_id = if let Some(ref s) = opts.session {
         s.clone()
     } else {
-        match ApiClient::read_session_file() {
+        match ApiClient::read_session_file(opts) {
             Some(s) => s,
             None => {
-                if json {
-                    println!("{{\"error\":\"No active session\"}}");
+                if format.is_structured() {
+                    print_error_object("No active session", format);
                 } else {
                     eprintln!("No active session found");
                 }
@@ -69,11 +114,11 @@ pub async fn complete(
         }
     };
 
-    let client = match ApiClient::new(session, no_session) {
+    let client = match ApiClient::new(opts) {
         Ok(c) => c,
         Err(e) => {
-            if json {
-                println!("{{\"error\":\"{}\"}}", e);
+            if format.is_structured() {
+                print_error_object(&e.to_string(), format);
             } else {
                 eprintln!("Warning: {}", e);
             }
@@ -81,23 +126,39 @@ pub async fn complete(
         }
     };
 
-    match client.complete_session(&session_id).await {
+    let mut result = client.complete_session(&session_id, summary.as_deref()).await;
+    if let (Err(e), Some(_)) = (&result, &summary) {
+        // The server may not take the summary; complete without it
+        summary_error = Some(e.to_string());
+        result = client.complete_session(&session_id, None).await;
+    }
+
+    match result {
         Ok(resp) => {
             // Delete session file
-            ApiClient::delete_session_file();
+            ApiClient::delete_session_file(opts);
 
-            if json {
-                println!("{}", serde_json::to_string_pretty(&resp)?);
+            if format.is_structured() {
+                let completed = Completed {
+                    response: &resp,
+                    summary_attached: wants_summary
+                        .then_some(summary.is_some() && summary_error.is_none()),
+                    summary_error,
+                };
+                output::print_serialized(&completed, format)?;
             } else {
+                if let Some(ref e) = summary_error {
+                    eprintln!("Warning: Failed to attach the summary: {}", e);
+                }
                 println!("Session completed: {}", resp.session_id);
             }
         }
         Err(e) => {
             // Still delete the file on failure
-            ApiClient::delete_session_file();
+            ApiClient::delete_session_file(opts);
 
-            if json {
-                println!("{{\"error\":\"{}\"}}", e);
+            if format.is_structured() {
+                print_error_object(&e.to_string(), format);
             } else {
                 eprintln!("Warning: Failed to complete session: {}", e);
             }
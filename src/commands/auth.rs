@@ -1,26 +1,99 @@
 use anyhow::{Context, Result};
+use clap::ValueEnum;
 use colored::Colorize;
-use std::io::{self, BufRead, BufReader, Write};
+use reqwest::StatusCode;
+use serde::Serialize;
+use std::io::{self, BufRead, BufReader, IsTerminal, Write};
 use std::net::TcpListener;
+use std::time::Duration;
+
+use crate::api::{self, ApiClient, ClientOptions, WhoamiResponse};
+use crate::config::{self, ClientSummary};
+use crate::output::{self, OutputFormat};
+use crate::pager;
+use crate::prompt;
+use crate::request_log;
+use crate::spinner;
+
+/// How long `auth login` waits for the browser to call back
+const CALLBACK_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// How often the callback listener checks for a connection
+const CALLBACK_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Environment variables reported by `auth whoami --client` when set
+const OVERRIDE_ENV_VARS: &[&str] = &[
+    config::API_URL_ENV,
+    request_log::LOG_FILE_ENV,
+    prompt::NON_INTERACTIVE_ENV,
+    pager::NO_PAGER_ENV,
+];
+
+/// The CLI's version, where it sends requests, and where its settings come from
+fn client_summary(opts: &ClientOptions) -> Result<ClientSummary> {
+    let config = config::load_config()?;
+    Ok(ClientSummary {
+        cli_version: env!("CARGO_PKG_VERSION"),
+        api_url: config::resolve_api_url(opts.base_url.as_deref())?,
+        api_version: config::resolve_api_version(opts.api_version.as_deref())?,
+        profile: config.selected_profile_name().unwrap_or(config::DEFAULT_PROFILE).to_string(),
+        config_storage: "file",
+        config_path: config::config_path()?.display().to_string(),
+        env_overrides: OVERRIDE_ENV_VARS
+            .iter()
+            .copied()
+            .filter(|var| std::env::var_os(var).is_some_and(|v| !v.is_empty()))
+            .collect(),
+    })
+}
+
+/// Profile field printed by `auth whoami --raw`
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum WhoamiField {
+    Email,
+    Name,
+    Tier,
+    Id,
+    #[value(name = "item_count")]
+    ItemCount,
+    #[value(name = "item_limit")]
+    ItemLimit,
+}
 
-use crate::api::ApiClient;
-use crate::config;
-use crate::output;
+impl WhoamiField {
+    /// The field's value, or None when the server didn't set it
+    fn value(self, user: &WhoamiResponse) -> Option<String> {
+        match self {
+            WhoamiField::Email => Some(user.email.clone()),
+            WhoamiField::Name => user.name.clone(),
+            WhoamiField::Tier => Some(user.tier.clone()),
+            WhoamiField::Id => Some(user.id.clone()),
+            WhoamiField::ItemCount => Some(user.item_count.to_string()),
+            WhoamiField::ItemLimit => Some(user.item_limit.to_string()),
+        }
+    }
+}
 
 /// Login via browser authentication
-pub async fn login(session: Option<String>, no_session: bool) -> Result<()> {
-    let _ = (&session, no_session); // session not needed for login flow itself
+pub async fn login(opts: &ClientOptions) -> Result<()> {
     // Check if already authenticated
     if config::is_authenticated() {
         output::print_warning("Already logged in. Use 'ck auth logout' first to re-authenticate.");
         return Ok(());
     }
+    // Browser login needs a person to sign in
+    if prompt::non_interactive() {
+        return Err(anyhow::anyhow!(
+            "Browser login isn't available in non-interactive mode; set an API key with \
+             'ck config set auth.api_key <key>' (or \"${{VAR}}\" to read it from the environment)"
+        ));
+    }
 
     // Bind to a random available port
     let listener = TcpListener::bind("127.0.0.1:0").context("Failed to start local server")?;
     let port = listener.local_addr()?.port();
 
-    let api_url = config::get_api_url()?;
+    let api_url = config::resolve_api_url(opts.base_url.as_deref())?;
     let auth_url = format!("{}/cli-auth?port={}", api_url, port);
 
     println!("{}", "Opening browser for authentication...".cyan());
@@ -43,23 +116,38 @@ pub async fn login(session: Option<String>, no_session: bool) -> Result<()> {
             // Fallback to manual key entry
             println!("\n{}", "Browser authentication failed.".yellow());
             println!("{}", e);
-            return manual_key_entry().await;
+            return manual_key_entry(opts).await;
         }
     };
 
     // Validate the key
-    validate_and_save_key(&api_key).await
+    validate_and_save_key(&api_key, opts).await
 }
 
 async fn wait_for_callback(listener: &TcpListener) -> Result<String> {
-    // Set timeout for accepting connections
-    listener.set_nonblocking(false)?;
+    // Poll for the connection so we can give up after CALLBACK_TIMEOUT
+    listener.set_nonblocking(true)?;
 
     // Use a thread to handle the TCP listener since it's blocking
     let listener_clone = listener.try_clone()?;
     let handle = std::thread::spawn(move || -> Result<String> {
-        // Accept connection with timeout (60 seconds)
-        let (mut stream, _) = listener_clone.accept()?;
+        let deadline = std::time::Instant::now() + CALLBACK_TIMEOUT;
+        let mut stream = loop {
+            match listener_clone.accept() {
+                Ok((stream, _)) => break stream,
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                    if std::time::Instant::now() >= deadline {
+                        return Err(anyhow::anyhow!(
+                            "No callback from the browser within {} seconds",
+                            CALLBACK_TIMEOUT.as_secs()
+                        ));
+                    }
+                    std::thread::sleep(CALLBACK_POLL_INTERVAL);
+                }
+                Err(e) => return Err(e.into()),
+            }
+        };
+        stream.set_nonblocking(false)?;
 
         // Read the request
         let mut reader = BufReader::new(&stream);
@@ -115,39 +203,133 @@ Connection: close
     }
 }
 
-async fn manual_key_entry() -> Result<()> {
+async fn manual_key_entry(opts: &ClientOptions) -> Result<()> {
     println!("\nTo authenticate manually:");
-    println!("1. Go to {} and log in", config::get_api_url()?.underline());
+    println!(
+        "1. Go to {} and log in",
+        config::resolve_api_url(opts.base_url.as_deref())?.underline()
+    );
     println!("2. Navigate to Settings > API Keys");
     println!("3. Create a new API key and copy it");
     println!();
 
-    print!("Enter your API key: ");
-    io::stdout().flush()?;
-
-    let mut api_key = String::new();
-    io::stdin().read_line(&mut api_key)?;
-    let api_key = api_key.trim().to_string();
+    let api_key = prompt::read_line(
+        "Enter your API key: ",
+        "rerun 'ck auth login' interactively to enter a key by hand",
+    )?;
+    let api_key = normalize_api_key(&api_key)?;
 
     if api_key.is_empty() {
         return Err(anyhow::anyhow!("No API key provided"));
     }
 
-    validate_and_save_key(&api_key).await
+    validate_and_save_key(&api_key, opts).await
 }
 
-async fn validate_and_save_key(api_key: &str) -> Result<()> {
-    print!("{}", "Validating API key...".dimmed());
-    io::stdout().flush()?;
+const API_KEY_PREFIX: &str = "ck_";
+const API_KEY_MIN_SECRET_LEN: usize = 8;
+
+/// Clean up a pasted API key: trim whitespace and strip one pair of
+/// surrounding single or double quotes (as copied from password managers).
+/// Whitespace inside the key is rejected rather than silently removed.
+fn normalize_api_key(raw: &str) -> Result<String> {
+    let trimmed = raw.trim();
+    let unquoted = ['"', '\'']
+        .iter()
+        .find_map(|&q| {
+            trimmed
+                .strip_prefix(q)
+                .and_then(|rest| rest.strip_suffix(q))
+        })
+        .unwrap_or(trimmed)
+        .trim();
+
+    if unquoted.chars().any(char::is_whitespace) {
+        return Err(anyhow::anyhow!(
+            "API key contains whitespace. Make sure you pasted the whole key on a single line."
+        ));
+    }
+
+    Ok(unquoted.to_string())
+}
+
+/// Cheap client-side check that a key has the shape of a CandleKeep API key.
+/// The server remains the authority on whether a well-formed key is valid.
+pub fn validate_api_key_format(api_key: &str) -> Result<()> {
+    let reason = if api_key.is_empty() {
+        Some("it is empty")
+    } else if !api_key.starts_with(API_KEY_PREFIX) {
+        Some("it should start with 'ck_'")
+    } else if api_key.len() < API_KEY_PREFIX.len() + API_KEY_MIN_SECRET_LEN {
+        Some("it is too short")
+    } else if !api_key
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        Some("it contains characters other than letters, digits, '_' and '-'")
+    } else {
+        None
+    };
+
+    match reason {
+        Some(reason) => Err(anyhow::anyhow!(
+            "That doesn't look like a CandleKeep API key ({}). Copy it from Settings > API Keys.",
+            reason
+        )),
+        None => Ok(()),
+    }
+}
+
+async fn validate_and_save_key(api_key: &str, opts: &ClientOptions) -> Result<()> {
+    let api_key = &normalize_api_key(api_key)?;
+    validate_api_key_format(api_key)?;
 
     // Validate the key by calling whoami (no session needed for validation)
-    let client = ApiClient::with_key(api_key, None, true)?;
-    let user = client.whoami().await.context("Invalid API key")?;
+    let validation_opts = ClientOptions {
+        session: None,
+        no_session: true,
+        ..opts.clone()
+    };
+    let client = ApiClient::with_key(api_key, &validation_opts)?;
+
+    // The key is already in hand, so a network hiccup shouldn't send the user
+    // back through the browser flow: retry, then offer to try again.
+    let user = loop {
+        print!("{}", "Validating API key...".dimmed());
+        io::stdout().flush()?;
+
+        let whoami = || client.whoami();
+        match api::retry_transient(api::RETRY_ATTEMPTS, api::RETRY_DELAY, whoami).await {
+            Ok(user) => break user,
+            Err(e) if api::is_transient_error(&e) => {
+                println!(" {}", "FAILED".red());
+                output::print_error(&format!(
+                    "Couldn't reach the CandleKeep server, check your connection: {:#}",
+                    e
+                ));
+                if !prompt::interactive()
+                    || !prompt::confirm("Retry validating the same key?", "retry")?
+                {
+                    return Err(e.context("Could not validate API key"));
+                }
+            }
+            Err(e) => {
+                println!(" {}", "FAILED".red());
+                return Err(match api::error_status(&e) {
+                    Some(StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN) => {
+                        e.context("API key rejected by the server")
+                    }
+                    _ => e.context("Could not validate API key"),
+                });
+            }
+        }
+    };
 
     println!(" {}", "OK".green());
 
     // Save the key
     config::save_api_key(api_key)?;
+    cache_profile(&user)?;
 
     output::print_success(&format!(
         "Logged in as {} ({})",
@@ -158,6 +340,58 @@ async fn validate_and_save_key(api_key: &str) -> Result<()> {
     Ok(())
 }
 
+/// Rotate the stored API key, or revalidate it if the server can't rotate keys
+pub async fn refresh(opts: &ClientOptions) -> Result<()> {
+    let client = ApiClient::new(opts)?;
+
+    let revoked = || {
+        anyhow::anyhow!(
+            "Your API key was revoked or has expired and can't be refreshed. \
+             Run 'ck auth logout' and then 'ck auth login'."
+        )
+    };
+
+    match client.refresh_key().await {
+        Ok(response) => {
+            let api_key = normalize_api_key(&response.api_key)?;
+            validate_api_key_format(&api_key).context("Server returned an unexpected key")?;
+            config::save_api_key(&api_key)?;
+
+            match response.expires_at {
+                Some(at) => output::print_success(&format!("API key refreshed (expires {})", at)),
+                None => output::print_success("API key refreshed."),
+            }
+            Ok(())
+        }
+        Err(e) => match api::error_status(&e) {
+            Some(StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN) => Err(revoked()),
+            // Older servers have no refresh endpoint: just check the key still works
+            Some(
+                StatusCode::NOT_FOUND
+                | StatusCode::METHOD_NOT_ALLOWED
+                | StatusCode::NOT_IMPLEMENTED,
+            ) => {
+                match client.whoami().await {
+                    Ok(user) => {
+                        cache_profile(&user)?;
+                        output::print_info("This server doesn't support key rotation.");
+                        output::print_success(&format!(
+                            "Current API key is valid ({})",
+                            user.email.cyan()
+                        ));
+                        Ok(())
+                    }
+                    Err(e) => match api::error_status(&e) {
+                        Some(StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN) => Err(revoked()),
+                        _ => Err(e.context("Could not validate API key")),
+                    },
+                }
+            }
+            _ => Err(e.context("Could not refresh API key")),
+        },
+    }
+}
+
 /// Logout - remove stored credentials
 pub fn logout() -> Result<()> {
     if !config::is_authenticated() {
@@ -170,16 +404,325 @@ pub fn logout() -> Result<()> {
     Ok(())
 }
 
-/// Show current user information
-pub async fn whoami(json: bool, session: Option<String>, no_session: bool) -> Result<()> {
-    let client = ApiClient::new(session, no_session)?;
+/// Remember `user` in the config so identity can be shown offline
+fn cache_profile(user: &WhoamiResponse) -> Result<()> {
+    let mut config = config::load_config()?;
+    let cache = config.cached_user_mut();
+    cache.cached_user_id = Some(user.id.clone());
+    cache.cached_email = Some(user.email.clone());
+    cache.cached_name = user.name.clone();
+    cache.cached_tier = Some(user.tier.clone());
+    cache.cached_item_limit = Some(user.item_limit);
+    cache.cached_item_count = Some(user.item_count);
+    cache.cached_at = Some(chrono::Utc::now().to_rfc3339());
+    config::save_config(&config)
+}
+
+/// The profile cached by the last successful whoami, with when it was fetched
+fn cached_profile() -> Result<Option<(WhoamiResponse, String)>> {
+    let config = config::load_config()?;
+    let Some(cache) = config.cached_user() else {
+        return Ok(None);
+    };
+    let (Some(id), Some(email), Some(tier)) =
+        (&cache.cached_user_id, &cache.cached_email, &cache.cached_tier)
+    else {
+        return Ok(None);
+    };
+
+    let user = WhoamiResponse {
+        id: id.clone(),
+        email: email.clone(),
+        name: cache.cached_name.clone(),
+        tier: tier.clone(),
+        item_limit: cache.cached_item_limit.unwrap_or(0),
+        item_count: cache.cached_item_count.unwrap_or(0),
+    };
+    Ok(Some((user, cache.cached_at.clone().unwrap_or_default())))
+}
+
+/// Fetch the live profile and refresh the cache
+async fn fetch_profile(opts: &ClientOptions) -> Result<WhoamiResponse> {
+    let client = ApiClient::new(opts)?;
     let user = client.whoami().await?;
+    cache_profile(&user)?;
+    Ok(user)
+}
 
-    if json {
-        output::print_whoami_json(&user);
+/// Show current user information, live or (with `cached`) from the last fetch
+pub async fn whoami(
+    cached: bool,
+    raw: Option<WhoamiField>,
+    check_limit: Option<LimitCheck>,
+    client: bool,
+    format: OutputFormat,
+    opts: &ClientOptions,
+) -> Result<()> {
+    let user = if cached {
+        let (user, cached_at) = cached_profile()?.context(
+            "No cached profile. Run 'ck auth whoami' while online to fetch it.",
+        )?;
+        if !format.is_structured() && raw.is_none() {
+            output::print_info(&format!("Cached profile from {}", cached_at));
+        }
+        user
+    } else {
+        spinner::with_spinner("Fetching profile...", format, fetch_profile(opts)).await?
+    };
+    let at_limit = check_limit.map(|check| check.reached(&user));
+    let client = client.then(|| client_summary(opts)).transpose()?;
+
+    if let Some(field) = raw {
+        let value = field.value(&user).with_context(|| {
+            format!(
+                "Field '{}' is not set for this account",
+                field.to_possible_value().map(|v| v.get_name().to_string()).unwrap_or_default()
+            )
+        })?;
+        println!("{}", value);
+    } else if format.is_structured() {
+        #[derive(Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Profile<'a> {
+            #[serde(flatten)]
+            user: &'a WhoamiResponse,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            at_limit: Option<bool>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            client: Option<ClientSummary>,
+        }
+        output::print_serialized(&Profile { user: &user, at_limit, client }, format)?;
     } else {
         output::print_whoami(&user);
+        if let Some(ref client) = client {
+            output::print_client_summary(client);
+        }
+    }
+
+    if let (Some(check), Some(true)) = (check_limit, at_limit) {
+        return Err(anyhow::anyhow!(check.message(&user)));
+    }
+    Ok(())
+}
+
+/// `auth whoami --watch`: poll every `interval` until the tier or item limit
+/// changes, failing after `timeout` if given. On a terminal the profile
+/// table is redrawn in place; `--json`/`--yaml` print one record per poll.
+pub async fn watch(
+    interval: Duration,
+    timeout: Option<Duration>,
+    format: OutputFormat,
+    opts: &ClientOptions,
+) -> Result<()> {
+    #[derive(Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Poll<'a> {
+        #[serde(flatten)]
+        user: &'a WhoamiResponse,
+        changed: bool,
+    }
+
+    let redraw = !format.is_structured() && io::stdout().is_terminal();
+    let started = std::time::Instant::now();
+    let mut user = fetch_profile(opts).await?;
+    let (tier, item_limit) = (user.tier.clone(), user.item_limit);
+    let mut shown = false;
+
+    loop {
+        let changed = user.tier != tier || user.item_limit != item_limit;
+        if format.is_structured() {
+            output::print_event(&Poll { user: &user, changed }, format)?;
+        } else if redraw || changed || !shown {
+            shown = true;
+            if redraw {
+                // Clear the screen and move the cursor home
+                print!("\x1b[2J\x1b[H");
+            }
+            output::print_whoami(&user);
+        }
+
+        if changed {
+            if !format.is_structured() {
+                if user.tier != tier {
+                    output::print_success(&format!("Tier changed: {} → {}", tier, user.tier));
+                }
+                if user.item_limit != item_limit {
+                    output::print_success(&format!(
+                        "Item limit changed: {} → {}",
+                        item_limit, user.item_limit
+                    ));
+                }
+            }
+            return Ok(());
+        }
+
+        let mut wait = interval;
+        if let Some(timeout) = timeout {
+            match timeout.checked_sub(started.elapsed()).filter(|left| !left.is_zero()) {
+                Some(left) => wait = wait.min(left),
+                None => {
+                    return Err(anyhow::anyhow!(
+                        "Tier and item limit unchanged after {}s",
+                        timeout.as_secs()
+                    ))
+                }
+            }
+        }
+        if redraw {
+            println!(
+                "{}",
+                format!(
+                    "Watching for tier or item limit changes every {}s (Ctrl-C to stop)",
+                    interval.as_secs()
+                )
+                .dimmed()
+            );
+        }
+        tokio::time::sleep(wait).await;
+        user = fetch_profile(opts).await?;
+    }
+}
+
+/// `--check-limit`, optionally with `--threshold`: fail once the library is
+/// full, or once usage reaches `threshold` of the limit
+#[derive(Debug, Clone, Copy)]
+pub struct LimitCheck {
+    pub threshold: Option<f64>,
+}
+
+impl LimitCheck {
+    fn reached(&self, user: &WhoamiResponse) -> bool {
+        match self.threshold {
+            Some(threshold) => user.near_limit(threshold),
+            None => user.at_limit(),
+        }
+    }
+
+    fn message(&self, user: &WhoamiResponse) -> String {
+        match self.threshold {
+            Some(threshold) if !user.at_limit() => format!(
+                "Near item limit ({}/{} items, threshold {:.0}%)",
+                user.item_count,
+                user.item_limit,
+                threshold * 100.0
+            ),
+            _ => format!("At item limit ({}/{} items)", user.item_count, user.item_limit),
+        }
+    }
+}
+
+/// Show whether you're logged in, from the cached profile unless `refresh`
+pub async fn status(refresh: bool, format: OutputFormat, opts: &ClientOptions) -> Result<()> {
+    #[derive(Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Status {
+        logged_in: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        profile: Option<WhoamiResponse>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        cached_at: Option<String>,
+    }
+
+    let status = if !config::is_authenticated() {
+        Status {
+            logged_in: false,
+            profile: None,
+            cached_at: None,
+        }
+    } else if refresh {
+        Status {
+            logged_in: true,
+            profile: Some(fetch_profile(opts).await?),
+            cached_at: None,
+        }
+    } else {
+        let cached = cached_profile()?;
+        Status {
+            logged_in: true,
+            cached_at: cached.as_ref().map(|(_, at)| at.clone()),
+            profile: cached.map(|(user, _)| user),
+        }
+    };
+
+    if format.is_structured() {
+        return output::print_serialized(&status, format);
+    }
+
+    match (&status.profile, status.logged_in) {
+        (_, false) => output::print_warning("Not logged in. Run 'ck auth login'."),
+        (Some(user), true) => output::print_success(&format!(
+            "Logged in as {} ({}){}",
+            user.email.cyan(),
+            user.tier,
+            status
+                .cached_at
+                .map(|at| format!(", cached {}", output::short_date(&at)).dimmed().to_string())
+                .unwrap_or_default()
+        )),
+        (None, true) => output::print_success(
+            "Logged in (profile not cached yet; run 'ck auth status --refresh')",
+        ),
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limit_check_threshold() {
+        let user = WhoamiResponse::with_usage;
+        let full = LimitCheck { threshold: None };
+        let near = LimitCheck { threshold: Some(0.9) };
+
+        assert!(!full.reached(&user(9, 10)));
+        assert!(near.reached(&user(9, 10)));
+        assert_eq!(near.message(&user(9, 10)), "Near item limit (9/10 items, threshold 90%)");
+        assert_eq!(near.message(&user(10, 10)), "At item limit (10/10 items)");
+    }
+
+    #[test]
+    fn test_normalize_api_key() {
+        assert_eq!(normalize_api_key("ck_abcdef12\n").unwrap(), "ck_abcdef12");
+        assert_eq!(normalize_api_key("  ck_abcdef12  ").unwrap(), "ck_abcdef12");
+        assert_eq!(normalize_api_key("\"ck_abcdef12\"").unwrap(), "ck_abcdef12");
+        assert_eq!(normalize_api_key(" 'ck_abcdef12'\r\n").unwrap(), "ck_abcdef12");
+        // Only a matching pair is stripped
+        assert_eq!(normalize_api_key("\"ck_abcdef12").unwrap(), "\"ck_abcdef12");
+        assert_eq!(normalize_api_key("").unwrap(), "");
+    }
+
+    #[test]
+    fn test_normalize_rejects_embedded_whitespace() {
+        assert!(normalize_api_key("ck_abc def12").is_err());
+        assert!(normalize_api_key("\"ck_abc\tdef12\"").is_err());
+    }
+
+    #[test]
+    fn test_accepts_well_formed_keys() {
+        assert!(validate_api_key_format("ck_abcdef12").is_ok());
+        assert!(validate_api_key_format("ck_AbC-123_xyz-9876543210").is_ok());
+    }
+
+    #[test]
+    fn test_rejects_malformed_keys() {
+        for key in [
+            "",
+            "abcdef123456",
+            "ck_short",
+            "https://www.getcandlekeep.com/settings",
+            "ck_abc def123",
+            "ck_abcdef12!",
+        ] {
+            let err = validate_api_key_format(key).unwrap_err();
+            assert!(
+                err.to_string().contains("doesn't look like a CandleKeep API key"),
+                "unexpected error for {:?}: {}",
+                key,
+                err
+            );
+        }
+    }
+}
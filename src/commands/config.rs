@@ -0,0 +1,145 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::path::Path;
+use std::process::Command;
+
+use crate::api::{ApiClient, ClientOptions};
+use crate::config;
+use crate::output::{self, OutputFormat};
+use crate::prompt;
+
+/// Editor used when neither `$VISUAL` nor `$EDITOR` is set
+#[cfg(windows)]
+const DEFAULT_EDITOR: &str = "notepad";
+#[cfg(not(windows))]
+const DEFAULT_EDITOR: &str = "vi";
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ConfigPaths {
+    config_dir: String,
+    config_file: String,
+    session_file: String,
+}
+
+/// Show the effective configuration
+pub fn show(format: OutputFormat) -> Result<()> {
+    let values = config::effective_values()?;
+
+    if format.is_structured() {
+        output::print_serialized(&values, format)?;
+    } else {
+        output::print_config(&values);
+    }
+
+    Ok(())
+}
+
+/// Print the locations of the files the CLI reads and writes
+pub fn path(format: OutputFormat, opts: &ClientOptions) -> Result<()> {
+    let paths = ConfigPaths {
+        config_dir: config::config_dir()?.display().to_string(),
+        config_file: config::config_path()?.display().to_string(),
+        session_file: ApiClient::session_file_path(opts)?.display().to_string(),
+    };
+
+    if format.is_structured() {
+        output::print_serialized(&paths, format)?;
+    } else {
+        println!("Config dir:   {}", paths.config_dir);
+        println!("Config file:  {}", paths.config_file);
+        println!("Session file: {}", paths.session_file);
+    }
+
+    Ok(())
+}
+
+/// Set a single config value by dotted key (e.g. `api.url`)
+pub fn set(key: &str, value: &str) -> Result<()> {
+    let mut cfg = config::load_config()?;
+    config::set_value(&mut cfg, key, value)?;
+    config::save_config(&cfg)?;
+
+    let shown = if key == "auth.api_key" {
+        config::redact_api_key(value)
+    } else {
+        value.to_string()
+    };
+    output::print_success(&format!("Set {} = {}", config::stored_key(&cfg, key), shown));
+
+    Ok(())
+}
+
+/// Open the config file in `$VISUAL`/`$EDITOR`, creating it from a commented
+/// template first if needed. The saved file is parsed again; if it's invalid
+/// the user can go back and fix it.
+pub fn edit() -> Result<()> {
+    if !prompt::interactive() {
+        return Err(anyhow::anyhow!(
+            "config edit needs a terminal; use 'ck config set <key> <value>' instead"
+        ));
+    }
+
+    let path = config::config_path()?;
+    if !path.exists() {
+        let dir = config::config_dir()?;
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create config directory: {}", dir.display()))?;
+        std::fs::write(&path, config::config_template())
+            .with_context(|| format!("Failed to write config file: {}", path.display()))?;
+    }
+
+    loop {
+        run_editor(&path)?;
+
+        match config::load_config() {
+            Ok(_) => {
+                output::print_success(&format!("Saved {}", path.display()));
+                return Ok(());
+            }
+            Err(e) => {
+                output::print_warning(&format!("The config file is invalid: {:#}", e));
+                if !prompt::confirm("Edit it again?", "re-open the editor")? {
+                    return Err(anyhow::anyhow!(
+                        "{} is invalid; fix it or run 'ck config edit' again",
+                        path.display()
+                    ));
+                }
+            }
+        }
+    }
+}
+
+/// Run the user's editor on `path` and wait for it to exit
+fn run_editor(path: &Path) -> Result<()> {
+    let editor = ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_EDITOR.to_string());
+
+    // Allow arguments, e.g. EDITOR="code --wait"
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().unwrap_or(DEFAULT_EDITOR);
+
+    let status = Command::new(program)
+        .args(parts)
+        .arg(path)
+        .status()
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => anyhow::anyhow!(
+                "Editor '{}' not found; set $EDITOR to the editor you use",
+                program
+            ),
+            _ => anyhow::Error::new(e).context(format!("Failed to start editor '{}'", program)),
+        })?;
+
+    if !status.success() {
+        return Err(anyhow::anyhow!(
+            "Editor '{}' exited with {}; config file left as it was saved",
+            program,
+            status
+        ));
+    }
+    Ok(())
+}
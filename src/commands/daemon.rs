@@ -0,0 +1,71 @@
+use anyhow::Result;
+use colored::Colorize;
+use std::time::Duration;
+
+use crate::api::ClientOptions;
+use crate::daemon::{self, DaemonReply, DaemonRequest};
+use crate::output::{self, OutputFormat};
+
+/// Run the daemon in the foreground until stopped, interrupted, or idle
+pub async fn start(idle_timeout_secs: u64, opts: &ClientOptions) -> Result<()> {
+    let path = daemon::socket_path()?;
+    if daemon::call(&path, &DaemonRequest::Health).await?.is_some() {
+        return Err(anyhow::anyhow!(
+            "A daemon is already running at {}; stop it with 'ck daemon stop'",
+            path.display()
+        ));
+    }
+    let idle_timeout = (idle_timeout_secs > 0).then(|| Duration::from_secs(idle_timeout_secs));
+
+    match idle_timeout {
+        Some(_) => eprintln!(
+            "Listening on {} (exits after {}s idle)",
+            path.display(),
+            idle_timeout_secs
+        ),
+        None => eprintln!("Listening on {}", path.display()),
+    }
+    eprintln!("{}", "Run commands with --daemon to use it; stop with 'ck daemon stop'".dimmed());
+    daemon::serve(&path, idle_timeout, opts).await
+}
+
+/// Print the running daemon's health; fails if none is running
+pub async fn status(format: OutputFormat) -> Result<()> {
+    let path = daemon::socket_path()?;
+    let health = match daemon::call(&path, &DaemonRequest::Health).await? {
+        Some(DaemonReply::Health(health)) => health,
+        Some(other) => return Err(anyhow::anyhow!("Unexpected reply from the daemon: {:?}", other)),
+        None => return Err(anyhow::anyhow!("No daemon running at {}", path.display())),
+    };
+
+    if format.is_structured() {
+        output::print_serialized(&health, format)?;
+    } else {
+        output::print_success(&format!("Daemon running at {}", path.display()));
+        println!("  PID:       {}", health.pid);
+        println!("  Version:   {}", health.version);
+        println!("  Uptime:    {}s", health.uptime_secs);
+        println!("  Forwarded: {} request(s)", health.requests);
+        if health.protocol != daemon::PROTOCOL_VERSION {
+            output::print_warning(&format!(
+                "It speaks protocol {} and this ck speaks {}; restart it",
+                health.protocol,
+                daemon::PROTOCOL_VERSION
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Ask the daemon to exit; fails if none is running
+pub async fn stop() -> Result<()> {
+    let path = daemon::socket_path()?;
+    match daemon::call(&path, &DaemonRequest::Shutdown).await? {
+        Some(DaemonReply::Shutdown) => {
+            output::print_success("Daemon stopped");
+            Ok(())
+        }
+        Some(other) => Err(anyhow::anyhow!("Unexpected reply from the daemon: {:?}", other)),
+        None => Err(anyhow::anyhow!("No daemon running at {}", path.display())),
+    }
+}
@@ -0,0 +1,277 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use reqwest::StatusCode;
+use serde::Serialize;
+
+use crate::api::{self, ApiClient, ClientOptions};
+use crate::commands::auth;
+use crate::config;
+use crate::output::{self, CheckResult, OutputFormat};
+
+/// Timeout for the connectivity probe when `--timeout` isn't given
+const PROBE_TIMEOUT_SECS: u64 = 10;
+/// Clock difference from the server beyond which we warn
+const MAX_CLOCK_SKEW_SECS: i64 = 300;
+/// Proxy variables reqwest picks up from the environment
+const PROXY_ENV_VARS: &[&str] = &[
+    "HTTPS_PROXY",
+    "https_proxy",
+    "HTTP_PROXY",
+    "http_proxy",
+    "ALL_PROXY",
+    "all_proxy",
+];
+
+/// Seconds the local clock is ahead of the server (negative if behind),
+/// from an HTTP `Date` header
+fn clock_skew(server_date: &str, now: DateTime<Utc>) -> Option<i64> {
+    let server = DateTime::parse_from_rfc2822(server_date).ok()?;
+    Some((now - server.with_timezone(&Utc)).num_seconds())
+}
+
+/// Run every check in order; later checks are skipped when what they need is missing
+async fn run_checks(opts: &ClientOptions) -> Vec<CheckResult> {
+    let mut checks = Vec::new();
+
+    // Config file
+    let path = config::config_path();
+    let config_ok = match &path {
+        Err(e) => {
+            checks.push(CheckResult::fail(
+                "Config file",
+                format!("{:#}", e),
+                "Set HOME to your home directory",
+            ));
+            false
+        }
+        Ok(path) if !path.exists() => {
+            checks.push(CheckResult::warn(
+                "Config file",
+                format!("No config file at {}", path.display()),
+                "Run 'ck auth login' to create it",
+            ));
+            false
+        }
+        Ok(path) => match config::load_config() {
+            Ok(_) => {
+                checks.push(CheckResult::pass("Config file", path.display().to_string()));
+                true
+            }
+            Err(e) => {
+                checks.push(CheckResult::fail(
+                    "Config file",
+                    format!("{:#}", e),
+                    format!("Fix or remove {}", path.display()),
+                ));
+                false
+            }
+        },
+    };
+
+    // API key
+    let api_key = config::get_api_key();
+    let key_ok = match api_key.as_ref().map(Option::as_ref) {
+        Err(e) => {
+            checks.push(CheckResult::fail(
+                "API key",
+                format!("{:#}", e),
+                "Export the variable, or save the key with 'ck auth login'",
+            ));
+            false
+        }
+        Ok(None) => {
+            checks.push(CheckResult::fail("API key", "No API key saved", "Run 'ck auth login'"));
+            false
+        }
+        Ok(Some(key)) => match auth::validate_api_key_format(key) {
+            Ok(()) => {
+                checks.push(CheckResult::pass("API key", config::redact_api_key(key)));
+                true
+            }
+            Err(e) => {
+                checks.push(CheckResult::fail(
+                    "API key",
+                    e.to_string(),
+                    "Run 'ck auth login' for a fresh key",
+                ));
+                false
+            }
+        },
+    };
+
+    // Credential storage: the key lives in the config file; no system keyring is used
+    match &path {
+        Ok(path) if config_ok && matches!(api_key, Ok(Some(_))) => {
+            checks.push(storage_check(path))
+        }
+        _ => checks.push(CheckResult::skip("Credential storage", "No saved key")),
+    }
+
+    // API URL
+    let api_url = match config::resolve_api_url(opts.base_url.as_deref()) {
+        Ok(url) => {
+            checks.push(CheckResult::pass("API URL", url.clone()));
+            Some(url)
+        }
+        Err(e) => {
+            checks.push(CheckResult::fail(
+                "API URL",
+                format!("{:#}", e),
+                "Fix it with 'ck config set api.url <URL>' or CANDLEKEEP_API_URL",
+            ));
+            None
+        }
+    };
+
+    // Connectivity and authentication; the response's Date header feeds the clock check
+    let mut connected = false;
+    let server_date = match (&api_url, key_ok) {
+        (Some(_), true) => {
+            let mut probe_opts = opts.clone();
+            probe_opts.timeout_secs = probe_opts.timeout_secs.or(Some(PROBE_TIMEOUT_SECS));
+
+            let result = match ApiClient::new(&probe_opts) {
+                Ok(client) => client.whoami_with_date().await,
+                Err(e) => Err(e),
+            };
+            match result {
+                Ok((user, date)) => {
+                    checks.push(CheckResult::pass(
+                        "Connectivity",
+                        format!("Signed in as {} ({})", user.email, user.tier),
+                    ));
+                    connected = true;
+                    date
+                }
+                Err(e) => {
+                    checks.push(connectivity_failure(&e));
+                    None
+                }
+            }
+        }
+        _ => {
+            checks.push(CheckResult::skip("Connectivity", "Needs a valid API key and URL"));
+            None
+        }
+    };
+
+    // Clock skew
+    match server_date.as_deref().and_then(|date| clock_skew(date, Utc::now())) {
+        Some(skew) if skew.abs() > MAX_CLOCK_SKEW_SECS => checks.push(CheckResult::warn(
+            "Clock",
+            format!(
+                "Local clock is {}s {} the server",
+                skew.abs(),
+                if skew > 0 { "ahead of" } else { "behind" }
+            ),
+            "Sync your system clock (e.g. enable NTP)",
+        )),
+        Some(skew) => {
+            checks.push(CheckResult::pass("Clock", format!("Within {}s of the server", skew.abs())))
+        }
+        None if connected => {
+            checks.push(CheckResult::skip("Clock", "The server didn't send a Date header"))
+        }
+        None => checks.push(CheckResult::skip("Clock", "Needs a server connection")),
+    }
+
+    checks
+}
+
+/// Warn when the config file holding the key is readable by other users
+fn storage_check(path: &std::path::Path) -> CheckResult {
+    let detail = format!("Stored in {} (no system keyring is used)", path.display());
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if let Ok(meta) = std::fs::metadata(path) {
+            if meta.permissions().mode() & 0o077 != 0 {
+                return CheckResult::warn(
+                    "Credential storage",
+                    format!("{} is readable by other users", path.display()),
+                    format!("chmod 600 {}", path.display()),
+                );
+            }
+        }
+    }
+
+    CheckResult::pass("Credential storage", detail)
+}
+
+/// Explain a failed whoami probe
+fn connectivity_failure(err: &anyhow::Error) -> CheckResult {
+    match api::error_status(err) {
+        Some(StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN) => CheckResult::fail(
+            "Connectivity",
+            "The server rejected the API key",
+            "Run 'ck auth login' again",
+        ),
+        Some(_) => CheckResult::fail(
+            "Connectivity",
+            err.to_string(),
+            "The server is reachable but returned an error; try again later",
+        ),
+        None => {
+            let proxy = PROXY_ENV_VARS
+                .iter()
+                .find(|var| std::env::var_os(var).is_some_and(|v| !v.is_empty()));
+            let hint = match proxy {
+                Some(var) => format!("Check the proxy in {} and your network connection", var),
+                None => "Check the API URL and your network connection".to_string(),
+            };
+            CheckResult::fail("Connectivity", format!("{:#}", err), hint)
+        }
+    }
+}
+
+/// Check the local setup and connection, printing a checklist.
+/// Fails if any check fails; warnings alone don't.
+///
+/// With `quiet` nothing but an `{ok}` object (for `--json`/`--yaml`) is
+/// printed, and the result is returned instead of an error.
+pub async fn run(format: OutputFormat, quiet: bool, opts: &ClientOptions) -> Result<bool> {
+    #[derive(Serialize)]
+    struct Report<'a> {
+        ok: bool,
+        checks: &'a [CheckResult],
+    }
+
+    let checks = run_checks(opts).await;
+    let failed = output::failed_checks(&checks);
+
+    if quiet {
+        if format.is_structured() {
+            output::print_check_status(failed == 0, format)?;
+        }
+        return Ok(failed == 0);
+    }
+
+    if format.is_structured() {
+        output::print_serialized(&Report { ok: failed == 0, checks: &checks }, format)?;
+    } else {
+        output::print_checks(&checks);
+        println!();
+        if failed == 0 {
+            output::print_success("No problems found");
+        }
+    }
+
+    if failed > 0 {
+        return Err(anyhow::anyhow!("{} check(s) failed", failed));
+    }
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clock_skew_from_date_header() {
+        let now = DateTime::parse_from_rfc3339("2026-03-01T12:00:30Z").unwrap().with_timezone(&Utc);
+        assert_eq!(clock_skew("Sun, 01 Mar 2026 12:00:00 GMT", now), Some(30));
+        assert_eq!(clock_skew("Sun, 01 Mar 2026 12:10:30 GMT", now), Some(-600));
+        assert_eq!(clock_skew("yesterday", now), None);
+    }
+}
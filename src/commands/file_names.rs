@@ -0,0 +1,192 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// File name used for each item when `--name-template` isn't given
+pub const DEFAULT_NAME_TEMPLATE: &str = "{id}.md";
+
+/// Longest `{title}` slug, in characters, so long titles still make
+/// usable file names
+const MAX_SLUG_CHARS: usize = 80;
+
+/// Placeholders `--name-template` understands
+const PLACEHOLDERS: &[&str] = &["{id}", "{title}"];
+
+/// Characters some common file system won't take in a file name
+const ILLEGAL_CHARS: &[char] = &['/', '\\', ':', '*', '?', '"', '<', '>', '|'];
+
+/// `--output-dir`/`--name-template` for commands that write one file per item
+#[derive(Debug, Clone, clap::Args)]
+pub struct OutputDirOptions {
+    /// Write each item to its own file in this directory (created if missing)
+    #[arg(long, value_name = "DIR")]
+    pub output_dir: Option<PathBuf>,
+    /// File name for each item: {id} and {title} (slugified) are filled in;
+    /// clashing names get -2, -3, ...
+    #[arg(
+        long,
+        requires = "output_dir",
+        value_name = "TEMPLATE",
+        default_value = DEFAULT_NAME_TEMPLATE,
+        value_parser = parse_name_template
+    )]
+    pub name_template: String,
+}
+
+/// Check that a `--name-template` only uses known placeholders
+pub fn parse_name_template(template: &str) -> Result<String> {
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let end = rest[start..]
+            .find('}')
+            .map(|end| start + end + 1)
+            .with_context(|| format!("Unclosed '{{' in --name-template '{}'", template))?;
+        let placeholder = &rest[start..end];
+        if !PLACEHOLDERS.contains(&placeholder) {
+            return Err(anyhow::anyhow!(
+                "Unknown placeholder '{}' in --name-template; use {}",
+                placeholder,
+                PLACEHOLDERS.join(" or ")
+            ));
+        }
+        rest = &rest[end..];
+    }
+    if template.trim().is_empty() {
+        return Err(anyhow::anyhow!("--name-template can't be empty"));
+    }
+    Ok(template.to_string())
+}
+
+/// Lowercase words joined by `-`, at most `MAX_SLUG_CHARS` long; "untitled"
+/// when nothing is left
+pub fn slugify(title: &str) -> String {
+    let mut slug = String::new();
+    for c in title.chars().flat_map(char::to_lowercase) {
+        if c.is_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let mut slug: String = slug.chars().take(MAX_SLUG_CHARS).collect();
+    while slug.ends_with('-') {
+        slug.pop();
+    }
+    if slug.is_empty() {
+        slug.push_str("untitled");
+    }
+    slug
+}
+
+/// Drop `ILLEGAL_CHARS` and control characters, and the trailing dots and
+/// spaces Windows rejects
+fn sanitize(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .filter(|c| !c.is_control() && !ILLEGAL_CHARS.contains(c))
+        .collect();
+    name.trim_end_matches(['.', ' ']).trim_start().to_string()
+}
+
+/// `name` with `-n` before its extension
+fn with_suffix(name: &str, n: usize) -> String {
+    match name.rfind('.') {
+        Some(dot) if dot > 0 => format!("{}-{}{}", &name[..dot], n, &name[dot..]),
+        _ => format!("{}-{}", name, n),
+    }
+}
+
+/// Hands out file names from a `--name-template`, never the same one twice
+/// (compared case-insensitively, for case-insensitive file systems)
+pub struct FileNamer {
+    template: String,
+    used: HashSet<String>,
+}
+
+impl FileNamer {
+    pub fn new(template: &str) -> Self {
+        Self { template: template.to_string(), used: HashSet::new() }
+    }
+
+    /// The file name for one item
+    pub fn name(&mut self, id: &str, title: &str) -> String {
+        let rendered = self.template.replace("{id}", id).replace("{title}", &slugify(title));
+        let mut base = sanitize(&rendered);
+        if base.is_empty() || base == "." || base == ".." {
+            base = sanitize(id);
+        }
+
+        let mut name = base.clone();
+        let mut n = 1;
+        while !self.used.insert(name.to_lowercase()) {
+            n += 1;
+            name = with_suffix(&base, n);
+        }
+        name
+    }
+}
+
+/// One file written into `--output-dir`
+#[derive(Debug, Serialize)]
+pub struct WrittenFile {
+    pub id: String,
+    pub path: PathBuf,
+}
+
+/// Write `files` (ID, title, content) into `dir` with names from
+/// `template`. Files from an earlier run with the same names are replaced.
+pub fn write_files<'a>(
+    dir: &Path,
+    template: &str,
+    files: impl IntoIterator<Item = (&'a str, &'a str, String)>,
+) -> Result<Vec<WrittenFile>> {
+    fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+
+    let mut namer = FileNamer::new(template);
+    let mut written = Vec::new();
+    for (id, title, content) in files {
+        let path = dir.join(namer.name(id, title));
+        fs::write(&path, content).with_context(|| format!("Failed to write {}", path.display()))?;
+        written.push(WrittenFile { id: id.to_string(), path });
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slugify("Deep Learning: A Primer (2nd ed.)"), "deep-learning-a-primer-2nd-ed");
+        assert_eq!(slugify("  Über / Straße  "), "über-straße");
+        assert_eq!(slugify("???"), "untitled");
+        let long = slugify(&"word ".repeat(40));
+        assert!(long.chars().count() <= MAX_SLUG_CHARS && !long.ends_with('-'), "{}", long);
+    }
+
+    #[test]
+    fn test_file_namer_sanitizes_and_dedups() {
+        let mut namer = FileNamer::new("{id}-{title}.md");
+        assert_eq!(namer.name("itm_1", "Intro"), "itm_1-intro.md");
+
+        let mut namer = FileNamer::new("{title}.md");
+        assert_eq!(namer.name("itm_1", "Notes"), "notes.md");
+        assert_eq!(namer.name("itm_2", "NOTES!"), "notes-2.md");
+        assert_eq!(namer.name("itm_3", "notes"), "notes-3.md");
+
+        let mut namer = FileNamer::new("a:b|{id}?.txt.");
+        assert_eq!(namer.name("itm_1", ""), "abitm_1.txt");
+    }
+
+    #[test]
+    fn test_parse_name_template() {
+        assert!(parse_name_template("{id}-{title}.md").is_ok());
+        let err = parse_name_template("{name}.md").unwrap_err().to_string();
+        assert!(err.contains("'{name}'"), "{}", err);
+        assert!(parse_name_template("{id").is_err());
+        assert!(parse_name_template(" ").is_err());
+    }
+}
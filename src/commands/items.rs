@@ -1,11 +1,25 @@
 use anyhow::{Context, Result};
+use clap::ValueEnum;
 use colored::Colorize;
+use futures_util::StreamExt;
 use indicatif::{ProgressBar, ProgressStyle};
-use std::io::{self, Write};
+use regex::RegexBuilder;
+use reqwest::StatusCode;
+use serde::Serialize;
+use std::io::{self, IsTerminal, Write};
 use std::path::Path;
 
-use crate::api::{ApiClient, ItemReadRequest, TocEntry};
-use crate::output;
+use crate::api::{self, ApiClient, ClientOptions, Item, ItemReadRequest, MetadataUpdate, Page, TocEntry};
+use crate::config;
+use crate::diff;
+use crate::interrupt;
+use crate::markdown;
+use crate::output::{self, CheckResult, CheckStatus, ItemField, OutputFormat};
+use crate::prompt;
+use crate::read_cache::{self, ReadCache};
+use crate::spinner;
+
+use super::file_names::{self, OutputDirOptions, WrittenFile};
 
 /// Parse comma-separated IDs (for commands that don't use page ranges)
 fn parse_ids(ids_str: &str) -> Vec<String> {
@@ -16,6 +30,28 @@ fn parse_ids(ids_str: &str) -> Vec<String> {
         .collect()
 }
 
+/// Page range syntax for `items read`, shared by `--help` and the
+/// missing-range error so they can't drift apart
+pub const PAGE_RANGE_FORMATS: &str = "Formats:
+  • id:all        - All pages
+  • id:1-5        - Pages 1 through 5
+  • id:1,3,5      - Specific pages
+  • id:1-3,7,10   - Combined ranges";
+
+/// `items read --help` footer: range syntax plus examples
+pub fn read_after_help() -> String {
+    format!(
+        "{}\n\n\
+        Examples:\n  \
+        ck items read itm_abc:1-5\n  \
+        ck items read itm_abc:1-5,itm_def:all\n  \
+        ck items read itm_abc:all --grep 'gradient descent'\n  \
+        ck items read itm_abc --chapter 'Chapter 3'\n  \
+        ck items read itm_abc --chapter 'Chapter 3' --offset -1 --pages 3",
+        PAGE_RANGE_FORMATS
+    )
+}
+
 /// Parse IDs with page ranges in format "id1:1-5,id2:all,id3:10-20"
 /// Every ID must have an explicit range (use 'all' for all pages)
 fn parse_ids_with_ranges(ids_str: &str) -> Result<Vec<ItemReadRequest>> {
@@ -61,314 +97,2642 @@ fn parse_ids_with_ranges(ids_str: &str) -> Result<Vec<ItemReadRequest>> {
             "Missing page range for: {}\n\n\
             Every ID must specify a page range. Use 'all' for all pages.\n\
             Example: {}\n\n\
-            Formats:\n  \
-            • id:all        - All pages\n  \
-            • id:1-5        - Pages 1 through 5\n  \
-            • id:1,3,5      - Specific pages\n  \
-            • id:1-3,7,10   - Combined ranges",
+            {}",
             missing_ranges.join(", "),
-            examples.join(",")
+            examples.join(","),
+            PAGE_RANGE_FORMATS
         ));
     }
 
     Ok(items)
 }
 
-/// List all items
-pub async fn list(json: bool, session: Option<String>, no_session: bool) -> Result<()> {
-    let client = ApiClient::new(session, no_session)?;
-    let response = client.list_items().await?;
+/// Page range covered by the TOC entry whose title contains `query`
+/// (case-insensitive): from its page up to the page before the next entry at
+/// the same or a higher level, or to the last page. Several matches are an
+/// error unless exactly one title matches `query` in full.
+fn chapter_range(toc: &[TocEntry], page_count: i32, query: &str) -> Result<(i32, i32)> {
+    let query = query.trim();
+    let needle = query.to_lowercase();
+    let matches: Vec<usize> = toc
+        .iter()
+        .enumerate()
+        .filter(|(_, entry)| entry.title.to_lowercase().contains(&needle))
+        .map(|(i, _)| i)
+        .collect();
+
+    let index = match matches.as_slice() {
+        [] => return Err(anyhow::anyhow!("No chapter matching '{}'", query)),
+        [only] => *only,
+        _ => {
+            let exact: Vec<usize> = matches
+                .iter()
+                .copied()
+                .filter(|&i| toc[i].title.trim().eq_ignore_ascii_case(query))
+                .collect();
+            match exact.as_slice() {
+                [only] => *only,
+                _ => {
+                    let candidates: Vec<String> = matches
+                        .iter()
+                        .map(|&i| format!("  • {} (page {})", toc[i].title, toc[i].page))
+                        .collect();
+                    return Err(anyhow::anyhow!(
+                        "'{}' matches {} chapters; be more specific:\n{}",
+                        query,
+                        matches.len(),
+                        candidates.join("\n")
+                    ));
+                }
+            }
+        }
+    };
 
-    if json {
-        output::print_items_json(&response);
-    } else {
-        output::print_items_table(&response.items, &response.enrichment_queue);
-    }
+    Ok(output::toc_entry_range(toc, index, page_count))
+}
 
-    Ok(())
+/// `count` pages starting `offset` pages after `start` (before it when
+/// negative), kept within 1..=page_count
+fn preview_range(start: i32, page_count: i32, offset: i32, count: u32) -> (i32, i32) {
+    let last = page_count.max(1);
+    let first = start.saturating_add(offset).clamp(1, last);
+    let count = i32::try_from(count).unwrap_or(i32::MAX);
+    (first, first.saturating_add(count - 1).min(last))
 }
 
-/// Read content from items
-/// Format: "id1:1-5,id2:all,id3:10-20"
-pub async fn read(ids_str: &str, json: bool, session: Option<String>, no_session: bool) -> Result<()> {
-    let items = parse_ids_with_ranges(ids_str)?;
+/// Turn plain item IDs plus `--chapter` titles into read requests, looking
+/// the page ranges up in each item's TOC. With `preview` (`--offset`,
+/// `--pages`), each range is that many pages from the chapter's start
+/// instead of the whole chapter. Returns the requests and the IDs that
+/// weren't found.
+async fn chapter_requests(
+    client: &ApiClient,
+    ids_str: &str,
+    chapters: &[String],
+    preview: Option<(i32, u32)>,
+    concurrency: usize,
+) -> Result<(Vec<ItemReadRequest>, Vec<String>)> {
+    let ids = parse_ids(ids_str);
+    if ids.is_empty() {
+        return Err(anyhow::anyhow!("No item IDs provided"));
+    }
+    if let Some(id) = ids.iter().find(|id| id.contains(':')) {
+        return Err(anyhow::anyhow!(
+            "--chapter picks the pages itself; pass plain item IDs (got '{}')",
+            id
+        ));
+    }
 
-    let client = ApiClient::new(session, no_session)?;
-    let response = client.batch_read(items).await?;
+    let toc = fetch_in_chunks(
+        ids,
+        concurrency,
+        |id| id.as_str(),
+        |chunk| async move { client.batch_toc(chunk).await.map(|r| (r.items, r.not_found)) },
+    )
+    .await?;
+    if !toc.failures.is_empty() {
+        return Err(toc.failure_error());
+    }
 
-    if json {
-        output::print_item_content_json(&response.items, &response.not_found);
-    } else {
-        output::print_item_content(&response.items, &response.not_found);
+    let mut requests = Vec::with_capacity(toc.items.len());
+    for item in &toc.items {
+        let entries = item.toc.as_deref().unwrap_or_default();
+        let ranges = chapters
+            .iter()
+            .map(|chapter| {
+                let (start, end) = chapter_range(entries, item.page_count, chapter)
+                    .with_context(|| format!("{} ({})", item.title, item.id))?;
+                let (start, end) = match preview {
+                    Some((offset, count)) => preview_range(start, item.page_count, offset, count),
+                    None => (start, end),
+                };
+                Ok(if start == end {
+                    start.to_string()
+                } else {
+                    format!("{}-{}", start, end)
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        requests.push(ItemReadRequest {
+            id: item.id.clone(),
+            pages: Some(ranges.join(",")),
+        });
     }
 
-    Ok(())
+    Ok((requests, toc.not_found.unwrap_or_default()))
 }
 
-/// Show table of contents for items
-pub async fn toc(ids_str: &str, json: bool, session: Option<String>, no_session: bool) -> Result<()> {
-    let ids = parse_ids(ids_str);
-    if ids.is_empty() {
-        return Err(anyhow::anyhow!("No item IDs provided"));
+/// Flags for `items list`: which items to show and how
+#[derive(Debug, Clone, clap::Args)]
+pub struct ListOptions {
+    /// Comma-separated table columns, in order [default: id,title,pages,status,enrich]
+    #[arg(long, value_enum, value_delimiter = ',')]
+    pub fields: Vec<ItemField>,
+    /// Maximum title width in the table (longer titles end with …)
+    #[arg(long, default_value_t = 60)]
+    pub title_width: usize,
+    /// Only show items with this tag (case-insensitive)
+    #[arg(long)]
+    pub tag: Option<String>,
+    /// Only show items with one of these statuses (comma-separated,
+    /// case-insensitive), e.g. failed,processing
+    #[arg(long, value_delimiter = ',', value_parser = parse_status, value_name = "STATUSES")]
+    pub status: Vec<String>,
+    /// Only show items waiting for metadata enrichment
+    #[arg(long)]
+    pub needs_enrichment: bool,
+    /// Print one item ID per line instead of a table (for shell pipelines)
+    #[arg(long)]
+    pub ids_only: bool,
+    /// With --ids-only, print "ID<TAB>title" lines
+    #[arg(long, requires = "ids_only")]
+    pub with_title: bool,
+    /// Explain the Enrich symbols and status colors after the table
+    #[arg(long, conflicts_with = "ids_only")]
+    pub legend: bool,
+    /// Print each item with this template instead of a table, e.g.
+    /// "{id}\t{title} ({pages}p)"; placeholders are the --fields names
+    #[arg(long, conflicts_with_all = ["ids_only", "fields", "legend"])]
+    pub template: Option<String>,
+    /// Only items whose --date-field is on or after this: YYYY-MM-DD, an
+    /// RFC 3339 timestamp, or an age like 7d, 12h, 2w
+    #[arg(long, value_name = "DATE")]
+    pub since: Option<String>,
+    /// Only items whose --date-field is before this (same forms as --since)
+    #[arg(long, value_name = "DATE")]
+    pub until: Option<String>,
+    /// Timestamp --since and --until compare against
+    #[arg(long, value_enum, default_value_t = DateField::Created)]
+    pub date_field: DateField,
+}
+
+/// Item timestamp used by `items list --since/--until`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum DateField {
+    Created,
+    Updated,
+}
+
+impl DateField {
+    fn of(self, item: &Item) -> &str {
+        match self {
+            DateField::Created => &item.created_at,
+            DateField::Updated => &item.updated_at,
+        }
+    }
+}
+
+/// Parse an `items list --status` value into its upper-case server form
+pub fn parse_status(value: &str) -> std::result::Result<String, String> {
+    let status = value.trim().to_uppercase();
+    if output::ITEM_STATUSES.iter().any(|(known, _)| *known == status) {
+        return Ok(status);
     }
+    let known: Vec<String> =
+        output::ITEM_STATUSES.iter().map(|(known, _)| known.to_lowercase()).collect();
+    Err(format!("unknown status '{}' (expected one of: {})", value, known.join(", ")))
+}
 
-    let client = ApiClient::new(session, no_session)?;
-    let response = client.batch_toc(ids).await?;
+/// List all items
+pub async fn list(list: &ListOptions, format: OutputFormat, opts: &ClientOptions) -> Result<()> {
+    let template = list
+        .template
+        .as_deref()
+        .map(|t| output::Template::parse(t, &ItemField::names()))
+        .transpose()?;
+
+    let now = chrono::Utc::now();
+    let since = list.since.as_deref().map(|d| super::parse_date_bound(d, now)).transpose()?;
+    let until = list.until.as_deref().map(|d| super::parse_date_bound(d, now)).transpose()?;
+
+    let client = ApiClient::new(opts)?;
+    let mut response =
+        spinner::with_spinner("Loading items...", format, client.list_items()).await?;
+
+    if let Some(ref tag) = list.tag {
+        response
+            .items
+            .retain(|item| item.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)));
+    }
+    if !list.status.is_empty() {
+        response
+            .items
+            .retain(|item| list.status.iter().any(|s| s.eq_ignore_ascii_case(&item.status)));
+    }
+    if list.needs_enrichment {
+        response.items.retain(|item| item.needs_enrichment == Some(true));
+    }
+    if since.is_some() || until.is_some() {
+        // Items with an unreadable timestamp can't be placed, so they're dropped
+        response.items.retain(|item| {
+            super::parse_timestamp(list.date_field.of(item)).is_some_and(|at| {
+                since.is_none_or(|since| at >= since) && until.is_none_or(|until| at < until)
+            })
+        });
+    }
 
-    if json {
-        output::print_toc_json(&response.items, &response.not_found);
+    if let Some(template) = template {
+        for item in &response.items {
+            println!(
+                "{}",
+                template.render(|name| {
+                    ItemField::from_str(name, false).map(|f| f.text(item)).unwrap_or_default()
+                })
+            );
+        }
+    } else if list.ids_only {
+        output::print_item_ids(&response.items, list.with_title);
+    } else if format.is_structured() {
+        output::print_serialized(&response, format)?;
     } else {
-        output::print_toc(&response.items, &response.not_found);
+        let fields = if list.fields.is_empty() {
+            output::DEFAULT_ITEM_FIELDS
+        } else {
+            &list.fields
+        };
+        output::print_items_table(
+            &response.items,
+            &response.enrichment_queue,
+            fields,
+            list.title_width,
+            list.legend,
+        );
     }
 
     Ok(())
 }
 
-/// Upload a file (PDF or Markdown)
-pub async fn add(file_path: &str, session: Option<String>, no_session: bool) -> Result<()> {
-    let path = Path::new(file_path);
+/// Version of the `items export` document layout
+const EXPORT_FORMAT_VERSION: u32 = 1;
 
-    // Validate file exists
-    if !path.exists() {
-        return Err(anyhow::anyhow!("File not found: {}", file_path));
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct LibraryExport {
+    format_version: u32,
+    cli_version: &'static str,
+    items: Vec<ExportedItem>,
+}
+
+#[derive(Serialize)]
+struct ExportedItem {
+    #[serde(flatten)]
+    item: Item,
+    /// Full markdown body (markdown documents, with --include-content)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    content: Option<String>,
+    /// Extracted pages (other documents, with --include-content)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pages: Option<Vec<Page>>,
+}
+
+/// Whether an item is a markdown document (content is edited as a whole)
+fn is_markdown(source_type: &str) -> bool {
+    source_type.eq_ignore_ascii_case("markdown")
+}
+
+/// Pages' text as one document, with a blank line between pages
+fn joined_pages(pages: &[Page]) -> String {
+    let texts: Vec<&str> = pages.iter().filter_map(|p| p.content.as_deref()).collect();
+    texts.join("\n\n")
+}
+
+/// Report the files written for `--output-dir`
+fn print_written_files(files: &[WrittenFile], dir: &Path, format: OutputFormat) -> Result<()> {
+    if format.is_structured() {
+        return output::print_serialized(&files, format);
     }
+    for file in files {
+        println!("  {}", file.path.display());
+    }
+    output::print_success(&format!("Wrote {} file(s) to {}", files.len(), dir.display()));
+    Ok(())
+}
 
-    // Get content type based on extension
-    let extension = path
-        .extension()
-        .and_then(|e| e.to_str())
-        .map(|e| e.to_lowercase());
+/// Export the whole library's metadata (and optionally content) as one JSON document
+pub async fn export(
+    output_path: Option<&str>,
+    include_content: bool,
+    files: &OutputDirOptions,
+    format: OutputFormat,
+    opts: &ClientOptions,
+) -> Result<()> {
+    // One file per item only makes sense with the content
+    let include_content = include_content || files.output_dir.is_some();
+    let client = ApiClient::new(opts)?;
+    let response = client.list_items().await?;
 
-    let content_type = match extension.as_deref() {
-        Some("pdf") => "application/pdf",
-        Some("md") | Some("markdown") => "text/markdown",
-        _ => {
-            return Err(anyhow::anyhow!(
-                "Unsupported file type. Only PDF and Markdown files are supported. Got: {}",
-                extension.unwrap_or_else(|| "no extension".to_string())
-            ));
-        }
+    let pb = if include_content && !prompt::non_interactive() {
+        let pb = ProgressBar::new(response.items.len() as u64);
+        pb.set_style(ProgressStyle::with_template(
+            "{spinner:.green} [{bar:40.cyan/blue}] {pos}/{len} {msg}",
+        )?);
+        pb
+    } else {
+        ProgressBar::hidden()
     };
 
-    // Get file info
-    let metadata = std::fs::metadata(path).context("Failed to read file metadata")?;
-    let size = metadata.len();
-    let filename = path
-        .file_name()
-        .and_then(|n| n.to_str())
-        .context("Invalid filename")?
-        .to_string();
+    let mut items = Vec::with_capacity(response.items.len());
+    for item in response.items {
+        let (content, pages) = if include_content {
+            pb.set_message(item.title.clone());
+            let exported = if is_markdown(&item.source_type) {
+                let body = client.get_content(&item.id).await?;
+                (Some(body.content), None)
+            } else {
+                let mut batch = client
+                    .batch_read(vec![ItemReadRequest { id: item.id.clone(), pages: None }])
+                    .await?;
+                (None, batch.items.pop().map(|i| i.pages))
+            };
+            pb.inc(1);
+            exported
+        } else {
+            (None, None)
+        };
 
-    println!("{}", format!("Uploading: {}", filename).cyan());
-    println!("{}", format!("Size: {} bytes", size).dimmed());
+        items.push(ExportedItem { item, content, pages });
+    }
+    pb.finish_and_clear();
+
+    if let Some(ref dir) = files.output_dir {
+        let contents = items.iter().map(|exported| {
+            let text = match (&exported.content, &exported.pages) {
+                (Some(content), _) => content.clone(),
+                (None, Some(pages)) => joined_pages(pages),
+                (None, None) => String::new(),
+            };
+            (exported.item.id.as_str(), exported.item.title.as_str(), text)
+        });
+        let written = file_names::write_files(dir, &files.name_template, contents)?;
+        return print_written_files(&written, dir, format);
+    }
 
-    let client = ApiClient::new(session, no_session)?;
+    let export = LibraryExport {
+        format_version: EXPORT_FORMAT_VERSION,
+        cli_version: env!("CARGO_PKG_VERSION"),
+        items,
+    };
+    let json = output::to_json(&export)?;
+
+    match output_path {
+        Some(path) => {
+            std::fs::write(path, json).with_context(|| format!("Failed to write {}", path))?;
+            output::print_success(&format!(
+                "Exported {} item(s) to {}",
+                export.items.len(),
+                path
+            ));
+        }
+        None => println!("{}", json),
+    }
 
-    // Step 1: Get presigned upload URL
-    print!("{}", "Creating upload...".dimmed());
-    io::stdout().flush()?;
+    Ok(())
+}
 
-    let upload_info = client
-        .create_upload(&filename, size, content_type)
-        .await?;
+/// Trim `--tags` values and drop empties; `None` when no tags were given
+pub fn parse_tags(tags: Vec<String>) -> Option<Vec<String>> {
+    let tags: Vec<String> = tags
+        .into_iter()
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+        .collect();
+    (!tags.is_empty()).then_some(tags)
+}
 
-    println!(" {}", "OK".green());
+/// Batch flags shared by `items read` and `items toc`
+#[derive(Debug, Clone, Copy, clap::Args)]
+pub struct BatchOptions {
+    /// Maximum number of batch requests in flight at once
+    #[arg(long, default_value_t = 4)]
+    pub concurrency: usize,
+    /// Exit non-zero if any ID isn't found (results are still printed)
+    #[arg(long)]
+    pub strict: bool,
+}
 
-    // Step 2: Upload file to presigned URL
-    let pb = ProgressBar::new(size);
-    pb.set_style(
-        ProgressStyle::with_template(
-            "{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({eta})",
-        )?
-        .progress_chars("#>-"),
-    );
+/// `--line-ending`/`--bom` for commands that write raw document text
+#[derive(Debug, Clone, clap::Args)]
+pub struct EncodingOptions {
+    /// Convert line endings before writing (default: keep them as received)
+    #[arg(long, value_enum, value_name = "STYLE")]
+    pub line_ending: Option<output::LineEnding>,
+    /// Start the output with a UTF-8 byte order mark
+    #[arg(long)]
+    pub bom: bool,
+}
 
-    // Read the file
-    let file_data = std::fs::read(path).context("Failed to read file")?;
+impl EncodingOptions {
+    pub fn encoding(&self) -> output::TextEncoding {
+        output::TextEncoding { line_ending: self.line_ending, bom: self.bom }
+    }
 
-    pb.set_position(0);
-    pb.set_message("Uploading...");
+    fn is_set(&self) -> bool {
+        self.line_ending.is_some() || self.bom
+    }
+}
 
-    // Upload to presigned URL
-    client
-        .upload_file(&upload_info.upload_url, file_data.clone(), content_type)
-        .await?;
+/// Flags that choose and filter what `items read` prints
+#[derive(Debug, Clone, clap::Args)]
+pub struct ReadOptions {
+    /// Read the pages of the TOC entry whose title contains this text
+    /// (case-insensitive); repeat for several sections
+    #[arg(long, value_name = "TITLE")]
+    pub chapter: Vec<String>,
+    /// With --chapter, read this many pages from the chapter's start,
+    /// however long the chapter is
+    #[arg(
+        long = "pages",
+        value_name = "COUNT",
+        requires = "chapter",
+        value_parser = clap::value_parser!(u32).range(1..)
+    )]
+    pub preview_pages: Option<u32>,
+    /// With --pages, start this many pages after the chapter's start
+    /// (negative reads before it)
+    #[arg(long, requires = "preview_pages", allow_negative_numbers = true, default_value_t = 0)]
+    pub offset: i32,
+    /// Only show pages whose content matches this regex (case-insensitive)
+    #[arg(long)]
+    pub grep: Option<String>,
+    /// Make --grep case-sensitive
+    #[arg(long, requires = "grep")]
+    pub case_sensitive: bool,
+    /// Highlight matches of this regex (case-insensitive) without dropping
+    /// any pages; repeat for several terms, each in its own color
+    #[arg(long, value_name = "TERM", conflicts_with = "stats")]
+    pub highlight: Vec<String>,
+    /// Leave out pages with no text (whitespace only), or fewer than
+    /// --min-chars characters; common in scanned PDFs
+    #[arg(long)]
+    pub skip_empty: bool,
+    /// With --skip-empty, the fewest characters a page needs to be kept
+    #[arg(long, requires = "skip_empty", value_name = "N", default_value_t = 1)]
+    pub min_chars: usize,
+    /// Print character, word, page, and estimated token counts instead of content
+    #[arg(long, conflicts_with = "output_dir")]
+    pub stats: bool,
+    /// Print only the raw page content: no titles, rules, page markers, or colors
+    #[arg(long, conflicts_with = "stats")]
+    pub no_headers: bool,
+    /// With --no-headers, put this between pages instead of a newline
+    /// (escapes: \n, \t, \f, \\)
+    #[arg(long, requires = "no_headers", value_name = "SEP")]
+    pub page_delimiter: Option<String>,
+    /// Show item and page metadata (e.g. section, bounding info) under each header
+    #[arg(long, conflicts_with_all = ["stats", "no_headers"])]
+    pub include_metadata: bool,
+    /// Read this many pages of a single item, starting at --cursor
+    #[arg(
+        long,
+        value_name = "PAGES",
+        conflicts_with = "chapter",
+        value_parser = clap::value_parser!(u32).range(1..)
+    )]
+    pub window: Option<u32>,
+    /// With --window, the number of pages to skip (the previous read's nextCursor)
+    #[arg(long, requires = "window", default_value_t = 0)]
+    pub cursor: u32,
+    #[command(flatten)]
+    pub encoding: EncodingOptions,
+    #[command(flatten)]
+    pub files: OutputDirOptions,
+    /// Write each item as one compact JSON line as soon as its chunk arrives,
+    /// then a final line with not_found
+    #[arg(long, conflicts_with_all = ["stats", "no_headers", "include_metadata", "output_dir"])]
+    pub ndjson: bool,
+    /// Name the TOC entry each page falls under in its header (a "chapter"
+    /// field with --json)
+    #[arg(long, conflicts_with_all = ["stats", "no_headers"])]
+    pub toc_context: bool,
+    /// Render each page's markdown as plain text (with --json, a "plainText"
+    /// field next to the raw content)
+    #[arg(long)]
+    pub strip_markdown: bool,
+    /// Serve repeat reads of the same pages from a local cache, and cache
+    /// what's fetched; entries expire after --cache-ttl or when the item changes
+    #[arg(long)]
+    pub cache_read: bool,
+    /// Seconds a --cache-read entry stays fresh
+    #[arg(long, value_name = "SECS", default_value_t = read_cache::DEFAULT_TTL_SECS)]
+    pub cache_ttl: u64,
+    /// Serve pages only from the --cache-read cache, however old, without
+    /// calling the API; fails on anything not cached
+    #[arg(long, conflicts_with_all = ["cache_read", "chapter", "window", "toc_context"])]
+    pub offline: bool,
+}
 
-    pb.set_position(size);
-    pb.finish_with_message("Upload complete");
+/// Title of the TOC entry `page` falls under: the last entry, in TOC order,
+/// that starts at or before it
+fn chapter_for_page(entries: &[TocEntry], page: i32) -> Option<&str> {
+    entries
+        .iter()
+        .filter(|entry| entry.page <= page)
+        .max_by_key(|entry| entry.page)
+        .map(|entry| entry.title.as_str())
+}
 
-    // Step 3: Confirm upload
-    print!("{}", "Processing...".dimmed());
-    io::stdout().flush()?;
+/// Fetch the TOCs of the items being read, keyed by item ID. Items whose TOC
+/// can't be fetched are left out, with a warning on stderr, and read without
+/// chapter names.
+async fn fetch_tocs(
+    client: &ApiClient,
+    requests: &[ItemReadRequest],
+    concurrency: usize,
+) -> std::collections::HashMap<String, Vec<TocEntry>> {
+    let mut ids: Vec<String> = requests.iter().map(|r| r.id.clone()).collect();
+    ids.sort();
+    ids.dedup();
+    let toc = fetch_in_chunks(
+        ids,
+        concurrency,
+        |id| id.as_str(),
+        |chunk| async move { client.batch_toc(chunk).await.map(|r| (r.items, r.not_found)) },
+    )
+    .await;
+    let toc = match toc {
+        Ok(toc) => toc,
+        Err(e) => {
+            output::eprint_warning(&format!(
+                "Could not fetch TOCs, reading without chapter names: {:#}",
+                e
+            ));
+            return Default::default();
+        }
+    };
+    for (ids, e) in &toc.failures {
+        output::eprint_warning(&format!(
+            "Could not fetch the TOC of {}, reading without chapter names: {:#}",
+            ids.join(", "),
+            e
+        ));
+    }
+    toc.items.into_iter().map(|item| (item.id, item.toc.unwrap_or_default())).collect()
+}
 
-    let confirm = client
-        .confirm_upload(&upload_info.item_id, &upload_info.storage_key)
-        .await?;
+/// Set each page's `chapter` from its item's TOC
+fn annotate_chapters(
+    item: &mut api::ItemWithPages,
+    tocs: &std::collections::HashMap<String, Vec<TocEntry>>,
+) {
+    let Some(entries) = tocs.get(&item.id) else {
+        return;
+    };
+    for page in &mut item.pages {
+        page.chapter = chapter_for_page(entries, page.page_num).map(str::to_string);
+    }
+}
 
-    println!(" {}", "OK".green());
+/// Drop pages with fewer than `min_chars` characters once surrounding
+/// whitespace is trimmed (pages without content always go), counting them
+/// in `skipped_empty_pages`
+fn skip_empty_pages(item: &mut api::ItemWithPages, min_chars: usize) {
+    let before = item.pages.len();
+    item.pages.retain(|page| {
+        page.content.as_deref().is_some_and(|c| c.trim().chars().count() >= min_chars.max(1))
+    });
+    item.skipped_empty_pages = Some(before - item.pages.len());
+}
 
-    output::print_success(&format!(
-        "Added: {} (ID: {})",
-        confirm.item.title,
-        confirm.item.id.cyan()
-    ));
-    output::print_info(&format!(
-        "Processing job created: {} ({})",
-        confirm.job.id,
-        confirm.job.status
-    ));
+/// Render each page's markdown as plain text: in place, or into
+/// `plain_text` when structured output keeps the raw content too
+fn strip_markdown(item: &mut api::ItemWithPages, keep_raw: bool) {
+    for page in &mut item.pages {
+        let Some(plain) = page.content.as_deref().map(markdown::to_plain_text) else {
+            continue;
+        };
+        if keep_raw {
+            page.plain_text = Some(plain);
+        } else {
+            page.content = Some(plain);
+        }
+    }
+}
 
-    Ok(())
+/// The window of pages starting after `cursor` pages, as a 1-based range
+/// (`None` past the end), plus the position report for output
+fn page_window(
+    page_count: i32,
+    window: u32,
+    cursor: u32,
+) -> (Option<(i32, i32)>, output::PageWindow) {
+    let total = u32::try_from(page_count).unwrap_or(0);
+    let end = cursor.saturating_add(window).min(total);
+    let range = (cursor < end).then(|| (cursor as i32 + 1, end as i32));
+    let has_more = end < total;
+
+    let position = output::PageWindow {
+        cursor,
+        window,
+        page_count,
+        next_cursor: has_more.then_some(end),
+        has_more,
+    };
+    (range, position)
 }
 
-/// Remove items
-pub async fn remove(ids_str: &str, skip_confirm: bool, session: Option<String>, no_session: bool) -> Result<()> {
+/// Build the read request for `--window`: one plain ID, looked up in the TOC
+/// for its page count
+async fn window_request(
+    client: &ApiClient,
+    ids_str: &str,
+    window: u32,
+    cursor: u32,
+) -> Result<(Vec<ItemReadRequest>, output::PageWindow)> {
     let ids = parse_ids(ids_str);
-    if ids.is_empty() {
-        return Err(anyhow::anyhow!("No item IDs provided"));
+    let [id] = ids.as_slice() else {
+        return Err(anyhow::anyhow!("--window reads one item at a time; pass a single item ID"));
+    };
+    if id.contains(':') {
+        return Err(anyhow::anyhow!(
+            "--window picks the pages itself; pass a plain item ID (got '{}')",
+            id
+        ));
     }
 
-    // Confirm deletion
-    if !skip_confirm {
-        println!(
-            "{}",
-            format!("This will delete {} item(s):", ids.len()).yellow()
-        );
-        for id in &ids {
-            println!("  - {}", id);
-        }
-        print!("\nAre you sure? [y/N]: ");
-        io::stdout().flush()?;
+    let toc = client.batch_toc(vec![id.clone()]).await?;
+    let item = toc
+        .items
+        .into_iter()
+        .find(|item| &item.id == id)
+        .ok_or_else(|| anyhow::anyhow!("Item not found: {}", id))?;
+
+    let (range, position) = page_window(item.page_count, window, cursor);
+    let requests = match range {
+        Some((start, end)) => vec![ItemReadRequest {
+            id: item.id,
+            pages: Some(format!("{}-{}", start, end)),
+        }],
+        None => Vec::new(),
+    };
+    Ok((requests, position))
+}
 
-        let mut input = String::new();
-        io::stdin().read_line(&mut input)?;
+/// IDs sent per request when `items read` and `items toc` split a batch
+const BATCH_CHUNK_SIZE: usize = 5;
 
-        if !matches!(input.trim().to_lowercase().as_str(), "y" | "yes") {
-            println!("{}", "Cancelled.".dimmed());
-            return Ok(());
+/// Results of a batch call split into chunks: merged items in request order,
+/// plus the chunks that failed
+struct ChunkedBatch<T> {
+    items: Vec<T>,
+    not_found: Option<Vec<String>>,
+    failures: Vec<(Vec<String>, anyhow::Error)>,
+}
+
+impl<T> ChunkedBatch<T> {
+    /// Report failed chunks after the successful ones have been printed,
+    /// then apply `--strict` to the not-found IDs
+    fn finish(self, strict: bool) -> Result<()> {
+        if self.failures.is_empty() {
+            return super::check_strict(strict, self.not_found.as_deref().unwrap_or_default(), &[]);
         }
+        Err(self.failure_error())
     }
 
-    let client = ApiClient::new(session, no_session)?;
-    let response = client.delete_items(ids).await?;
-
-    // Report results
-    if !response.deleted.is_empty() {
-        output::print_success(&format!(
-            "Deleted {} item(s): {}",
-            response.deleted.len(),
-            response.deleted.join(", ")
-        ));
+    /// Report each failed chunk; the error counts the items in them
+    fn failure_error(&self) -> anyhow::Error {
+        let mut failed = 0;
+        for (ids, e) in &self.failures {
+            output::print_error(&format!("Failed to fetch {}: {:#}", ids.join(", "), e));
+            failed += ids.len();
+        }
+        anyhow::anyhow!("{} item(s) could not be fetched", failed)
     }
+}
 
-    if !response.not_found.is_empty() {
-        output::print_warning(&format!(
-            "Not found: {}",
-            response.not_found.join(", ")
-        ));
-    }
+/// Send `requests` in chunks of `BATCH_CHUNK_SIZE`, at most `concurrency` at a
+/// time. `fetch` returns one chunk's items and not-found IDs. If every chunk
+/// fails, the first error is returned as-is.
+async fn fetch_in_chunks<R, T, F, Fut>(
+    requests: Vec<R>,
+    concurrency: usize,
+    id_of: impl Fn(&R) -> &str,
+    fetch: F,
+) -> Result<ChunkedBatch<T>>
+where
+    F: Fn(Vec<R>) -> Fut,
+    Fut: std::future::Future<Output = Result<(Vec<T>, Option<Vec<String>>)>>,
+{
+    fetch_in_chunks_with(requests, concurrency, id_of, fetch, |_| Ok(())).await
+}
 
-    if let Some(storage_errors) = response.storage_errors {
-        if !storage_errors.is_empty() {
-            output::print_warning(&format!(
-                "Storage cleanup failed for: {}",
-                storage_errors.join(", ")
-            ));
+/// `fetch_in_chunks`, calling `on_chunk` with each chunk's items as soon as
+/// it arrives (in request order), before they're collected
+async fn fetch_in_chunks_with<R, T, F, Fut>(
+    requests: Vec<R>,
+    concurrency: usize,
+    id_of: impl Fn(&R) -> &str,
+    fetch: F,
+    mut on_chunk: impl FnMut(&mut Vec<T>) -> Result<()>,
+) -> Result<ChunkedBatch<T>>
+where
+    F: Fn(Vec<R>) -> Fut,
+    Fut: std::future::Future<Output = Result<(Vec<T>, Option<Vec<String>>)>>,
+{
+    let mut chunks = Vec::new();
+    let mut requests = requests.into_iter();
+    loop {
+        let chunk: Vec<R> = requests.by_ref().take(BATCH_CHUNK_SIZE).collect();
+        if chunk.is_empty() {
+            break;
+        }
+        chunks.push(chunk);
+    }
+    let chunk_count = chunks.len();
+
+    let mut results = futures_util::stream::iter(chunks)
+        .map(|chunk| {
+            let ids: Vec<String> = chunk.iter().map(|r| id_of(r).to_string()).collect();
+            let request = fetch(chunk);
+            async move { (ids, request.await) }
+        })
+        .buffered(concurrency.max(1));
+
+    let mut batch = ChunkedBatch {
+        items: Vec::new(),
+        not_found: None,
+        failures: Vec::new(),
+    };
+    while let Some((ids, result)) = results.next().await {
+        match result {
+            Ok((mut items, not_found)) => {
+                on_chunk(&mut items)?;
+                batch.items.extend(items);
+                if let Some(not_found) = not_found {
+                    batch.not_found.get_or_insert_with(Vec::new).extend(not_found);
+                }
+            }
+            Err(e) => batch.failures.push((ids, e)),
         }
     }
 
-    Ok(())
+    if chunk_count > 0 && batch.failures.len() == chunk_count {
+        return Err(batch.failures.swap_remove(0).1);
+    }
+
+    Ok(batch)
 }
 
-/// Enrich item metadata
-pub async fn enrich(
-    id: &str,
-    title: Option<&str>,
-    author: Option<&str>,
-    description: Option<&str>,
-    confidence: Option<f64>,
-    toc_json: Option<&str>,
-    session: Option<String>,
-    no_session: bool,
+/// Read content from items
+/// Format: "id1:1-5,id2:all,id3:10-20"
+///
+/// With `grep`, only pages whose content matches the pattern are kept
+/// (case-insensitive unless `case_sensitive`).
+pub async fn read(
+    ids_str: &str,
+    read: ReadOptions,
+    batch: BatchOptions,
+    format: OutputFormat,
+    opts: &ClientOptions,
 ) -> Result<()> {
-    if title.is_none() && author.is_none() && description.is_none() && toc_json.is_none() {
+    if read.encoding.is_set() && (!read.no_headers || format.is_structured()) {
         return Err(anyhow::anyhow!(
-            "At least one of --title, --author, --description, or --toc is required"
+            "--line-ending and --bom apply to raw content; add --no-headers"
         ));
     }
 
-    if let Some(conf) = confidence {
-        if !(0.0..=1.0).contains(&conf) {
-            return Err(anyhow::anyhow!("Confidence must be between 0.0 and 1.0"));
+    let ranged = if read.chapter.is_empty() && read.window.is_none() {
+        Some(parse_ids_with_ranges(ids_str)?)
+    } else {
+        None
+    };
+
+    let pattern = read
+        .grep
+        .as_deref()
+        .map(|p| {
+            RegexBuilder::new(p)
+                .case_insensitive(!read.case_sensitive)
+                .build()
+                .with_context(|| format!("Invalid --grep pattern: {}", p))
+        })
+        .transpose()?;
+    let highlight = read
+        .highlight
+        .iter()
+        .map(|term| {
+            RegexBuilder::new(term)
+                .case_insensitive(true)
+                .build()
+                .with_context(|| format!("Invalid --highlight pattern: {}", term))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let client = ApiClient::new(opts)?;
+    let mut window = None;
+    let (items, toc_not_found) = match (ranged, read.window) {
+        (Some(items), _) => (items, Vec::new()),
+        (None, Some(size)) => {
+            let (requests, position) = window_request(&client, ids_str, size, read.cursor).await?;
+            window = Some(position);
+            (requests, Vec::new())
         }
-    }
+        (None, None) => {
+            let preview = read.preview_pages.map(|count| (read.offset, count));
+            chapter_requests(&client, ids_str, &read.chapter, preview, batch.concurrency).await?
+        }
+    };
 
-    // Parse TOC JSON if provided
-    let toc: Option<Vec<TocEntry>> = match toc_json {
-        Some(json_str) => {
-            let parsed: Vec<TocEntry> = serde_json::from_str(json_str)
-                .context("Invalid TOC JSON. Expected format: [{\"title\":\"Chapter 1\",\"page\":1,\"level\":1}]")?;
+    let tocs = if read.toc_context {
+        fetch_tocs(&client, &items, batch.concurrency).await
+    } else {
+        Default::default()
+    };
 
-            // Validate TOC entries
-            for entry in &parsed {
-                if entry.title.trim().is_empty() {
-                    return Err(anyhow::anyhow!("TOC entry title cannot be empty"));
-                }
-                if entry.page < 1 {
-                    return Err(anyhow::anyhow!("TOC entry page must be >= 1"));
-                }
-                if let Some(level) = entry.level {
-                    if level < 1 {
-                        return Err(anyhow::anyhow!("TOC entry level must be >= 1"));
-                    }
-                }
-            }
+    let cache = if read.offline {
+        Some(ReadCache::offline(opts)?)
+    } else if read.cache_read {
+        let ttl = std::time::Duration::from_secs(read.cache_ttl);
+        Some(ReadCache::online(opts, ttl)?)
+    } else {
+        None
+    };
 
-            Some(parsed)
+    let fetch_chunk = |chunk| {
+        let client = &client;
+        let cache = cache.as_ref();
+        async move {
+            let fetch = |chunk| async move {
+                client.batch_read(chunk).await.map(|r| (r.items, r.not_found))
+            };
+            let versions = || async move {
+                let items = client.list_items().await?.items;
+                Ok(items.into_iter().map(|item| (item.id, item.updated_at)).collect())
+            };
+            match cache {
+                Some(cache) => cache.read_through(chunk, fetch, versions).await,
+                None => fetch(chunk).await,
+            }
         }
-        None => None,
     };
 
-    let client = ApiClient::new(session, no_session)?;
-    let response = client.enrich_item(id, title, author, description, confidence, toc.clone()).await?;
+    if read.ndjson {
+        let keep = |page: &Page| {
+            pattern
+                .as_ref()
+                .is_none_or(|p| page.content.as_deref().is_some_and(|c| p.is_match(c)))
+        };
+        let mut response = fetch_in_chunks_with(
+            items,
+            batch.concurrency,
+            |request| request.id.as_str(),
+            fetch_chunk,
+            |chunk| {
+                for item in chunk.iter_mut() {
+                    item.pages.retain(keep);
+                    if read.skip_empty {
+                        skip_empty_pages(item, read.min_chars);
+                    }
+                    annotate_chapters(item, &tocs);
+                    if read.strip_markdown {
+                        strip_markdown(item, true);
+                    }
+                    output::print_event(item, OutputFormat::Json)?;
+                }
+                Ok(())
+            },
+        )
+        .await?;
+        response.not_found.get_or_insert_with(Vec::new).extend(toc_not_found);
 
-    output::print_success(&format!(
-        "Enriched: {} (ID: {})",
-        response.item.title,
-        response.item.id.cyan()
-    ));
+        let not_found = response.not_found.as_deref().unwrap_or_default();
+        output::print_ndjson_end(not_found, &super::invalid_ids(not_found), window.as_ref())?;
+        return response.finish(batch.strict);
+    }
 
-    if let Some(author) = &response.item.author {
-        output::print_info(&format!("Author: {}", author));
+    let fetch = fetch_in_chunks(items, batch.concurrency, |request| request.id.as_str(), fetch_chunk);
+    let mut response = spinner::with_spinner("Reading pages...", format, fetch).await?;
+    if !toc_not_found.is_empty() {
+        response
+            .not_found
+            .get_or_insert_with(Vec::new)
+            .extend(toc_not_found);
     }
 
-    if let Some(desc) = &response.item.description {
-        let preview = if desc.len() > 80 {
-            format!("{}...", &desc[..77])
-        } else {
-            desc.clone()
-        };
-        output::print_info(&format!("Description: {}", preview));
+    for item in &mut response.items {
+        annotate_chapters(item, &tocs);
     }
 
-    if let Some(ref toc_entries) = toc {
-        output::print_info(&format!("TOC: {} entries added", toc_entries.len()));
+    let mut display = output::ContentDisplay::default();
+    if let Some(ref pattern) = pattern {
+        for item in &mut response.items {
+            display
+                .pages_before_filter
+                .insert(item.id.clone(), item.pages.len());
+            item.pages.retain(|page| {
+                page.content
+                    .as_deref()
+                    .is_some_and(|content| pattern.is_match(content))
+            });
+        }
+    }
+    if read.skip_empty {
+        for item in &mut response.items {
+            skip_empty_pages(item, read.min_chars);
+        }
+    }
+    if read.strip_markdown {
+        // Stats, text output, and files work on the plain text; structured
+        // output carries both
+        let keep_raw = format.is_structured() && !read.stats && read.files.output_dir.is_none();
+        for item in &mut response.items {
+            strip_markdown(item, keep_raw);
+        }
+    }
+    display.highlight = pattern.into_iter().chain(highlight).collect();
+    display.include_metadata = read.include_metadata;
+    display.encoding = read.encoding.encoding();
+    if read.no_headers {
+        display.raw_delimiter = Some(
+            read.page_delimiter
+                .as_deref()
+                .map(output::unescape)
+                .unwrap_or_else(|| "\n".to_string()),
+        );
     }
 
-    if let Some(conf) = response.item.enrichment_confidence {
-        output::print_info(&format!("Confidence: {:.1}%", conf * 100.0));
+    if let Some(ref dir) = read.files.output_dir {
+        let contents = response
+            .items
+            .iter()
+            .map(|item| (item.id.as_str(), item.title.as_str(), joined_pages(&item.pages)));
+        let written = file_names::write_files(dir, &read.files.name_template, contents)?;
+        print_written_files(&written, dir, format)?;
+        if !format.is_structured() {
+            super::warn_not_found(response.not_found.as_deref().unwrap_or_default());
+        }
+        return response.finish(batch.strict);
     }
 
-    if response.item.needs_enrichment {
-        output::print_warning("Still flagged for enrichment (confidence < 80%)");
+    if read.stats {
+        let stats: Vec<output::ContentStats> = response
+            .items
+            .iter()
+            .map(|item| {
+                output::ContentStats::from_pages(
+                    &item.id,
+                    &item.title,
+                    item.pages.iter().map(|p| p.content.as_deref().unwrap_or("")),
+                )
+            })
+            .collect();
+
+        let not_found = response.not_found.as_deref().unwrap_or_default();
+        if format.is_structured() {
+            output::print_content_stats_serialized(&stats, not_found, format)?;
+        } else {
+            output::print_content_stats(&stats);
+            super::warn_not_found(not_found);
+        }
+        return response.finish(batch.strict);
     }
 
-    Ok(())
+    if format.is_structured() {
+        let invalid = super::invalid_ids(response.not_found.as_deref().unwrap_or_default());
+        output::print_item_content_serialized(
+            &response.items,
+            &response.not_found,
+            &invalid,
+            window.as_ref(),
+            format,
+        )?;
+    } else {
+        output::print_item_content(&response.items, &display);
+        let not_found = response.not_found.as_deref().unwrap_or_default();
+        if read.no_headers {
+            // Keep stdout to document text only
+            for warning in super::not_found_warnings(not_found) {
+                output::eprint_warning(&warning);
+            }
+        } else {
+            super::warn_not_found(not_found);
+        }
+
+        if let Some(ref position) = window {
+            let hint = match position.next_cursor {
+                Some(next) => format!(
+                    "next: ck items read {} --window {} --cursor {}",
+                    ids_str.trim(),
+                    position.window,
+                    next
+                ),
+                None => format!("End of document ({} pages)", position.page_count),
+            };
+            if read.no_headers {
+                eprintln!("{}", hint);
+            } else {
+                println!();
+                println!("{}", hint.dimmed());
+            }
+        }
+    }
+
+    response.finish(batch.strict)
+}
+
+/// Pages shown by `items tail`: the last `count` of `page_count`, clamped to
+/// the whole document. `None` when it has no pages.
+fn tail_range(page_count: i32, count: u32) -> Option<(i32, i32)> {
+    if page_count <= 0 {
+        return None;
+    }
+    let count = i32::try_from(count).unwrap_or(i32::MAX);
+    Some(((page_count - count + 1).max(1), page_count))
+}
+
+/// Read the last `count` pages of an item
+pub async fn tail(id: &str, count: u32, format: OutputFormat, opts: &ClientOptions) -> Result<()> {
+    let client = ApiClient::new(opts)?;
+
+    // The TOC response carries the page count without any page content
+    let toc = client.batch_toc(vec![id.to_string()]).await?;
+    let item = toc
+        .items
+        .into_iter()
+        .find(|item| item.id == id)
+        .ok_or_else(|| anyhow::anyhow!("Item not found: {}", id))?;
+
+    let Some((start, end)) = tail_range(item.page_count, count) else {
+        if format.is_structured() {
+            output::print_item_content_serialized(&[], &None, &[], None, format)?;
+        } else {
+            output::print_info(&format!("{} has no pages", item.title));
+        }
+        return Ok(());
+    };
+
+    let pages = if start == end {
+        start.to_string()
+    } else {
+        format!("{}-{}", start, end)
+    };
+    let response = client
+        .batch_read(vec![ItemReadRequest { id: item.id, pages: Some(pages) }])
+        .await?;
+
+    if format.is_structured() {
+        output::print_item_content_serialized(
+            &response.items,
+            &response.not_found,
+            &[],
+            None,
+            format,
+        )?;
+    } else {
+        output::print_item_content(&response.items, &output::ContentDisplay::default());
+    }
+    Ok(())
+}
+
+/// Default share of pages that must have text for `items verify` to pass
+pub const DEFAULT_MIN_CONTENT_RATIO: f64 = 0.8;
+
+/// Extraction health of one item, as reported by `items verify`
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct VerifyReport {
+    id: String,
+    title: String,
+    status: String,
+    page_count: i32,
+    /// Page numbers whose content is missing or only whitespace
+    empty_pages: Vec<i32>,
+    /// Share of returned pages that have text (0.0-1.0)
+    content_ratio: f64,
+    has_toc: bool,
+    ok: bool,
+}
+
+impl VerifyReport {
+    fn new(item: &api::ItemWithPages, has_toc: bool, min_content_ratio: f64) -> Self {
+        let empty_pages: Vec<i32> = item
+            .pages
+            .iter()
+            .filter(|page| page.content.as_deref().is_none_or(|c| c.trim().is_empty()))
+            .map(|page| page.page_num)
+            .collect();
+        let content_ratio = if item.pages.is_empty() {
+            0.0
+        } else {
+            (item.pages.len() - empty_pages.len()) as f64 / item.pages.len() as f64
+        };
+
+        Self {
+            id: item.id.clone(),
+            title: item.title.clone(),
+            status: item.status.clone(),
+            page_count: item.page_count,
+            ok: item.status.eq_ignore_ascii_case("READY") && content_ratio >= min_content_ratio,
+            empty_pages,
+            content_ratio,
+            has_toc,
+        }
+    }
+
+    /// The report as a checklist; `pages_read` is how many pages came back
+    fn checks(&self, pages_read: usize, min_content_ratio: f64) -> Vec<CheckResult> {
+        let mut checks = Vec::new();
+
+        let status = output::format_status(&self.status).to_string();
+        if self.status.eq_ignore_ascii_case("READY") {
+            checks.push(CheckResult::pass("Status", status));
+        } else {
+            checks.push(CheckResult::fail(
+                "Status",
+                status,
+                "Processing didn't finish; re-upload the file or check its job",
+            ));
+        }
+
+        let content = format!(
+            "{} of {} pages have text ({:.0}%, need {:.0}%)",
+            pages_read - self.empty_pages.len(),
+            pages_read,
+            self.content_ratio * 100.0,
+            min_content_ratio * 100.0
+        );
+        let empty: Vec<String> = self.empty_pages.iter().map(|p| p.to_string()).collect();
+        checks.push(CheckResult {
+            name: "Content",
+            status: if self.content_ratio >= min_content_ratio {
+                CheckStatus::Pass
+            } else {
+                CheckStatus::Fail
+            },
+            detail: content,
+            hint: (!empty.is_empty()).then(|| format!("Empty pages: {}", empty.join(", "))),
+        });
+
+        if self.has_toc {
+            checks.push(CheckResult::pass("TOC", "Present"));
+        } else {
+            checks.push(CheckResult::warn(
+                "TOC",
+                "None",
+                format!("Add one with 'ck items enrich {} --toc <JSON>'", self.id),
+            ));
+        }
+
+        checks
+    }
+}
+
+/// Check that an item processed cleanly: it's READY, enough pages have
+/// text, and whether it has a TOC. Fails when the extraction looks broken.
+///
+/// With `quiet` nothing but an `{ok}` object (for `--json`/`--yaml`) is
+/// printed, and any failure, including a failed lookup, is returned as `false`.
+pub async fn verify(
+    id: &str,
+    min_content_ratio: f64,
+    quiet: bool,
+    format: OutputFormat,
+    opts: &ClientOptions,
+) -> Result<bool> {
+    let fetched = verify_report(id, min_content_ratio, opts).await;
+    if quiet {
+        let ok = fetched.as_ref().is_ok_and(|(report, _)| report.ok);
+        if format.is_structured() {
+            output::print_check_status(ok, format)?;
+        }
+        return Ok(ok);
+    }
+    let (report, pages_read) = fetched?;
+
+    if format.is_structured() {
+        output::print_serialized(&report, format)?;
+    } else {
+        println!("{} ({})", report.title.bold(), report.id.dimmed());
+        output::print_checks(&report.checks(pages_read, min_content_ratio));
+    }
+
+    if !report.ok {
+        return Err(anyhow::anyhow!("{} looks like a failed extraction", id));
+    }
+    Ok(true)
+}
+
+/// Fetch an item's pages and TOC and build its report, plus the number of
+/// pages read
+async fn verify_report(
+    id: &str,
+    min_content_ratio: f64,
+    opts: &ClientOptions,
+) -> Result<(VerifyReport, usize)> {
+    let client = ApiClient::new(opts)?;
+    let (read, toc) = tokio::try_join!(
+        client.batch_read(vec![ItemReadRequest { id: id.to_string(), pages: None }]),
+        client.batch_toc(vec![id.to_string()]),
+    )?;
+    let item = read
+        .items
+        .into_iter()
+        .find(|item| item.id == id)
+        .ok_or_else(|| anyhow::anyhow!("Item not found: {}", id))?;
+    let has_toc = toc
+        .items
+        .iter()
+        .any(|t| t.id == id && t.toc.as_ref().is_some_and(|entries| !entries.is_empty()));
+
+    Ok((VerifyReport::new(&item, has_toc, min_content_ratio), item.pages.len()))
+}
+
+/// Parse `--min-content-ratio`, a share between 0 and 1
+pub fn parse_ratio(value: &str) -> std::result::Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(ratio) if (0.0..=1.0).contains(&ratio) => Ok(ratio),
+        _ => Err(format!("expected a number between 0 and 1, got '{}'", value)),
+    }
+}
+
+/// Show table of contents for items
+pub async fn toc(
+    ids_str: &str,
+    depth: Option<i32>,
+    flat: bool,
+    batch: BatchOptions,
+    format: OutputFormat,
+    opts: &ClientOptions,
+) -> Result<()> {
+    let ids = parse_ids(ids_str);
+    if ids.is_empty() {
+        return Err(anyhow::anyhow!("No item IDs provided"));
+    }
+
+    let client = ApiClient::new(opts)?;
+    let fetch = fetch_in_chunks(
+        ids,
+        batch.concurrency,
+        |id| id.as_str(),
+        |chunk| {
+            let client = &client;
+            async move { client.batch_toc(chunk).await.map(|r| (r.items, r.not_found)) }
+        },
+    );
+    let mut response = spinner::with_spinner("Loading table of contents...", format, fetch).await?;
+
+    // Entries without a level are treated as top-level (level 0), matching
+    // how they are indented when printed
+    if let Some(max_level) = depth {
+        for item in &mut response.items {
+            if let Some(ref mut entries) = item.toc {
+                entries.retain(|e| e.level.unwrap_or(0) <= max_level);
+            }
+        }
+    }
+
+    if format.is_structured() {
+        let invalid = super::invalid_ids(response.not_found.as_deref().unwrap_or_default());
+        output::print_toc_serialized(
+            &response.items,
+            flat,
+            &response.not_found,
+            &invalid,
+            format,
+        )?;
+    } else {
+        output::print_toc(&response.items, flat);
+        super::warn_not_found(response.not_found.as_deref().unwrap_or_default());
+    }
+
+    response.finish(batch.strict)
+}
+
+/// Largest file `items add --from-url` will download
+const MAX_DOWNLOAD_BYTES: u64 = 512 * 1024 * 1024;
+
+/// File types the backend accepts for uploads, extension to MIME type, in
+/// the order the first extension for a type is preferred when naming files
+const DEFAULT_UPLOAD_TYPES: &[(&str, &str)] = &[
+    ("pdf", "application/pdf"),
+    ("md", "text/markdown"),
+    ("markdown", "text/markdown"),
+    ("epub", "application/epub+zip"),
+];
+
+/// Sent with `--allow-any-type` for files of no known type
+const ANY_CONTENT_TYPE: &str = "application/octet-stream";
+
+/// A MIME type without parameters, lowercased
+fn bare_mime(value: &str) -> String {
+    value.split(';').next().unwrap_or("").trim().to_lowercase()
+}
+
+/// The file types `items add` accepts: `DEFAULT_UPLOAD_TYPES` plus
+/// `[upload.types]`, which may also remap a built-in extension
+struct UploadTypes {
+    by_extension: Vec<(String, String)>,
+}
+
+impl UploadTypes {
+    fn new(configured: impl IntoIterator<Item = (String, String)>) -> Self {
+        let mut by_extension: Vec<(String, String)> = DEFAULT_UPLOAD_TYPES
+            .iter()
+            .map(|(ext, mime)| (ext.to_string(), mime.to_string()))
+            .collect();
+        for (ext, mime) in configured {
+            let ext = ext.trim().trim_start_matches('.').to_lowercase();
+            let mime = bare_mime(&mime);
+            if ext.is_empty() || mime.is_empty() {
+                continue;
+            }
+            match by_extension.iter_mut().find(|(known, _)| *known == ext) {
+                Some(entry) => entry.1 = mime,
+                None => by_extension.push((ext, mime)),
+            }
+        }
+        Self { by_extension }
+    }
+
+    /// The built-in types plus those in config
+    fn load() -> Result<Self> {
+        Ok(Self::new(config::get_upload_types()?))
+    }
+
+    /// Content type for a file extension, if it's an accepted one
+    fn for_extension(&self, extension: &str) -> Option<&str> {
+        let extension = extension.to_lowercase();
+        self.by_extension
+            .iter()
+            .find(|(ext, _)| *ext == extension)
+            .map(|(_, mime)| mime.as_str())
+    }
+
+    /// File extension to name a download of `content_type` with
+    fn extension_for(&self, content_type: &str) -> &str {
+        self.by_extension
+            .iter()
+            .find(|(_, mime)| mime == content_type)
+            .map(|(ext, _)| ext.as_str())
+            .unwrap_or("bin")
+    }
+
+    fn supports(&self, content_type: &str) -> bool {
+        self.by_extension.iter().any(|(_, mime)| mime == content_type)
+    }
+
+    /// Accepted MIME types, each once
+    fn content_types(&self) -> Vec<&str> {
+        let mut types: Vec<&str> = Vec::new();
+        for (_, mime) in &self.by_extension {
+            if !types.contains(&mime.as_str()) {
+                types.push(mime);
+            }
+        }
+        types
+    }
+
+    /// Accepted extensions for error messages, e.g. ".pdf, .md"
+    fn describe_extensions(&self) -> String {
+        let extensions: Vec<String> =
+            self.by_extension.iter().map(|(ext, _)| format!(".{}", ext)).collect();
+        extensions.join(", ")
+    }
+}
+
+/// Validate a `--content-type` value against the accepted types, ignoring
+/// case and any `; charset=...` parameters
+fn parse_content_type(value: &str, types: &UploadTypes) -> Result<String> {
+    let mime = bare_mime(value);
+    if types.supports(&mime) {
+        return Ok(mime);
+    }
+    Err(anyhow::anyhow!(
+        "Unsupported --content-type '{}'. Use one of: {} (or pass --allow-any-type)",
+        value,
+        types.content_types().join(", ")
+    ))
+}
+
+/// Flags for `items add` beyond the document to upload
+#[derive(Debug, Clone, clap::Args)]
+pub struct AddOptions {
+    /// Skip the item-limit and size pre-checks and let the server decide
+    #[arg(long)]
+    pub force: bool,
+    /// Print plain progress lines instead of animated progress bars
+    /// (automatic when stdout isn't a terminal)
+    #[arg(long)]
+    pub quiet_progress: bool,
+    /// Upload with this MIME type instead of guessing from the extension or
+    /// Content-Type header (application/pdf, text/markdown, application/epub+zip,
+    /// or one added under [upload.types])
+    #[arg(long, value_name = "MIME")]
+    pub content_type: Option<String>,
+    /// Upload files of any type, sending unknown ones as application/octet-stream
+    /// and any --content-type as given (the server may still refuse them)
+    #[arg(long)]
+    pub allow_any_type: bool,
+    /// Refuse files larger than this (e.g. 50MB; overrides [upload] max_size).
+    /// --force skips the check.
+    #[arg(long, value_name = "SIZE", value_parser = config::parse_size)]
+    pub max_size: Option<u64>,
+    /// Title for the new item (one file only)
+    #[arg(long)]
+    pub title: Option<String>,
+    /// Author for the new item(s)
+    #[arg(long)]
+    pub author: Option<String>,
+    /// Description for the new item(s)
+    #[arg(long)]
+    pub description: Option<String>,
+    /// Wait until the server has finished processing each new item
+    #[arg(long)]
+    pub wait: bool,
+    /// With --wait or --open, give up waiting after this many seconds
+    #[arg(long, value_name = "SECS", default_value_t = DEFAULT_WAIT_TIMEOUT_SECS)]
+    pub wait_timeout: u64,
+    /// Wait for processing, then open the item in your browser (one file only)
+    #[arg(long, overrides_with = "no_open")]
+    pub open: bool,
+    /// Don't open the browser (overrides an earlier --open)
+    #[arg(long, overrides_with = "open")]
+    pub no_open: bool,
+}
+
+/// Default for `items add --wait-timeout`
+const DEFAULT_WAIT_TIMEOUT_SECS: u64 = 600;
+/// Time between status checks while waiting for processing
+const WAIT_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// The web app page for an item: `<base>/library/<id>`
+fn item_web_url(base_url: &str, item_id: &str) -> Result<String> {
+    if !super::is_valid_id(item_id) {
+        return Err(anyhow::anyhow!("Not a valid item ID: {}", item_id));
+    }
+    let mut url = reqwest::Url::parse(base_url)
+        .with_context(|| format!("Invalid base URL: {}", base_url))?;
+    url.path_segments_mut()
+        .map_err(|_| anyhow::anyhow!("Invalid base URL: {}", base_url))?
+        .pop_if_empty()
+        .extend(["library", item_id]);
+    Ok(url.to_string())
+}
+
+/// Whether a browser can be opened for the user: not non-interactive, a
+/// terminal is attached, and on Linux a display is available
+fn can_open_browser() -> bool {
+    let has_display = !cfg!(target_os = "linux")
+        || ["DISPLAY", "WAYLAND_DISPLAY"]
+            .iter()
+            .any(|var| std::env::var_os(var).is_some_and(|v| !v.is_empty()));
+    !prompt::non_interactive() && io::stderr().is_terminal() && has_display
+}
+
+/// Poll the library until `item_id` leaves processing, returning its final
+/// status (READY or FAILED)
+async fn wait_for_processing(
+    client: &ApiClient,
+    item_id: &str,
+    timeout: std::time::Duration,
+) -> Result<String> {
+    let started = std::time::Instant::now();
+    loop {
+        let items = client.list_items().await?.items;
+        let item = items
+            .iter()
+            .find(|item| item.id == item_id)
+            .ok_or_else(|| anyhow::anyhow!("Item {} is no longer in your library", item_id))?;
+        if item.status.eq_ignore_ascii_case("READY") || item.status.eq_ignore_ascii_case("FAILED") {
+            return Ok(item.status.clone());
+        }
+        if started.elapsed() >= timeout {
+            return Err(anyhow::anyhow!(
+                "{} is still {} after {}s; check on it with 'ck items list'",
+                item_id,
+                item.status,
+                timeout.as_secs()
+            ));
+        }
+        tokio::time::sleep(WAIT_POLL_INTERVAL).await;
+    }
+}
+
+/// What every upload in one `items add` shares, resolved from `AddOptions` and config
+struct UploadPlan {
+    /// `--content-type`, when given
+    content_type: Option<String>,
+    /// Extensions and MIME types accepted without `--content-type`
+    types: UploadTypes,
+    /// `--allow-any-type`
+    allow_any_type: bool,
+    max_size: Option<u64>,
+    progress: ProgressMode,
+    /// Metadata to set on each new item; empty when none was given
+    metadata: MetadataUpdate,
+    /// `--wait`/`--open`: how long to wait for processing to finish
+    wait: Option<std::time::Duration>,
+    /// `--open`: the web URL base to open each item under once it's ready
+    open: Option<String>,
+}
+
+/// Refuse a file over the `--max-size` / `[upload] max_size` limit before
+/// spending time on the upload
+fn check_max_size(name: &str, size: u64, max_size: Option<u64>) -> Result<()> {
+    match max_size {
+        Some(limit) if size > limit => Err(anyhow::anyhow!(
+            "{} is too large ({} bytes, limit {} bytes); pass --force to let the server decide",
+            name,
+            size,
+            limit
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Upload a file (PDF, Markdown, or EPUB) from a local path or a URL
+pub async fn add(
+    files: &[String],
+    from_url: Option<&str>,
+    add: &AddOptions,
+    format: OutputFormat,
+    opts: &ClientOptions,
+) -> Result<()> {
+    let types = UploadTypes::load()?;
+    let content_type = match add.content_type.as_deref() {
+        Some(value) if add.allow_any_type => Some(bare_mime(value)),
+        Some(value) => Some(parse_content_type(value, &types)?),
+        None => None,
+    };
+    if add.title.is_some() && (files.len() > 1 || files.iter().any(|f| is_glob(f))) {
+        return Err(anyhow::anyhow!(
+            "--title names a single item; add one file at a time to give each its own title"
+        ));
+    }
+    if add.open && (files.len() > 1 || files.iter().any(|f| is_glob(f))) {
+        return Err(anyhow::anyhow!("--open works with one file at a time; use --wait instead"));
+    }
+
+    if !add.force {
+        check_item_limit(format, opts).await?;
+    }
+    let max_size = match add.max_size {
+        _ if add.force => None,
+        Some(bytes) => Some(bytes),
+        None => config::get_upload_max_size()?,
+    };
+
+    let plan = UploadPlan {
+        content_type,
+        types,
+        allow_any_type: add.allow_any_type,
+        max_size,
+        progress: ProgressMode::detect(format, add.quiet_progress),
+        metadata: MetadataUpdate {
+            title: add.title.clone(),
+            author: add.author.clone().map(Some),
+            description: add.description.clone().map(Some),
+            ..Default::default()
+        },
+        wait: (add.wait || add.open).then(|| std::time::Duration::from_secs(add.wait_timeout)),
+        open: match add.open {
+            true => Some(config::resolve_api_url(opts.base_url.as_deref())?),
+            false => None,
+        },
+    };
+    match (files, from_url) {
+        ([file_path], None) if !is_glob(file_path) => add_local(file_path, &plan, format, opts).await,
+        ([], Some(url)) => add_from_url(url, &plan, format, opts).await,
+        ([], None) => Err(anyhow::anyhow!("Provide either a file path or --from-url")),
+        (patterns, _) => add_many(patterns, &plan, format, opts).await,
+    }
+}
+
+/// Whether `arg` contains glob syntax the shell didn't expand. An existing
+/// file is always taken literally, so names like `report [v2].pdf` work.
+fn is_glob(arg: &str) -> bool {
+    arg.contains(['*', '?', '[']) && !Path::new(arg).exists()
+}
+
+/// Expand glob patterns among `args`, keeping plain paths as given and
+/// dropping repeats. Patterns that are invalid or match no files are
+/// returned separately as failures.
+fn expand_file_args(args: &[String]) -> (Vec<String>, Vec<(String, String)>) {
+    let mut paths: Vec<String> = Vec::new();
+    let mut failures = Vec::new();
+
+    for arg in args {
+        if !is_glob(arg) {
+            if !paths.contains(arg) {
+                paths.push(arg.clone());
+            }
+            continue;
+        }
+
+        let matches = match glob::glob(arg) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok())
+                .filter(|path| path.is_file())
+                .map(|path| path.to_string_lossy().into_owned())
+                .collect::<Vec<_>>(),
+            Err(e) => {
+                failures.push((arg.clone(), format!("Invalid pattern: {}", e)));
+                continue;
+            }
+        };
+        if matches.is_empty() {
+            failures.push((arg.clone(), "No files match".to_string()));
+        }
+        for path in matches {
+            if !paths.contains(&path) {
+                paths.push(path);
+            }
+        }
+    }
+
+    (paths, failures)
+}
+
+/// Upload several local files one after another. A failed file is reported
+/// and skipped; the command fails at the end if any did.
+async fn add_many(
+    args: &[String],
+    plan: &UploadPlan,
+    format: OutputFormat,
+    opts: &ClientOptions,
+) -> Result<()> {
+    let events = format.is_structured();
+    let (paths, mut failures) = expand_file_args(args);
+
+    for (arg, error) in &failures {
+        if events {
+            output::print_event(&AddEvent::Failed { file: arg, error }, format)?;
+        } else {
+            output::print_error(&format!("{}: {}", arg, error));
+        }
+    }
+
+    let mut added = 0;
+    for (i, path) in paths.iter().enumerate() {
+        if !events {
+            println!("{}", format!("[{}/{}] {}", i + 1, paths.len(), path).bold());
+        }
+        match add_local(path, plan, format, opts).await {
+            Ok(()) => added += 1,
+            Err(e) => {
+                let error = format!("{:#}", e);
+                if events {
+                    output::print_event(&AddEvent::Failed { file: path, error: &error }, format)?;
+                } else {
+                    println!();
+                    output::print_error(&format!("{}: {}", path, error));
+                }
+                failures.push((path.clone(), error));
+            }
+        }
+        if !events {
+            println!();
+        }
+    }
+
+    let failed = failures.len();
+    if events {
+        output::print_event(&AddEvent::Summary { added, failed }, format)?;
+    } else if failed == 0 {
+        output::print_success(&format!("Added {} file(s)", added));
+    } else {
+        output::print_warning(&format!("Added {} file(s), {} failed:", added, failed));
+        for (file, error) in &failures {
+            let reason = error.lines().next().unwrap_or_default();
+            println!("  {} {}", file, format!("({})", reason).dimmed());
+        }
+    }
+
+    if failed > 0 {
+        return Err(anyhow::anyhow!("{} file(s) could not be added", failed));
+    }
+    Ok(())
+}
+
+/// How `items add` reports download and upload progress
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ProgressMode {
+    /// Animated indicatif bars
+    Bar,
+    /// Occasional plain lines, for logs and terminals without ANSI support
+    Plain,
+    /// Nothing; structured output reports progress as events instead
+    Off,
+}
+
+/// Percentage steps at which plain-mode downloads print a line
+const PLAIN_PROGRESS_STEP: u64 = 25;
+
+impl ProgressMode {
+    fn detect(format: OutputFormat, quiet_progress: bool) -> Self {
+        if format.is_structured() {
+            ProgressMode::Off
+        } else if quiet_progress
+            || prompt::non_interactive()
+            || !output::colors_enabled()
+            || !io::stdout().is_terminal()
+            || !io::stderr().is_terminal()
+        {
+            ProgressMode::Plain
+        } else {
+            ProgressMode::Bar
+        }
+    }
+
+    /// A byte-transfer bar, or a hidden one unless mode is `Bar`.
+    /// With no `len` the bar is a spinner.
+    fn transfer_bar(self, len: Option<u64>) -> Result<ProgressBar> {
+        if self != ProgressMode::Bar {
+            return Ok(ProgressBar::hidden());
+        }
+        let pb = match len {
+            Some(len) => ProgressBar::new(len),
+            None => ProgressBar::new_spinner(),
+        };
+        pb.set_style(
+            ProgressStyle::with_template(
+                "{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({eta})",
+            )?
+            .progress_chars("#>-"),
+        );
+        Ok(pb)
+    }
+}
+
+/// Progress events printed by `items add` in structured output mode, one per line
+#[derive(Serialize)]
+#[serde(tag = "phase", rename_all = "snake_case")]
+enum AddEvent<'a> {
+    Warning {
+        message: &'a str,
+    },
+    Downloaded {
+        url: &'a str,
+        bytes: u64,
+    },
+    #[serde(rename_all = "camelCase")]
+    UploadCreated {
+        item_id: &'a str,
+        filename: &'a str,
+        size: u64,
+    },
+    #[serde(rename_all = "camelCase")]
+    Uploaded {
+        item_id: &'a str,
+    },
+    #[serde(rename_all = "camelCase")]
+    Confirmed {
+        item_id: &'a str,
+        job_id: &'a str,
+        status: &'a str,
+    },
+    #[serde(rename_all = "camelCase")]
+    Complete {
+        item_id: &'a str,
+        title: &'a str,
+        job_id: &'a str,
+        job_status: &'a str,
+    },
+    /// `--wait`: processing finished; `url` is set for `--open`
+    #[serde(rename_all = "camelCase")]
+    Processed {
+        item_id: &'a str,
+        status: &'a str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        url: Option<&'a str>,
+        opened: bool,
+    },
+    /// One file of a multi-file add that couldn't be added
+    Failed {
+        file: &'a str,
+        error: &'a str,
+    },
+    /// Totals after a multi-file add
+    Summary {
+        added: usize,
+        failed: usize,
+    },
+}
+
+/// Fraction of the item limit at which `items add` starts warning
+const NEAR_LIMIT_THRESHOLD: f64 = 0.9;
+
+/// Refuse to upload into a full library, and warn when it's nearly full.
+/// If the profile can't be fetched, the server gets to decide.
+async fn check_item_limit(format: OutputFormat, opts: &ClientOptions) -> Result<()> {
+    let Ok(user) = ApiClient::new(opts)?.whoami().await else {
+        return Ok(());
+    };
+
+    if user.at_limit() {
+        return Err(anyhow::anyhow!(
+            "At item limit ({}/{}); upgrade your tier or remove items first (or pass --force)",
+            user.item_count,
+            user.item_limit
+        ));
+    }
+    if user.near_limit(NEAR_LIMIT_THRESHOLD) {
+        let message = format!(
+            "Library is nearly full ({}/{} items)",
+            user.item_count, user.item_limit
+        );
+        if format.is_structured() {
+            output::print_event(&AddEvent::Warning { message: &message }, format)?;
+        } else {
+            output::print_warning(&message);
+        }
+    }
+
+    Ok(())
+}
+
+/// Upload a local file
+async fn add_local(
+    file_path: &str,
+    plan: &UploadPlan,
+    format: OutputFormat,
+    opts: &ClientOptions,
+) -> Result<()> {
+    let path = Path::new(file_path);
+
+    // Validate file exists
+    if !path.exists() {
+        return Err(anyhow::anyhow!("File not found: {}", file_path));
+    }
+
+    // Get content type based on extension
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase());
+
+    let detected = extension.as_deref().and_then(|ext| plan.types.for_extension(ext));
+    let content_type = match plan.content_type.as_deref().or(detected) {
+        Some(content_type) => content_type,
+        None if plan.allow_any_type => ANY_CONTENT_TYPE,
+        None => {
+            return Err(anyhow::anyhow!(
+                "Unsupported file type. Supported extensions: {} (add more under \
+                [upload.types] in config). Got: {}\n\
+                Pass --content-type or --allow-any-type to upload it anyway.",
+                plan.types.describe_extensions(),
+                extension.unwrap_or_else(|| "no extension".to_string())
+            ));
+        }
+    };
+
+    let filename = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .context("Invalid filename")?
+        .to_string();
+
+    let client = ApiClient::new(opts)?;
+    upload(&client, path, &filename, content_type, plan, format).await
+}
+
+/// Removes the wrapped file when dropped, so downloads are cleaned up on any
+/// exit path, including Ctrl-C
+struct TempFile(std::path::PathBuf);
+
+impl TempFile {
+    fn new(path: std::path::PathBuf) -> Self {
+        interrupt::track_temp_file(path.clone());
+        Self(path)
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+        interrupt::forget_temp_file(&self.0);
+    }
+}
+
+/// Download a document from a URL to a temp file, then upload it
+async fn add_from_url(
+    url: &str,
+    plan: &UploadPlan,
+    format: OutputFormat,
+    opts: &ClientOptions,
+) -> Result<()> {
+    let events = format.is_structured();
+    let parsed = reqwest::Url::parse(url).with_context(|| format!("Invalid URL: {}", url))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(anyhow::anyhow!("Only http and https URLs are supported: {}", url));
+    }
+
+    let client = ApiClient::new(opts)?;
+
+    if !events {
+        println!("{}", format!("Downloading: {}", url).cyan());
+    }
+    let response = client.download(url).await?;
+
+    if let Some(len) = response.content_length() {
+        check_max_size(url, len, plan.max_size)?;
+        if len > MAX_DOWNLOAD_BYTES {
+            return Err(anyhow::anyhow!(
+                "Remote file is too large ({} bytes, limit {} bytes)",
+                len,
+                MAX_DOWNLOAD_BYTES
+            ));
+        }
+    }
+
+    // Name from the last path segment, e.g. https://host/papers/attention.pdf
+    let url_name = parsed
+        .path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .filter(|name| !name.is_empty())
+        .map(|name| name.to_string());
+    let url_extension = url_name
+        .as_deref()
+        .and_then(|name| Path::new(name).extension())
+        .and_then(|e| e.to_str());
+
+    // Trust a specific Content-Type header; fall back to the URL's extension
+    // when the server sends a generic type
+    let header_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.split(';').next().unwrap_or("").trim().to_lowercase())
+        .unwrap_or_default();
+
+    let header_type = match header_type.as_str() {
+        "text/x-markdown" => "text/markdown".to_string(),
+        _ => header_type,
+    };
+    let generic = matches!(
+        header_type.as_str(),
+        "" | "application/octet-stream" | "binary/octet-stream" | "text/plain"
+    );
+    let by_extension = url_extension.and_then(|ext| plan.types.for_extension(ext));
+    let supported = plan.types.content_types().join(", ");
+
+    let content_type = match (&plan.content_type, generic, by_extension) {
+        (Some(forced), _, _) => forced.as_str(),
+        (None, false, _) if plan.types.supports(&header_type) => header_type.as_str(),
+        (None, true, Some(detected)) => detected,
+        // A generic type someone added under [upload.types], e.g. text/plain
+        (None, true, None) if plan.types.supports(&header_type) => header_type.as_str(),
+        (None, _, _) if plan.allow_any_type => ANY_CONTENT_TYPE,
+        (None, true, None) => {
+            return Err(anyhow::anyhow!(
+                "Could not determine a supported file type for {} (Content-Type: {}). \
+                Supported types: {}",
+                url,
+                if header_type.is_empty() { "none" } else { &header_type },
+                supported
+            ));
+        }
+        (None, false, _) => {
+            return Err(anyhow::anyhow!(
+                "Unsupported content type: {}. Supported types: {}",
+                header_type,
+                supported
+            ));
+        }
+    };
+
+    let filename = match url_name {
+        Some(name) if by_extension == Some(content_type) => name,
+        Some(name) => format!("{}.{}", name, plan.types.extension_for(content_type)),
+        None => format!("download.{}", plan.types.extension_for(content_type)),
+    };
+
+    let temp = TempFile::new(std::env::temp_dir().join(format!(
+        "ck-download-{}-{}",
+        std::process::id(),
+        filename
+    )));
+
+    let total = response.content_length();
+    let progress = plan.progress;
+    let pb = progress.transfer_bar(total)?;
+    let mut next_step = PLAIN_PROGRESS_STEP;
+
+    let mut file = std::fs::File::create(&temp.0).context("Failed to create temp file")?;
+    let mut downloaded: u64 = 0;
+    let mut stream = response.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.context("Download interrupted")?;
+        downloaded += chunk.len() as u64;
+        if downloaded > MAX_DOWNLOAD_BYTES {
+            pb.abandon();
+            return Err(anyhow::anyhow!(
+                "Remote file exceeds the {} byte download limit",
+                MAX_DOWNLOAD_BYTES
+            ));
+        }
+        file.write_all(&chunk).context("Failed to write temp file")?;
+        pb.set_position(downloaded);
+        if let (ProgressMode::Plain, Some(total)) = (progress, total) {
+            let percent = (downloaded * 100 / total.max(1)).min(100);
+            if percent >= next_step {
+                eprintln!("Downloading... {}%", percent);
+                next_step = (percent / PLAIN_PROGRESS_STEP + 1) * PLAIN_PROGRESS_STEP;
+            }
+        }
+    }
+    file.flush()?;
+    drop(file);
+    pb.finish_with_message("Download complete");
+    if progress == ProgressMode::Plain {
+        eprintln!("Download complete ({} bytes)", downloaded);
+    }
+    if events {
+        output::print_event(&AddEvent::Downloaded { url, bytes: downloaded }, format)?;
+    }
+
+    upload(&client, &temp.0, &filename, content_type, plan, format).await
+}
+
+/// Whether the confirmed item already carries every field in `metadata`
+fn metadata_applied(item: &api::ConfirmItem, metadata: &MetadataUpdate) -> bool {
+    let matches = |wanted: &Option<Option<String>>, actual: &Option<String>| match wanted {
+        Some(value) => value == actual,
+        None => true,
+    };
+    metadata.title.as_ref().is_none_or(|title| title == &item.title)
+        && matches(&metadata.author, &item.author)
+        && matches(&metadata.description, &item.description)
+}
+
+/// Upload a file through the presigned-URL flow and confirm it
+async fn upload(
+    client: &ApiClient,
+    path: &Path,
+    filename: &str,
+    content_type: &str,
+    plan: &UploadPlan,
+    format: OutputFormat,
+) -> Result<()> {
+    let events = format.is_structured();
+    let progress = plan.progress;
+
+    // Get file info
+    let metadata = std::fs::metadata(path).context("Failed to read file metadata")?;
+    let size = metadata.len();
+    check_max_size(filename, size, plan.max_size)?;
+
+    if !events {
+        println!("{}", format!("Uploading: {}", filename).cyan());
+        println!("{}", format!("Size: {} bytes", size).dimmed());
+
+        print!("{}", "Creating upload...".dimmed());
+        io::stdout().flush()?;
+    }
+
+    // Step 1: Get presigned upload URL
+    let upload_info = client
+        .create_upload(filename, size, content_type)
+        .await?;
+    // Deleted again if we're interrupted before the confirm goes through
+    let _pending = interrupt::PendingUpload::new(&upload_info.item_id);
+
+    if events {
+        output::print_event(
+            &AddEvent::UploadCreated {
+                item_id: &upload_info.item_id,
+                filename,
+                size,
+            },
+            format,
+        )?;
+    } else {
+        println!(" {}", "OK".green());
+    }
+
+    // Step 2: Upload file to presigned URL
+    let pb = progress.transfer_bar(Some(size))?;
+
+    // Read the file
+    let file_data = std::fs::read(path).context("Failed to read file")?;
+
+    pb.set_position(0);
+    pb.set_message("Uploading...");
+
+    // Upload to presigned URL
+    client
+        .upload_file(&upload_info.upload_url, file_data, content_type)
+        .await?;
+
+    pb.set_position(size);
+    pb.finish_with_message("Upload complete");
+    if progress == ProgressMode::Plain {
+        eprintln!("Uploaded {} bytes (100%)", size);
+        eprintln!("Upload complete");
+    }
+
+    if events {
+        output::print_event(&AddEvent::Uploaded { item_id: &upload_info.item_id }, format)?;
+    } else {
+        print!("{}", "Processing...".dimmed());
+        io::stdout().flush()?;
+    }
+
+    // Step 3: Confirm upload. The same idempotency key on every attempt lets
+    // the server ignore a retry whose first try went through.
+    let idempotency_key = api::new_idempotency_key();
+    let mut confirm = api::retry_transient(api::RETRY_ATTEMPTS, api::RETRY_DELAY, || {
+        client.confirm_upload(
+            &upload_info.item_id,
+            &upload_info.storage_key,
+            &plan.metadata,
+            Some(&idempotency_key),
+        )
+    })
+    .await?;
+
+    // Older servers ignore metadata on confirm; set it with a separate call
+    if !metadata_applied(&confirm.item, &plan.metadata) {
+        match client.enrich_item(&confirm.item.id, &plan.metadata).await {
+            Ok(response) => confirm.item.title = response.item.title,
+            Err(e) => {
+                let message = format!(
+                    "Uploaded, but setting metadata failed: {:#}. Set it with 'ck items enrich {}'",
+                    e, confirm.item.id
+                );
+                if events {
+                    output::print_event(&AddEvent::Warning { message: &message }, format)?;
+                } else {
+                    println!();
+                    output::print_warning(&message);
+                }
+            }
+        }
+    }
+
+    if events {
+        output::print_event(
+            &AddEvent::Confirmed {
+                item_id: &confirm.item.id,
+                job_id: &confirm.job.id,
+                status: &confirm.job.status,
+            },
+            format,
+        )?;
+        output::print_event(
+            &AddEvent::Complete {
+                item_id: &confirm.item.id,
+                title: &confirm.item.title,
+                job_id: &confirm.job.id,
+                job_status: &confirm.job.status,
+            },
+            format,
+        )?;
+    } else {
+        println!(" {}", "OK".green());
+
+        output::print_success(&format!(
+            "Added: {} (ID: {})",
+            confirm.item.title,
+            confirm.item.id.cyan()
+        ));
+        output::print_info(&format!(
+            "Processing job created: {} ({})",
+            confirm.job.id,
+            confirm.job.status
+        ));
+    }
+
+    let Some(timeout) = plan.wait else {
+        return Ok(());
+    };
+    let wait = wait_for_processing(client, &confirm.item.id, timeout);
+    let status = spinner::with_spinner("Waiting for processing...", format, wait).await?;
+    let ready = status.eq_ignore_ascii_case("READY");
+    let url = match plan.open {
+        Some(ref base) if ready => match item_web_url(base, &confirm.item.id) {
+            Ok(url) => Some(url),
+            Err(e) => {
+                let message = format!("Not opening a browser: {:#}", e);
+                if events {
+                    output::print_event(&AddEvent::Warning { message: &message }, format)?;
+                } else {
+                    output::print_warning(&message);
+                }
+                None
+            }
+        },
+        _ => None,
+    };
+    let opened = url.as_deref().is_some_and(|url| can_open_browser() && open::that(url).is_ok());
+
+    if events {
+        output::print_event(
+            &AddEvent::Processed {
+                item_id: &confirm.item.id,
+                status: &status,
+                url: url.as_deref(),
+                opened,
+            },
+            format,
+        )?;
+    } else if ready {
+        output::print_success(&format!("Processed: {} is ready", confirm.item.id.cyan()));
+        match url {
+            Some(_) if opened => output::print_info("Opened in your browser"),
+            Some(url) => output::print_info(&format!("View it at {}", url)),
+            None => {}
+        }
+    }
+
+    if !ready {
+        return Err(anyhow::anyhow!(
+            "Processing failed for {}; see 'ck items verify {}'",
+            confirm.item.id,
+            confirm.item.id
+        ));
+    }
+    Ok(())
+}
+
+/// Remove items. Without IDs (or with `interactive`), the user picks items
+/// from their library in a checkbox list.
+pub async fn remove(
+    ids_str: Option<&str>,
+    interactive: bool,
+    skip_confirm: bool,
+    strict: bool,
+    format: OutputFormat,
+    opts: &ClientOptions,
+) -> Result<()> {
+    let ids = match ids_str {
+        Some(ids_str) if !interactive => parse_ids(ids_str),
+        _ => {
+            if format.is_structured() {
+                return Err(anyhow::anyhow!(
+                    "Interactive selection is not available with --json or --format; pass item IDs"
+                ));
+            }
+            match select_items_to_remove(opts).await? {
+                Some(ids) => ids,
+                None => {
+                    println!("{}", "Cancelled.".dimmed());
+                    return Ok(());
+                }
+            }
+        }
+    };
+    if ids.is_empty() {
+        return Err(anyhow::anyhow!("No item IDs provided"));
+    }
+
+    // Confirm deletion
+    if !skip_confirm {
+        println!(
+            "{}",
+            format!("This will delete {} item(s):", ids.len()).yellow()
+        );
+        for id in &ids {
+            println!("  - {}", id);
+        }
+        println!();
+
+        if !prompt::confirm("Are you sure?", "delete")? {
+            println!("{}", "Cancelled.".dimmed());
+            return Ok(());
+        }
+    }
+
+    let client = ApiClient::new(opts)?;
+    let response = client.delete_items(ids).await?;
+
+    // Report results
+    if !response.deleted.is_empty() {
+        output::print_success(&format!(
+            "Deleted {} item(s): {}",
+            response.deleted.len(),
+            response.deleted.join(", ")
+        ));
+    }
+
+    super::warn_not_found(&response.not_found);
+
+    let storage_errors = response.storage_errors.unwrap_or_default();
+    if !storage_errors.is_empty() {
+        output::print_warning(&format!(
+            "Storage cleanup failed for: {}",
+            storage_errors.join(", ")
+        ));
+    }
+
+    super::check_strict(strict, &response.not_found, &storage_errors)
+}
+
+/// Fetch the library and let the user choose items to delete.
+/// Returns None if nothing was selected.
+async fn select_items_to_remove(opts: &ClientOptions) -> Result<Option<Vec<String>>> {
+    prompt::ensure_can_select()?;
+
+    let client = ApiClient::new(opts)?;
+    let response = client.list_items().await?;
+
+    if response.items.is_empty() {
+        println!("{}", "No items found.".dimmed());
+        return Ok(None);
+    }
+
+    let labels: Vec<String> = response
+        .items
+        .iter()
+        .map(|item| format!("{} ({})", output::truncate_display(&item.title, 60), item.id))
+        .collect();
+
+    let chosen = prompt::select_many("Select items to delete (space to toggle, enter to confirm)", &labels)?;
+    if chosen.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(
+        chosen
+            .into_iter()
+            .map(|i| response.items[i].id.clone())
+            .collect(),
+    ))
+}
+
+/// Metadata field `items enrich --clear` can reset
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ClearField {
+    Author,
+    Description,
+    Tags,
+    Toc,
+}
+
+impl ClearField {
+    fn name(self) -> &'static str {
+        match self {
+            ClearField::Author => "author",
+            ClearField::Description => "description",
+            ClearField::Tags => "tags",
+            ClearField::Toc => "toc",
+        }
+    }
+
+    /// The `items enrich` flag that sets this field
+    fn flag(self) -> String {
+        format!("--{}", self.name())
+    }
+}
+
+/// Add `new` TOC entries to `existing`: entries with the same title and page
+/// are dropped, and the result is sorted by page (existing entries first on
+/// a tie). Returns the merged TOC, how many entries were added, and a
+/// warning for each new entry that lands on a page already holding a
+/// differently titled entry (both are kept).
+fn merge_toc_entries(
+    existing: Vec<TocEntry>,
+    new: Vec<TocEntry>,
+) -> (Vec<TocEntry>, usize, Vec<String>) {
+    let mut merged = existing;
+    let existing_len = merged.len();
+    let mut warnings = Vec::new();
+
+    for entry in new {
+        if merged.iter().any(|e| e.page == entry.page && e.title == entry.title) {
+            continue;
+        }
+        if let Some(other) = merged[..existing_len].iter().find(|e| e.page == entry.page) {
+            warnings.push(format!(
+                "Page {} already has \"{}\"; adding \"{}\" as well",
+                entry.page, other.title, entry.title
+            ));
+        }
+        merged.push(entry);
+    }
+
+    let added = merged.len() - existing_len;
+    merged.sort_by_key(|e| e.page);
+    (merged, added, warnings)
+}
+
+/// Every client-side problem with an `items enrich` request, plus its
+/// parsed `--toc`. Nothing is sent; the request is valid when the list is empty.
+fn check_enrichment(
+    update: &MetadataUpdate,
+    toc_json: Option<&str>,
+    clear: &[ClearField],
+) -> (Option<Vec<TocEntry>>, Vec<String>) {
+    let mut errors = Vec::new();
+    if update.title.is_none()
+        && update.author.is_none()
+        && update.description.is_none()
+        && update.tags.is_none()
+        && toc_json.is_none()
+        && clear.is_empty()
+    {
+        errors.push(
+            "At least one of --title, --author, --description, --tags, --toc, or --clear is required"
+                .to_string(),
+        );
+    }
+
+    for field in clear {
+        let set = match field {
+            ClearField::Author => update.author.is_some(),
+            ClearField::Description => update.description.is_some(),
+            ClearField::Tags => update.tags.is_some(),
+            ClearField::Toc => toc_json.is_some(),
+        };
+        if set {
+            errors.push(format!("Can't both set and clear {}; pick one", field.flag()));
+        }
+    }
+
+    if let Some(conf) = update.confidence {
+        if !(0.0..=1.0).contains(&conf) {
+            errors.push("Confidence must be between 0.0 and 1.0".to_string());
+        }
+    }
+
+    let toc = toc_json.and_then(|json_str| {
+        let parsed: Vec<TocEntry> = match serde_json::from_str(json_str) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                errors.push(format!(
+                    "Invalid TOC JSON. Expected format: \
+                     [{{\"title\":\"Chapter 1\",\"page\":1,\"level\":1}}]: {}",
+                    e
+                ));
+                return None;
+            }
+        };
+
+        // Entries are numbered from 1, as a person counts them
+        for (n, entry) in parsed.iter().enumerate().map(|(i, e)| (i + 1, e)) {
+            if entry.title.trim().is_empty() {
+                errors.push(format!("TOC entry {}: title cannot be empty", n));
+            }
+            if entry.page < 1 {
+                errors.push(format!("TOC entry {}: page must be >= 1", n));
+            }
+            if entry.level.is_some_and(|level| level < 1) {
+                errors.push(format!("TOC entry {}: level must be >= 1", n));
+            }
+        }
+        Some(parsed)
+    });
+
+    (toc, errors)
+}
+
+/// `items enrich --validate-only`: run the client-side checks and report
+/// them without calling the API. Returns whether the request is valid.
+pub fn validate_enrichment(
+    update: &MetadataUpdate,
+    toc_json: Option<&str>,
+    clear: &[ClearField],
+    format: OutputFormat,
+) -> Result<bool> {
+    #[derive(Serialize)]
+    struct Validation<'a> {
+        valid: bool,
+        errors: &'a [String],
+    }
+
+    let (_, errors) = check_enrichment(update, toc_json, clear);
+    let valid = errors.is_empty();
+    if format.is_structured() {
+        output::print_serialized(&Validation { valid, errors: &errors }, format)?;
+    } else if valid {
+        output::print_success("Valid");
+    } else {
+        for error in &errors {
+            output::print_error(error);
+        }
+    }
+    Ok(valid)
+}
+
+/// Enrich item metadata
+pub async fn enrich(
+    id: &str,
+    mut update: MetadataUpdate,
+    toc_json: Option<&str>,
+    merge_toc: bool,
+    clear: &[ClearField],
+    opts: &ClientOptions,
+) -> Result<()> {
+    let (toc, errors) = check_enrichment(&update, toc_json, clear);
+    if !errors.is_empty() {
+        return Err(anyhow::anyhow!("{}", errors.join("\n")));
+    }
+    update.toc = toc;
+
+    for field in clear {
+        match field {
+            ClearField::Author => update.author = Some(None),
+            ClearField::Description => update.description = Some(None),
+            ClearField::Tags => update.tags = Some(Vec::new()),
+            ClearField::Toc => update.toc = Some(Vec::new()),
+        }
+    }
+
+    let client = ApiClient::new(opts)?;
+
+    // With --merge-toc, read the current TOC and send the combined set
+    let mut merged_toc = None;
+    if let (true, Some(new)) = (merge_toc, update.toc.take()) {
+        let existing = client
+            .batch_toc(vec![id.to_string()])
+            .await?
+            .items
+            .into_iter()
+            .find(|item| item.id == id)
+            .ok_or_else(|| anyhow::anyhow!("Item not found: {}", id))?
+            .toc
+            .unwrap_or_default();
+        let (merged, added, warnings) = merge_toc_entries(existing, new);
+        for warning in &warnings {
+            output::print_warning(warning);
+        }
+        merged_toc = Some((added, merged.len()));
+        update.toc = Some(merged);
+    }
+
+    let response = client.enrich_item(id, &update).await?;
+
+    output::print_success(&format!(
+        "Enriched: {} (ID: {})",
+        response.item.title,
+        response.item.id.cyan()
+    ));
+
+    if let Some(author) = &response.item.author {
+        output::print_info(&format!("Author: {}", author));
+    }
+
+    if let Some(desc) = &response.item.description {
+        output::print_info(&format!("Description: {}", output::truncate_display(desc, 80)));
+    }
+
+    if !clear.is_empty() {
+        let names: Vec<&str> = clear.iter().map(|f| f.name()).collect();
+        output::print_info(&format!("Cleared: {}", names.join(", ")));
+    }
+
+    if let Some((added, total)) = merged_toc {
+        output::print_info(&format!("TOC: {} entries merged ({} total)", added, total));
+    } else if let Some(ref toc_entries) = update.toc {
+        if !toc_entries.is_empty() {
+            output::print_info(&format!("TOC: {} entries added", toc_entries.len()));
+        }
+    }
+
+    if let Some(ref tags) = update.tags {
+        if response.item.tags.is_empty() && !tags.is_empty() {
+            output::print_warning("Tags were sent but the server didn't return any; it may not support tags");
+        } else if !response.item.tags.is_empty() {
+            output::print_info(&format!("Tags: {}", response.item.tags.join(", ")));
+        }
+    }
+
+    if let Some(conf) = response.item.enrichment_confidence {
+        output::print_info(&format!("Confidence: {:.1}%", conf * 100.0));
+    }
+
+    if response.item.needs_enrichment {
+        output::print_warning("Still flagged for enrichment (confidence < 80%)");
+    }
+
+    Ok(())
+}
+
+/// Rename an item. Sends only the title, so enrichment confidence and the
+/// needs-enrichment flag are left for the server to keep as they were.
+pub async fn rename(id: &str, title: &str, format: OutputFormat, opts: &ClientOptions) -> Result<()> {
+    let title = title.trim();
+    if title.is_empty() {
+        return Err(anyhow::anyhow!("Title cannot be empty"));
+    }
+
+    let client = ApiClient::new(opts)?;
+    let response = client
+        .enrich_item(
+            id,
+            &MetadataUpdate {
+                title: Some(title.to_string()),
+                ..Default::default()
+            },
+        )
+        .await?;
+
+    if format.is_structured() {
+        output::print_serialized(&response.item, format)?;
+    } else {
+        output::print_success(&format!(
+            "Renamed: {} (ID: {})",
+            response.item.title,
+            response.item.id.cyan()
+        ));
+    }
+
+    Ok(())
 }
 
 /// Flag item as needing enrichment
-pub async fn flag(id: &str, session: Option<String>, no_session: bool) -> Result<()> {
-    let client = ApiClient::new(session, no_session)?;
+pub async fn flag(id: &str, opts: &ClientOptions) -> Result<()> {
+    let client = ApiClient::new(opts)?;
     let response = client.flag_item(id).await?;
 
     output::print_success(&format!(
@@ -380,20 +2744,106 @@ pub async fn flag(id: &str, session: Option<String>, no_session: bool) -> Result
     Ok(())
 }
 
-/// Create a new markdown document
+/// What `items create` does when an item with the same title exists.
+/// Titles match exactly and case-sensitively.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum IfExists {
+    /// Fail without creating anything
+    #[default]
+    Error,
+    /// Print the existing item's ID and create nothing
+    Skip,
+    /// Replace the existing document's content (needs --content)
+    Replace,
+}
+
+/// Create a new markdown document, unless `if_exists` says otherwise for a
+/// title that's already taken
 pub async fn create(
     title: &str,
     description: Option<&str>,
     content: Option<&str>,
-    json: bool,
-    session: Option<String>,
-    no_session: bool,
+    tags: &[String],
+    if_exists: IfExists,
+    format: OutputFormat,
+    opts: &ClientOptions,
 ) -> Result<()> {
-    let client = ApiClient::new(session, no_session)?;
-    let response = client.create_markdown(title, description, content).await?;
+    let client = ApiClient::new(opts)?;
+
+    // Titles aren't unique server-side, so look for clashes first
+    let existing: Vec<Item> = client
+        .list_items()
+        .await?
+        .items
+        .into_iter()
+        .filter(|item| item.title == title)
+        .collect();
+
+    if let Some(first) = existing.first() {
+        let ids: Vec<&str> = existing.iter().map(|item| item.id.as_str()).collect();
+        match if_exists {
+            IfExists::Error => {
+                return Err(anyhow::anyhow!(
+                    "An item titled \"{}\" already exists (ID: {}); \
+                     pass --if-exists skip or --if-exists replace",
+                    title,
+                    ids.join(", ")
+                ));
+            }
+            IfExists::Skip => {
+                if format.is_structured() {
+                    output::print_serialized(first, format)?;
+                } else {
+                    output::print_info(&format!(
+                        "Already exists: {} (ID: {}); nothing created",
+                        first.title,
+                        first.id.cyan()
+                    ));
+                }
+                return Ok(());
+            }
+            IfExists::Replace => {
+                if existing.len() > 1 {
+                    return Err(anyhow::anyhow!(
+                        "{} items are titled \"{}\" ({}); replace one with 'ck items put <id>'",
+                        existing.len(),
+                        title,
+                        ids.join(", ")
+                    ));
+                }
+                let content = content
+                    .ok_or_else(|| anyhow::anyhow!("--if-exists replace needs --content"))?;
+                let response = client.put_content(&first.id, content).await?;
+
+                if format.is_structured() {
+                    output::print_serialized(&response, format)?;
+                } else {
+                    output::print_success(&format!(
+                        "Replaced content: {} (ID: {})",
+                        response.title,
+                        response.id.cyan()
+                    ));
+                    println!("  Version: {}", response.version);
+                    println!("  Pages:   {}", response.page_count);
+                    if description.is_some() || !tags.is_empty() {
+                        output::print_warning(
+                            "Description and tags are unchanged; set them with 'ck items enrich'",
+                        );
+                    }
+                }
+                return Ok(());
+            }
+        }
+    }
+
+    let idempotency_key = api::new_idempotency_key();
+    let response = api::retry_transient(api::RETRY_ATTEMPTS, api::RETRY_DELAY, || {
+        client.create_markdown(title, description, content, tags, Some(&idempotency_key))
+    })
+    .await?;
 
-    if json {
-        println!("{}", serde_json::to_string_pretty(&response)?);
+    if format.is_structured() {
+        output::print_serialized(&response, format)?;
     } else {
         output::print_success(&format!(
             "Created: {} (ID: {})",
@@ -401,6 +2851,11 @@ pub async fn create(
             response.id.cyan()
         ));
         println!("  Pages: {}", response.page_count);
+        if !response.tags.is_empty() {
+            println!("  Tags:  {}", response.tags.join(", "));
+        } else if !tags.is_empty() {
+            output::print_warning("Tags were sent but the server didn't return any; it may not support tags");
+        }
         println!();
         println!("  To add content: ck items put {} --file content.md", response.id);
         println!("  To view:        ck items get {}", response.id);
@@ -410,18 +2865,176 @@ pub async fn create(
 }
 
 /// Get full content of a document (outputs to stdout for piping)
-pub async fn get(id: &str, session: Option<String>, no_session: bool) -> Result<()> {
-    let client = ApiClient::new(session, no_session)?;
-    let response = client.get_content(id).await?;
+/// With `stats`, print size figures instead of the content. With
+/// `plain_text` and structured output, print the document with both the raw
+/// and the plain-text content.
+pub async fn get(
+    id: &str,
+    stats: bool,
+    plain_text: bool,
+    encoding: &EncodingOptions,
+    format: OutputFormat,
+    opts: &ClientOptions,
+) -> Result<()> {
+    let client = ApiClient::new(opts)?;
+    let mut response = client.get_content(id).await?;
+    if plain_text {
+        let mut plain = markdown::to_plain_text(&response.content);
+        if !plain.is_empty() {
+            plain.push('\n');
+        }
+        if format.is_structured() && !stats {
+            response.plain_text = Some(plain);
+        } else {
+            response.content = plain;
+        }
+    }
+
+    if stats {
+        let mut stats =
+            output::ContentStats::from_pages(&response.id, &response.title, [response.content.as_str()]);
+        // The body is a single string; report the server's page count
+        stats.pages = response.page_count.max(0) as usize;
+
+        if format.is_structured() {
+            output::print_serialized(&[stats], format)?;
+        } else {
+            output::print_content_stats(&[stats]);
+        }
+        return Ok(());
+    }
+
+    if response.plain_text.is_some() {
+        return output::print_serialized(&response, format);
+    }
 
     // Output raw content to stdout (for piping to files)
-    print!("{}", response.content);
+    print!("{}", encoding.encoding().apply(&response.content));
+
+    Ok(())
+}
+
+/// Compare a stored document with a local file.
+/// Returns true when they're identical, so callers can set the exit code.
+pub async fn diff(id: &str, file: &str, format: OutputFormat, opts: &ClientOptions) -> Result<bool> {
+    let local = std::fs::read_to_string(file).with_context(|| format!("Failed to read {}", file))?;
+
+    let client = ApiClient::new(opts)?;
+    let response = client.get_content(id).await?;
+
+    let result = diff::diff_documents(&response.content, &local);
+    if format.is_structured() {
+        output::print_serialized(&result, format)?;
+    } else {
+        output::print_diff(&result, id, file);
+    }
+
+    Ok(result.is_identical())
+}
+
+/// Parse `--order 3,1,2` and check it's a permutation of `existing` page numbers
+fn parse_page_order(order: &str, existing: &[i32]) -> Result<Vec<i32>> {
+    let order: Vec<i32> = order
+        .split(',')
+        .map(|p| p.trim())
+        .filter(|p| !p.is_empty())
+        .map(|p| {
+            p.parse()
+                .map_err(|_| anyhow::anyhow!("Invalid page number in --order: '{}'", p))
+        })
+        .collect::<Result<_>>()?;
+
+    let mut sorted_order = order.clone();
+    sorted_order.sort_unstable();
+    let mut sorted_existing = existing.to_vec();
+    sorted_existing.sort_unstable();
+
+    if sorted_order != sorted_existing {
+        let mut seen = std::collections::HashSet::new();
+        let duplicates: Vec<String> = order
+            .iter()
+            .filter(|p| !seen.insert(**p))
+            .map(|p| p.to_string())
+            .collect();
+        let unknown: Vec<String> = order
+            .iter()
+            .filter(|p| !existing.contains(p))
+            .map(|p| p.to_string())
+            .collect();
+        let missing: Vec<String> = existing
+            .iter()
+            .filter(|p| !order.contains(p))
+            .map(|p| p.to_string())
+            .collect();
+
+        let mut problems = Vec::new();
+        if !duplicates.is_empty() {
+            problems.push(format!("repeated: {}", duplicates.join(", ")));
+        }
+        if !unknown.is_empty() {
+            problems.push(format!("no such page: {}", unknown.join(", ")));
+        }
+        if !missing.is_empty() {
+            problems.push(format!("missing: {}", missing.join(", ")));
+        }
+        return Err(anyhow::anyhow!(
+            "--order must list each of the {} pages exactly once ({})",
+            existing.len(),
+            problems.join("; ")
+        ));
+    }
+
+    Ok(order)
+}
+
+/// Reorder a document's pages, e.g. `--order 3,1,2` moves page 3 first
+pub async fn reorder(id: &str, order: &str, format: OutputFormat, opts: &ClientOptions) -> Result<()> {
+    let client = ApiClient::new(opts)?;
+
+    let response = client
+        .batch_read(vec![ItemReadRequest {
+            id: id.to_string(),
+            pages: None,
+        }])
+        .await?;
+    let item = response
+        .items
+        .into_iter()
+        .find(|item| item.id == id)
+        .with_context(|| format!("Item not found: {}", id))?;
+
+    let existing: Vec<i32> = item.pages.iter().map(|p| p.page_num).collect();
+    let order = parse_page_order(order, &existing)?;
+
+    let response = client
+        .reorder_pages(id, &order)
+        .await
+        .map_err(|e| match api::error_status(&e) {
+            Some(
+                StatusCode::NOT_FOUND
+                | StatusCode::METHOD_NOT_ALLOWED
+                | StatusCode::NOT_IMPLEMENTED,
+            ) => e.context("This server doesn't support page reordering"),
+            _ => e,
+        })?;
+
+    if format.is_structured() {
+        output::print_serialized(&response, format)?;
+    } else {
+        output::print_success(&format!(
+            "Reordered: {} (ID: {})",
+            response.title,
+            response.id.cyan()
+        ));
+        println!("  Version: {}", response.version);
+        println!("  Pages: {}", response.page_count);
+    }
 
     Ok(())
 }
 
 /// Replace document content from file or stdin
-pub async fn put(id: &str, file_path: Option<&str>, session: Option<String>, no_session: bool) -> Result<()> {
+pub async fn put(id: &str, file_path: Option<&str>, opts: &ClientOptions) -> Result<()> {
     let content = if let Some(path) = file_path {
         // Read from file
         let path = Path::new(path);
@@ -430,38 +3043,774 @@ pub async fn put(id: &str, file_path: Option<&str>, session: Option<String>, no_
         }
         std::fs::read_to_string(path).context("Failed to read file")?
     } else {
-        // Read from stdin
-        let mut buffer = String::new();
-        io::stdin()
-            .read_line(&mut buffer)
-            .context("Failed to read from stdin")?;
-
-        // Keep reading until EOF
-        loop {
-            let mut line = String::new();
-            match io::stdin().read_line(&mut line) {
-                Ok(0) => break, // EOF
-                Ok(_) => buffer.push_str(&line),
-                Err(e) => return Err(anyhow::anyhow!("Failed to read from stdin: {}", e)),
-            }
-        }
-        buffer
+        prompt::read_stdin(
+            "No --file given and stdin is a terminal; pipe the content in or pass --file",
+        )
+        .context("Failed to read from stdin")?
     };
 
     if content.trim().is_empty() {
         return Err(anyhow::anyhow!("No content provided"));
     }
 
-    let client = ApiClient::new(session, no_session)?;
-    let response = client.put_content(id, &content).await?;
+    let client = ApiClient::new(opts)?;
+    // A full replace, so sending it again after a dropped connection is safe
+    let mut retries = 0;
+    let response = api::retry_transient_with(
+        api::RETRY_ATTEMPTS,
+        api::RETRY_DELAY,
+        |attempt, e| {
+            retries += 1;
+            output::eprint_warning(&format!(
+                "Write failed ({:#}); retrying ({}/{})",
+                e,
+                attempt,
+                api::RETRY_ATTEMPTS
+            ));
+        },
+        || client.put_content(id, &content),
+    )
+    .await?;
 
     output::print_success(&format!(
         "Updated: {} (ID: {})",
         response.title,
         response.id.cyan()
     ));
+    if retries > 0 {
+        let noun = if retries == 1 { "retry" } else { "retries" };
+        output::print_info(&format!("Written after {} {}", retries, noun));
+    }
     println!("  Version: {}", response.version);
     println!("  Pages: {}", response.page_count);
 
     Ok(())
 }
+
+/// One line of an `items batch` file, e.g.
+/// `{"cmd":"enrich","id":"itm_abc","title":"Deep Learning"}`
+#[derive(Debug, PartialEq, serde::Deserialize)]
+#[serde(tag = "cmd", rename_all = "lowercase", deny_unknown_fields)]
+enum BatchSpec {
+    Enrich {
+        id: String,
+        title: Option<String>,
+        author: Option<String>,
+        description: Option<String>,
+        confidence: Option<f64>,
+        tags: Option<Vec<String>>,
+    },
+    Create {
+        title: String,
+        description: Option<String>,
+        content: Option<String>,
+        #[serde(default)]
+        tags: Vec<String>,
+    },
+    Remove {
+        id: Option<String>,
+        #[serde(default)]
+        ids: Vec<String>,
+    },
+}
+
+impl BatchSpec {
+    fn name(&self) -> &'static str {
+        match self {
+            BatchSpec::Enrich { .. } => "enrich",
+            BatchSpec::Create { .. } => "create",
+            BatchSpec::Remove { .. } => "remove",
+        }
+    }
+
+    /// Check what the server would otherwise reject halfway through a batch
+    fn validate(&self) -> Result<()> {
+        match self {
+            BatchSpec::Enrich {
+                id,
+                title,
+                author,
+                description,
+                confidence,
+                tags,
+            } => {
+                if id.trim().is_empty() {
+                    return Err(anyhow::anyhow!("\"id\" is empty"));
+                }
+                if title.is_none()
+                    && author.is_none()
+                    && description.is_none()
+                    && confidence.is_none()
+                    && tags.is_none()
+                {
+                    return Err(anyhow::anyhow!(
+                        "enrich needs at least one of title, author, description, confidence, or tags"
+                    ));
+                }
+                if confidence.is_some_and(|c| !(0.0..=1.0).contains(&c)) {
+                    return Err(anyhow::anyhow!("Confidence must be between 0.0 and 1.0"));
+                }
+            }
+            BatchSpec::Create { title, .. } => {
+                if title.trim().is_empty() {
+                    return Err(anyhow::anyhow!("\"title\" is empty"));
+                }
+            }
+            BatchSpec::Remove { id, ids } => {
+                if id.is_some() && !ids.is_empty() {
+                    return Err(anyhow::anyhow!("Pass either \"id\" or \"ids\", not both"));
+                }
+                if self.remove_ids().is_empty() {
+                    return Err(anyhow::anyhow!("remove needs \"id\" or a non-empty \"ids\""));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn remove_ids(&self) -> Vec<String> {
+        match self {
+            BatchSpec::Remove { id, ids } => id
+                .iter()
+                .chain(ids)
+                .map(|id| id.trim().to_string())
+                .filter(|id| !id.is_empty())
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Parse and validate every line of a batch file. Blank lines and lines
+/// starting with `#` are skipped; each spec keeps its 1-based line number.
+/// All problems are reported together so nothing runs from a bad file.
+fn parse_batch(text: &str) -> Result<Vec<(usize, BatchSpec)>> {
+    let mut specs = Vec::new();
+    let mut problems = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let line_no = index + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let spec = serde_json::from_str::<BatchSpec>(line)
+            .map_err(|e| {
+                // Every spec is one line, so serde's "at line 1" would mislead
+                let message = e.to_string();
+                let message = message.rsplit_once(" at line ").map_or(&*message, |(m, _)| m);
+                anyhow::anyhow!("{} (column {})", message, e.column())
+            })
+            .and_then(|spec| spec.validate().map(|()| spec));
+        match spec {
+            Ok(spec) => specs.push((line_no, spec)),
+            Err(e) => problems.push(format!("  line {}: {}", line_no, e)),
+        }
+    }
+
+    if !problems.is_empty() {
+        return Err(anyhow::anyhow!(
+            "Invalid batch file; nothing was run:\n{}",
+            problems.join("\n")
+        ));
+    }
+    if specs.is_empty() {
+        return Err(anyhow::anyhow!("The batch file has no commands"));
+    }
+    Ok(specs)
+}
+
+/// Outcome of one batch command
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BatchResult {
+    line: usize,
+    cmd: &'static str,
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    detail: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Run one spec, returning a one-line description of what it did
+async fn run_batch_spec(client: &ApiClient, spec: &BatchSpec) -> Result<String> {
+    match spec {
+        BatchSpec::Enrich {
+            id,
+            title,
+            author,
+            description,
+            confidence,
+            tags,
+        } => {
+            let update = MetadataUpdate {
+                title: title.clone(),
+                author: author.clone().map(Some),
+                description: description.clone().map(Some),
+                confidence: *confidence,
+                toc: None,
+                tags: tags.clone().and_then(parse_tags),
+            };
+            let response = client.enrich_item(id, &update).await?;
+            Ok(format!("Enriched {} ({})", response.item.title, response.item.id))
+        }
+        BatchSpec::Create {
+            title,
+            description,
+            content,
+            tags,
+        } => {
+            let tags = parse_tags(tags.clone()).unwrap_or_default();
+            let idempotency_key = api::new_idempotency_key();
+            let response = api::retry_transient(api::RETRY_ATTEMPTS, api::RETRY_DELAY, || {
+                client.create_markdown(
+                    title,
+                    description.as_deref(),
+                    content.as_deref(),
+                    &tags,
+                    Some(&idempotency_key),
+                )
+            })
+            .await?;
+            Ok(format!("Created {} ({})", response.title, response.id))
+        }
+        BatchSpec::Remove { .. } => {
+            let response = client.delete_items(spec.remove_ids()).await?;
+            if !response.not_found.is_empty() {
+                return Err(anyhow::anyhow!(
+                    "Not found: {}{}",
+                    response.not_found.join(", "),
+                    if response.deleted.is_empty() {
+                        String::new()
+                    } else {
+                        format!(" (deleted {})", response.deleted.join(", "))
+                    }
+                ));
+            }
+            Ok(format!("Deleted {}", response.deleted.join(", ")))
+        }
+    }
+}
+
+/// Run the command specs in `file` (or stdin for `-`) in order with one client.
+///
+/// The whole file is validated before anything runs. By default the batch
+/// stops at the first failure and the command fails; with
+/// `continue_on_error` every command runs and failures only show in the
+/// summary. Returns false if any command failed without `continue_on_error`.
+pub async fn batch(
+    file: &str,
+    continue_on_error: bool,
+    skip_confirm: bool,
+    format: OutputFormat,
+    opts: &ClientOptions,
+) -> Result<bool> {
+    #[derive(Serialize)]
+    struct Summary<'a> {
+        results: &'a [BatchResult],
+        succeeded: usize,
+        failed: usize,
+        skipped: usize,
+    }
+
+    let text = if file == "-" {
+        prompt::read_stdin("stdin is a terminal; pipe the batch file in or pass its path")
+            .context("Failed to read from stdin")?
+    } else {
+        std::fs::read_to_string(file).with_context(|| format!("Failed to read {}", file))?
+    };
+    let specs = parse_batch(&text)?;
+
+    let removals: usize = specs.iter().map(|(_, spec)| spec.remove_ids().len()).sum();
+    if removals > 0 && !skip_confirm {
+        let question = format!("This batch deletes {} item(s). Are you sure?", removals);
+        if !prompt::confirm(&question, "delete")? {
+            println!("{}", "Cancelled.".dimmed());
+            return Ok(true);
+        }
+    }
+
+    let client = ApiClient::new(opts)?;
+    let total = specs.len();
+    let mut results = Vec::with_capacity(total);
+    for (index, (line, spec)) in specs.iter().enumerate() {
+        let result = match run_batch_spec(&client, spec).await {
+            Ok(detail) => BatchResult {
+                line: *line,
+                cmd: spec.name(),
+                ok: true,
+                detail: Some(detail),
+                error: None,
+            },
+            Err(e) => BatchResult {
+                line: *line,
+                cmd: spec.name(),
+                ok: false,
+                detail: None,
+                error: Some(format!("{:#}", e)),
+            },
+        };
+
+        if !format.is_structured() {
+            let counter = format!("[{}/{}]", index + 1, total).dimmed();
+            match (&result.detail, &result.error) {
+                (Some(detail), _) => println!("{} {} {}", counter, "✓".green(), detail),
+                (_, Some(error)) => println!(
+                    "{} {} line {} ({}): {}",
+                    counter,
+                    "✗".red(),
+                    result.line,
+                    result.cmd,
+                    error
+                ),
+                _ => {}
+            }
+        }
+
+        let failed = !result.ok;
+        results.push(result);
+        if failed && !continue_on_error {
+            break;
+        }
+    }
+
+    let failed = results.iter().filter(|r| !r.ok).count();
+    let succeeded = results.len() - failed;
+    let skipped = total - results.len();
+    if format.is_structured() {
+        output::print_serialized(
+            &Summary { results: &results, succeeded, failed, skipped },
+            format,
+        )?;
+    } else {
+        println!();
+        let mut summary = format!("{} succeeded, {} failed", succeeded, failed);
+        if skipped > 0 {
+            summary.push_str(&format!(", {} not run", skipped));
+        }
+        if failed == 0 {
+            output::print_success(&summary);
+        } else {
+            output::print_warning(&summary);
+        }
+    }
+
+    Ok(failed == 0 || continue_on_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_page_order() {
+        assert_eq!(parse_page_order("3, 1,2", &[1, 2, 3]).unwrap(), vec![3, 1, 2]);
+
+        let err = parse_page_order("1,1,4", &[1, 2, 3]).unwrap_err().to_string();
+        assert!(err.contains("repeated: 1"));
+        assert!(err.contains("no such page: 4"));
+        assert!(err.contains("missing: 2, 3"));
+
+        assert!(parse_page_order("1,x", &[1, 2]).is_err());
+    }
+
+    #[test]
+    fn test_parse_content_type() {
+        let types = UploadTypes::new([]);
+        assert_eq!(parse_content_type("application/pdf", &types).unwrap(), "application/pdf");
+        assert_eq!(
+            parse_content_type(" Text/Markdown; charset=utf-8", &types).unwrap(),
+            "text/markdown"
+        );
+
+        let err = parse_content_type("image/png", &types).unwrap_err().to_string();
+        assert!(err.contains("application/epub+zip"));
+    }
+
+    #[test]
+    fn test_upload_types_merge_config() {
+        let types = UploadTypes::new([
+            (".DOCX".to_string(), "Application/Vnd.Docx".to_string()),
+            ("md".to_string(), "text/x-md".to_string()),
+            ("txt".to_string(), "text/plain; charset=utf-8".to_string()),
+        ]);
+        assert_eq!(types.for_extension("docx"), Some("application/vnd.docx"));
+        assert_eq!(types.for_extension("PDF"), Some("application/pdf"));
+        assert_eq!(types.for_extension("md"), Some("text/x-md"));
+        assert_eq!(types.for_extension("markdown"), Some("text/markdown"));
+        assert_eq!(types.for_extension("png"), None);
+        assert_eq!(types.extension_for("text/plain"), "txt");
+        assert_eq!(types.extension_for("text/markdown"), "markdown");
+        assert_eq!(types.extension_for(ANY_CONTENT_TYPE), "bin");
+        assert_eq!(types.describe_extensions(), ".pdf, .md, .markdown, .epub, .docx, .txt");
+        assert!(parse_content_type("application/vnd.docx", &types).is_ok());
+    }
+
+    #[test]
+    fn test_parse_status() {
+        assert_eq!(parse_status("failed").unwrap(), "FAILED");
+        assert_eq!(parse_status(" Processing").unwrap(), "PROCESSING");
+
+        let err = parse_status("done").unwrap_err();
+        assert!(err.contains("draft, processing, ready, failed"), "{}", err);
+    }
+
+    #[test]
+    fn test_metadata_applied_checks_requested_fields() {
+        let item = api::ConfirmItem {
+            id: "itm_1".into(),
+            title: "Deep Learning".into(),
+            author: None,
+            description: None,
+        };
+        assert!(metadata_applied(&item, &MetadataUpdate::default()));
+
+        let title = MetadataUpdate { title: Some("Deep Learning".into()), ..Default::default() };
+        assert!(metadata_applied(&item, &title));
+
+        // The server kept the filename title, or dropped the author
+        let renamed = MetadataUpdate { title: Some("DL".into()), ..Default::default() };
+        assert!(!metadata_applied(&item, &renamed));
+        let author = MetadataUpdate { author: Some(Some("Goodfellow".into())), ..Default::default() };
+        assert!(!metadata_applied(&item, &author));
+    }
+
+    #[test]
+    fn test_expand_file_args() {
+        let dir = std::env::temp_dir().join(format!("ck-glob-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in ["a.pdf", "b.pdf", "notes.md", "report [v2].txt"] {
+            std::fs::write(dir.join(name), "x").unwrap();
+        }
+        let path = |name: &str| dir.join(name).to_string_lossy().into_owned();
+
+        let args = vec![
+            path("*.pdf"),
+            path("a.pdf"),
+            path("notes.md"),
+            path("*.epub"),
+            path("report [v2].txt"),
+        ];
+        let (paths, failures) = expand_file_args(&args);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            paths,
+            vec![path("a.pdf"), path("b.pdf"), path("notes.md"), path("report [v2].txt")]
+        );
+        assert_eq!(failures, vec![(path("*.epub"), "No files match".to_string())]);
+    }
+
+    #[test]
+    fn test_page_window() {
+        let (range, position) = page_window(25, 10, 10);
+        assert_eq!(range, Some((11, 20)));
+        assert_eq!(position.next_cursor, Some(20));
+        assert!(position.has_more);
+
+        let (range, position) = page_window(25, 10, 20);
+        assert_eq!(range, Some((21, 25)));
+        assert_eq!(position.next_cursor, None);
+        assert!(!position.has_more);
+
+        assert_eq!(page_window(25, 10, 30).0, None);
+        assert_eq!(page_window(0, 10, 0).0, None);
+    }
+
+    #[test]
+    fn test_skip_empty_pages() {
+        let mut item: api::ItemWithPages = serde_json::from_value(serde_json::json!({
+            "id": "itm_1", "title": "Scan", "description": null, "sourceType": "pdf",
+            "metadata": null, "createdAt": "", "updatedAt": "", "pageCount": 4,
+            "status": "READY",
+            "pages": [
+                {"id": "p1", "pageNum": 1, "content": "Chapter one", "metadata": null},
+                {"id": "p2", "pageNum": 2, "content": " \n\t", "metadata": null},
+                {"id": "p3", "pageNum": 3, "content": null, "metadata": null},
+                {"id": "p4", "pageNum": 4, "content": " 12 ", "metadata": null}
+            ]
+        }))
+        .unwrap();
+
+        skip_empty_pages(&mut item, 3);
+        let kept: Vec<i32> = item.pages.iter().map(|p| p.page_num).collect();
+        assert_eq!(kept, vec![1]);
+        assert_eq!(item.skipped_empty_pages, Some(3));
+        let json = serde_json::to_value(&item).unwrap();
+        assert_eq!(json["skippedEmptyPages"], 3);
+    }
+
+    #[test]
+    fn test_verify_report_flags_empty_pages() {
+        let page = |n, content: Option<&str>| Page {
+            id: format!("p{}", n),
+            page_num: n,
+            content: content.map(str::to_string),
+            metadata: None,
+            chapter: None,
+            plain_text: None,
+        };
+        let item = api::ItemWithPages {
+            id: "itm_1".to_string(),
+            title: "Scan".to_string(),
+            description: None,
+            source_type: "pdf".to_string(),
+            metadata: None,
+            created_at: String::new(),
+            updated_at: String::new(),
+            page_count: 4,
+            pages: vec![
+                page(1, Some("text")),
+                page(2, Some("  \n")),
+                page(3, None),
+                page(4, Some("more")),
+            ],
+            status: "READY".to_string(),
+            skipped_empty_pages: None,
+        };
+
+        let report = VerifyReport::new(&item, false, 0.5);
+        assert_eq!(report.empty_pages, vec![2, 3]);
+        assert_eq!(report.content_ratio, 0.5);
+        assert!(report.ok);
+        assert!(!VerifyReport::new(&item, false, 0.8).ok);
+
+        let statuses: Vec<_> = report.checks(4, 0.5).iter().map(|c| (c.name, c.status)).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                ("Status", CheckStatus::Pass),
+                ("Content", CheckStatus::Pass),
+                ("TOC", CheckStatus::Warn)
+            ]
+        );
+        let checks = VerifyReport::new(&item, false, 0.8).checks(4, 0.8);
+        assert_eq!(checks[1].status, CheckStatus::Fail);
+        assert_eq!(checks[1].hint.as_deref(), Some("Empty pages: 2, 3"));
+    }
+
+    #[test]
+    fn test_tail_range() {
+        assert_eq!(tail_range(20, 5), Some((16, 20)));
+        assert_eq!(tail_range(3, 5), Some((1, 3)));
+        assert_eq!(tail_range(1, 1), Some((1, 1)));
+        assert_eq!(tail_range(0, 5), None);
+    }
+
+    #[test]
+    fn test_chapter_range() {
+        let entry = |title: &str, page, level| TocEntry {
+            title: title.to_string(),
+            page,
+            level: Some(level),
+        };
+        let toc = vec![
+            entry("Chapter 1: Basics", 1, 0),
+            entry("1.1 Setup", 2, 1),
+            entry("Chapter 2: Advanced", 10, 0),
+            entry("2.1 Tuning", 12, 1),
+            entry("Chapter 3", 20, 0),
+        ];
+
+        assert_eq!(chapter_range(&toc, 30, "chapter 1").unwrap(), (1, 9));
+        assert_eq!(chapter_range(&toc, 30, "setup").unwrap(), (2, 9));
+        assert_eq!(chapter_range(&toc, 30, "TUNING").unwrap(), (12, 19));
+        assert_eq!(chapter_range(&toc, 30, "Chapter 3").unwrap(), (20, 30));
+
+        let err = chapter_range(&toc, 30, "chapter").unwrap_err().to_string();
+        assert!(err.contains("matches 3 chapters"));
+        assert!(err.contains("Chapter 2: Advanced (page 10)"));
+        assert!(chapter_range(&toc, 30, "appendix").is_err());
+    }
+
+    #[test]
+    fn test_preview_range() {
+        assert_eq!(preview_range(10, 30, 0, 3), (10, 12));
+        assert_eq!(preview_range(10, 30, -2, 3), (8, 10));
+        // Clamped to the item's pages
+        assert_eq!(preview_range(2, 30, -5, 3), (1, 3));
+        assert_eq!(preview_range(28, 30, 0, 5), (28, 30));
+        assert_eq!(preview_range(28, 30, 10, 5), (30, 30));
+    }
+
+    #[test]
+    fn test_missing_range_error_uses_shared_format_help() {
+        let err = parse_ids_with_ranges("itm_abc").unwrap_err().to_string();
+        assert!(err.contains("itm_abc:all"));
+        assert!(err.contains(PAGE_RANGE_FORMATS));
+        assert!(read_after_help().starts_with(PAGE_RANGE_FORMATS));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_in_chunks_keeps_order_and_isolates_failures() {
+        let ids: Vec<String> = (0..12).map(|i| format!("id{}", i)).collect();
+
+        let batch = fetch_in_chunks(ids, 3, |id| id.as_str(), |chunk| async move {
+            if chunk.contains(&"id5".to_string()) {
+                return Err(anyhow::anyhow!("boom"));
+            }
+            let missing = chunk.iter().filter(|id| id.ends_with('1')).cloned().collect();
+            Ok((chunk, Some(missing)))
+        })
+        .await
+        .unwrap();
+
+        let expected: Vec<String> = (0..5).chain(10..12).map(|i| format!("id{}", i)).collect();
+        assert_eq!(batch.items, expected);
+        assert_eq!(batch.not_found, Some(vec!["id1".to_string(), "id11".to_string()]));
+        assert_eq!(batch.failures.len(), 1);
+        assert_eq!(batch.failures[0].0, vec!["id5", "id6", "id7", "id8", "id9"]);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_in_chunks_returns_error_when_all_fail() {
+        let result: Result<ChunkedBatch<String>> =
+            fetch_in_chunks(vec!["a".to_string()], 4, |id| id.as_str(), |_| async {
+                Err(anyhow::anyhow!("Not found: nope"))
+            })
+            .await;
+
+        assert_eq!(result.err().unwrap().to_string(), "Not found: nope");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_in_chunks_with_sees_each_chunk() {
+        let ids: Vec<String> = (0..7).map(|i| format!("id{}", i)).collect();
+        let mut seen = Vec::new();
+
+        let batch = fetch_in_chunks_with(
+            ids,
+            2,
+            |id| id.as_str(),
+            |chunk| async move { Ok((chunk, None)) },
+            |items| {
+                seen.push(items.len());
+                items.retain(|id| id != "id6");
+                Ok(())
+            },
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(seen, vec![5, 2]);
+        assert_eq!(batch.items.len(), 6);
+    }
+
+    #[test]
+    fn test_parse_batch_validates_every_line() {
+        let specs = parse_batch(
+            "# comment\n\
+             {\"cmd\":\"enrich\",\"id\":\"itm_1\",\"title\":\"T\"}\n\
+             \n\
+             {\"cmd\":\"remove\",\"ids\":[\"itm_2\",\" \"]}\n",
+        )
+        .unwrap();
+        assert_eq!(specs.len(), 2);
+        assert_eq!(specs[0].0, 2);
+        assert_eq!(specs[0].1.name(), "enrich");
+        assert_eq!(specs[1].0, 4);
+        assert_eq!(specs[1].1.remove_ids(), vec!["itm_2"]);
+
+        let err = parse_batch(
+            "{\"cmd\":\"enrich\",\"id\":\"itm_1\"}\n\
+             {\"cmd\":\"create\",\"title\":\"T\"}\n\
+             {\"cmd\":\"create\",\"title\":\"T\",\"tilte\":\"x\"}\n\
+             {\"cmd\":\"remove\"}",
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("line 1: enrich needs"));
+        assert!(!err.contains("line 2"));
+        assert!(err.contains("line 3: unknown field `tilte`"));
+        assert!(err.contains("line 4: remove needs"));
+
+        assert!(parse_batch("# nothing\n").is_err());
+    }
+
+    #[test]
+    fn test_check_enrichment_collects_every_error() {
+        let update = MetadataUpdate { confidence: Some(1.5), ..Default::default() };
+        let toc = r#"[{"title":"Intro","page":1},{"title":" ","page":0,"level":0}]"#;
+        let (parsed, errors) = check_enrichment(&update, Some(toc), &[ClearField::Toc]);
+        assert_eq!(parsed.map(|toc| toc.len()), Some(2));
+        assert_eq!(
+            errors,
+            vec![
+                "Can't both set and clear --toc; pick one",
+                "Confidence must be between 0.0 and 1.0",
+                "TOC entry 2: title cannot be empty",
+                "TOC entry 2: page must be >= 1",
+                "TOC entry 2: level must be >= 1",
+            ]
+        );
+
+        let (parsed, errors) = check_enrichment(&MetadataUpdate::default(), Some("[{"), &[]);
+        assert!(parsed.is_none());
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].starts_with("Invalid TOC JSON"), "{}", errors[0]);
+
+        let (_, errors) = check_enrichment(&MetadataUpdate::default(), None, &[]);
+        assert!(errors[0].starts_with("At least one of --title"));
+
+        let update = MetadataUpdate { title: Some("Doc".to_string()), ..Default::default() };
+        assert!(check_enrichment(&update, None, &[]).1.is_empty());
+    }
+
+    #[test]
+    fn test_merge_toc_entries_dedups_and_sorts() {
+        let entry = |title: &str, page| TocEntry {
+            title: title.to_string(),
+            page,
+            level: Some(1),
+        };
+        let (merged, added, warnings) = merge_toc_entries(
+            vec![entry("Intro", 1), entry("Methods", 10)],
+            vec![
+                entry("Results", 20),
+                entry("Intro", 1),
+                entry("Background", 5),
+                entry("Data", 10),
+            ],
+        );
+
+        let titles: Vec<&str> = merged.iter().map(|e| e.title.as_str()).collect();
+        assert_eq!(titles, vec!["Intro", "Background", "Methods", "Data", "Results"]);
+        assert_eq!(added, 3);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("Page 10 already has \"Methods\""));
+    }
+
+    #[test]
+    fn test_chapter_for_page_picks_nearest_preceding_entry() {
+        let entry = |title: &str, page| TocEntry {
+            title: title.to_string(),
+            page,
+            level: Some(1),
+        };
+        let toc = vec![
+            entry("Intro", 1),
+            entry("Chapter 2", 3),
+            entry("Section 2.1", 3),
+            entry("Appendix", 9),
+        ];
+
+        assert_eq!(chapter_for_page(&toc, 2), Some("Intro"));
+        assert_eq!(chapter_for_page(&toc, 3), Some("Section 2.1"));
+        assert_eq!(chapter_for_page(&toc, 8), Some("Section 2.1"));
+        assert_eq!(chapter_for_page(&toc, 12), Some("Appendix"));
+        assert_eq!(chapter_for_page(&[entry("Later", 5)], 2), None);
+    }
+
+    #[test]
+    fn test_item_web_url() {
+        assert_eq!(
+            item_web_url("https://www.getcandlekeep.com", "itm_abcdefgh").unwrap(),
+            "https://www.getcandlekeep.com/library/itm_abcdefgh"
+        );
+        assert_eq!(
+            item_web_url("http://localhost:3000/ck/", "itm_abcdefgh").unwrap(),
+            "http://localhost:3000/ck/library/itm_abcdefgh"
+        );
+        assert!(item_web_url("https://host", "../admin").is_err());
+    }
+}
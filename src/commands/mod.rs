@@ -1,4 +1,149 @@
+use anyhow::Result;
+use chrono::{DateTime, NaiveDate, TimeDelta, Utc};
+use std::ops::RangeInclusive;
+
+use crate::output;
+
 pub mod access;
 pub mod auth;
+pub mod config;
+pub mod daemon;
+pub mod doctor;
+pub mod file_names;
 pub mod items;
+pub mod profiles;
 pub mod sources;
+pub mod version;
+
+/// `--strict` check run after results are printed: fail if any IDs weren't
+/// found or any storage cleanup failed
+pub fn check_strict(strict: bool, not_found: &[String], storage_errors: &[String]) -> Result<()> {
+    if !strict {
+        return Ok(());
+    }
+
+    let mut problems = Vec::new();
+    if !not_found.is_empty() {
+        problems.push(format!("{} ID(s) not found", not_found.len()));
+    }
+    if !storage_errors.is_empty() {
+        problems.push(format!("storage cleanup failed for {} item(s)", storage_errors.len()));
+    }
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!("--strict: {}", problems.join(", ")))
+    }
+}
+
+/// Lengths of the IDs the API issues
+const ID_LENGTH: RangeInclusive<usize> = 8..=64;
+
+/// Whether `id` is shaped like an API ID: 8-64 ASCII letters, digits, `_`
+/// or `-`. Says nothing about whether the ID exists.
+pub fn is_valid_id(id: &str) -> bool {
+    ID_LENGTH.contains(&id.len())
+        && id.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'-')
+}
+
+/// The not-found IDs that can't be real IDs, so retrying them is pointless
+pub fn invalid_ids(not_found: &[String]) -> Vec<String> {
+    not_found.iter().filter(|id| !is_valid_id(id)).cloned().collect()
+}
+
+/// Warnings for IDs the server didn't find, telling well-formed IDs (which
+/// may have been deleted) apart from malformed ones (likely typos)
+pub fn not_found_warnings(not_found: &[String]) -> Vec<String> {
+    let (valid, invalid): (Vec<&String>, Vec<&String>) =
+        not_found.iter().partition(|id| is_valid_id(id));
+
+    let join = |ids: &[&String]| ids.iter().map(|id| id.as_str()).collect::<Vec<_>>().join(", ");
+    let mut warnings = Vec::new();
+    if !valid.is_empty() {
+        warnings.push(format!("Not found (may have been deleted): {}", join(&valid)));
+    }
+    if !invalid.is_empty() {
+        warnings.push(format!("Invalid ID format: {}", join(&invalid)));
+    }
+    warnings
+}
+
+/// Print `not_found_warnings` for `not_found`
+pub fn warn_not_found(not_found: &[String]) {
+    for warning in not_found_warnings(not_found) {
+        output::print_warning(&warning);
+    }
+}
+
+/// Parse an RFC 3339 timestamp, or None if it's malformed
+pub fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+/// Parse a `--since`/`--until` bound: a `YYYY-MM-DD` date (midnight UTC), an
+/// RFC 3339 timestamp, or an age relative to `now` such as `12h`, `7d` or `2w`
+pub fn parse_date_bound(value: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>> {
+    let value = value.trim();
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Ok(date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc());
+    }
+    if let Some(timestamp) = parse_timestamp(value) {
+        return Ok(timestamp);
+    }
+
+    let invalid = || {
+        anyhow::anyhow!(
+            "Invalid date '{}': use YYYY-MM-DD, an RFC 3339 timestamp, or an age like 12h, 7d, 2w",
+            value
+        )
+    };
+    let split = value.char_indices().last().map_or(0, |(i, _)| i);
+    let (amount, unit) = value.split_at(split);
+    let amount: i64 = amount.parse().map_err(|_| invalid())?;
+    let age = match unit {
+        "h" => TimeDelta::try_hours(amount),
+        "d" => TimeDelta::try_days(amount),
+        "w" => TimeDelta::try_weeks(amount),
+        _ => None,
+    }
+    .ok_or_else(invalid)?;
+    now.checked_sub_signed(age).ok_or_else(invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_date_bound() {
+        let now = parse_timestamp("2026-03-10T12:00:00Z").unwrap();
+        let at = |s| parse_timestamp(s).unwrap();
+        assert_eq!(parse_date_bound("2026-01-01", now).unwrap(), at("2026-01-01T00:00:00Z"));
+        assert_eq!(
+            parse_date_bound("2026-02-01T08:30:00+01:00", now).unwrap(),
+            at("2026-02-01T07:30:00Z")
+        );
+        assert_eq!(parse_date_bound("7d", now).unwrap(), at("2026-03-03T12:00:00Z"));
+        assert_eq!(parse_date_bound("12h", now).unwrap(), at("2026-03-10T00:00:00Z"));
+        assert!(parse_date_bound("2026-13-01", now).is_err());
+        assert!(parse_date_bound("7é", now).is_err());
+        assert!(parse_date_bound("yesterday", now).is_err());
+        assert!(parse_date_bound("7y", now).is_err());
+    }
+
+    #[test]
+    fn test_is_valid_id() {
+        assert!(is_valid_id("itm_abc12345"));
+        assert!(is_valid_id("cm3x9k2ld0000qz2h5n7b8c1d"));
+        assert!(!is_valid_id("itm_ab"));
+        assert!(!is_valid_id("itm abc12345"));
+        assert!(!is_valid_id("itm_abc12345;"));
+        assert_eq!(
+            invalid_ids(&["itm_abc12345".to_string(), "oops!".to_string()]),
+            vec!["oops!".to_string()]
+        );
+    }
+}
@@ -0,0 +1,129 @@
+use anyhow::Result;
+use colored::Colorize;
+
+use crate::commands::auth;
+use crate::config::{self, Config, ProfileSummary, DEFAULT_PROFILE};
+use crate::output::{self, OutputFormat};
+use crate::prompt;
+
+/// Fail unless `name` is a defined profile (not the top-level one)
+fn ensure_named_profile(config: &Config, name: &str) -> Result<()> {
+    if name == DEFAULT_PROFILE {
+        return Err(anyhow::anyhow!(
+            "'{}' is the top-level [auth] and [api] settings, not a named profile",
+            DEFAULT_PROFILE
+        ));
+    }
+    if !config.profiles.contains_key(name) {
+        return Err(anyhow::anyhow!("No profile named '{}'; see 'ck profiles list'", name));
+    }
+    Ok(())
+}
+
+/// `key` is the config key holding `api_key`, for expanding `${VAR}` references
+fn summary(
+    name: &str,
+    active: bool,
+    url: &str,
+    key: &str,
+    api_key: Option<&str>,
+) -> ProfileSummary {
+    // Checked as used; a reference to an unset variable isn't valid
+    let expanded = api_key.and_then(|api_key| config::expand_env(key, api_key).ok());
+    ProfileSummary {
+        name: name.to_string(),
+        active,
+        url: url.to_string(),
+        api_key: api_key.map(config::redact_api_key),
+        key_valid: expanded.is_some_and(|key| auth::validate_api_key_format(&key).is_ok()),
+    }
+}
+
+/// List the top-level settings and every named profile, marking the one in use
+pub fn list(format: OutputFormat) -> Result<()> {
+    let config = config::load_config()?;
+    let selected = config.selected_profile_name();
+
+    let mut profiles = vec![summary(
+        DEFAULT_PROFILE,
+        selected.is_none(),
+        &config.api.url,
+        "auth.api_key",
+        config.auth.api_key.as_deref(),
+    )];
+    for (name, profile) in &config.profiles {
+        profiles.push(summary(
+            name,
+            selected == Some(name.as_str()),
+            profile.api_url.as_deref().unwrap_or(&config.api.url),
+            &format!("profiles.{}.api_key", name),
+            profile.api_key.as_deref(),
+        ));
+    }
+
+    if format.is_structured() {
+        output::print_serialized(&profiles, format)?;
+    } else {
+        output::print_profiles(&profiles);
+    }
+    Ok(())
+}
+
+/// Make `name` the active profile ("default" for the top-level settings)
+pub fn use_profile(name: &str) -> Result<()> {
+    let mut config = config::load_config()?;
+    if name != DEFAULT_PROFILE {
+        ensure_named_profile(&config, name)?;
+    }
+
+    config.active_profile = (name != DEFAULT_PROFILE).then(|| name.to_string());
+    config::save_config(&config)?;
+
+    output::print_success(&format!("Now using profile '{}'", name));
+    Ok(())
+}
+
+/// Delete a named profile; the top-level settings become active if it was
+pub fn remove(name: &str, skip_confirm: bool) -> Result<()> {
+    let mut config = config::load_config()?;
+    ensure_named_profile(&config, name)?;
+
+    if !skip_confirm && !prompt::confirm(&format!("Remove profile '{}'?", name), "remove")? {
+        println!("{}", "Cancelled.".dimmed());
+        return Ok(());
+    }
+
+    config.profiles.remove(name);
+    let was_active = config.active_profile.as_deref() == Some(name);
+    if was_active {
+        config.active_profile = None;
+    }
+    config::save_config(&config)?;
+
+    output::print_success(&format!("Removed profile '{}'", name));
+    if was_active {
+        output::print_info(&format!("Now using profile '{}'", DEFAULT_PROFILE));
+    }
+    Ok(())
+}
+
+/// Rename a named profile, keeping it active if it was
+pub fn rename(old: &str, new: &str) -> Result<()> {
+    let mut config = config::load_config()?;
+    ensure_named_profile(&config, old)?;
+    config::validate_profile_name(new)?;
+    if new == DEFAULT_PROFILE || config.profiles.contains_key(new) {
+        return Err(anyhow::anyhow!("A profile named '{}' already exists", new));
+    }
+
+    if let Some(profile) = config.profiles.remove(old) {
+        config.profiles.insert(new.to_string(), profile);
+    }
+    if config.active_profile.as_deref() == Some(old) {
+        config.active_profile = Some(new.to_string());
+    }
+    config::save_config(&config)?;
+
+    output::print_success(&format!("Renamed profile '{}' to '{}'", old, new));
+    Ok(())
+}
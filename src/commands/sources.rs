@@ -1,9 +1,13 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
+use clap::ValueEnum;
 use colored::Colorize;
-use std::io::{self, Write};
+use serde::Serialize;
+use std::fs::File;
+use std::io::{self, BufWriter, IsTerminal, Write};
 
-use crate::api::ApiClient;
-use crate::output;
+use crate::api::{ApiClient, ClientOptions, NewSource, Source};
+use crate::output::{self, OutputFormat};
+use crate::prompt;
 
 /// Parse comma-separated IDs
 fn parse_ids(ids_str: &str) -> Vec<String> {
@@ -14,23 +18,228 @@ fn parse_ids(ids_str: &str) -> Vec<String> {
         .collect()
 }
 
+/// Whether a source's author handle or name matches `author`
+/// (case-insensitive, ignoring a leading '@')
+fn matches_author(source: &Source, author: &str) -> bool {
+    let wanted = author.trim_start_matches('@');
+    [source.author_handle.as_deref(), source.author_name.as_deref()]
+        .into_iter()
+        .flatten()
+        .any(|a| a.trim_start_matches('@').eq_ignore_ascii_case(wanted))
+}
+
+/// Field to sort `sources list` by
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum SourceSort {
+    /// Author handle, falling back to name (as shown in the table, ignoring a
+    /// leading '@')
+    Author,
+    /// Creation time
+    Date,
+    /// Content text
+    Content,
+}
+
+/// Sort sources in place; missing values sort first
+fn sort_sources(sources: &mut [Source], sort: SourceSort) {
+    match sort {
+        SourceSort::Author => sources.sort_by_cached_key(|s| {
+            output::source_author(s).unwrap_or("").trim_start_matches('@').to_lowercase()
+        }),
+        SourceSort::Date => sources.sort_by_cached_key(|s| super::parse_timestamp(&s.created_at)),
+        SourceSort::Content => {
+            sources.sort_by_cached_key(|s| s.content.clone().unwrap_or_default().to_lowercase())
+        }
+    }
+}
+
+/// Flags for `sources list`
+#[derive(Debug, Clone, clap::Args)]
+pub struct ListOptions {
+    /// Maximum number of sources to return
+    #[arg(long)]
+    pub limit: Option<u32>,
+    /// Only show sources by this author (handle or name, case-insensitive)
+    #[arg(long)]
+    pub author: Option<String>,
+    /// Sort by field
+    #[arg(long, value_enum)]
+    pub sort: Option<SourceSort>,
+    /// Reverse the order
+    #[arg(long)]
+    pub reverse: bool,
+    /// Print one source ID per line instead of a table (for shell pipelines)
+    #[arg(long)]
+    pub ids_only: bool,
+    /// With --ids-only, end each ID with a NUL byte instead of a newline (for xargs -0)
+    #[arg(short = '0', long = "null", requires = "ids_only")]
+    pub null_separated: bool,
+    /// Print each source with this template instead of a table, e.g.
+    /// "{id}\t{author}: {content}"; placeholders: id, type, author, handle,
+    /// content, url, published, created, updated
+    #[arg(long, conflicts_with = "ids_only")]
+    pub template: Option<String>,
+}
+
 /// List sources
-pub async fn list(json: bool, limit: Option<u32>, session: Option<String>, no_session: bool) -> Result<()> {
-    let client = ApiClient::new(session, no_session)?;
-    let limit = limit.unwrap_or(50);
-    let response = client.list_sources(limit, None).await?;
+pub async fn list(list: &ListOptions, format: OutputFormat, opts: &ClientOptions) -> Result<()> {
+    let template = list
+        .template
+        .as_deref()
+        .map(|t| output::Template::parse(t, output::SOURCE_TEMPLATE_FIELDS))
+        .transpose()?;
+
+    let client = ApiClient::new(opts)?;
+    let limit = list.limit.unwrap_or(50);
+    let author = list.author.as_deref();
+    let mut response = client.list_sources(limit, None, author).await?;
+
+    // Also filter locally in case the server ignores the author parameter
+    if let Some(author) = author {
+        response.sources.retain(|s| matches_author(s, author));
+    }
+
+    if let Some(sort) = list.sort {
+        sort_sources(&mut response.sources, sort);
+    }
+    if list.reverse {
+        response.sources.reverse();
+    }
+
+    if let Some(template) = template {
+        for source in &response.sources {
+            println!(
+                "{}",
+                template.render(|name| output::source_template_value(source, name))
+            );
+        }
+    } else if list.ids_only {
+        output::print_source_ids(&response.sources, list.null_separated)?;
+    } else if format.is_structured() {
+        output::print_serialized(&response, format)?;
+    } else {
+        output::print_sources_table(&response.sources, response.total, author);
+    }
+
+    Ok(())
+}
+
+/// Page size used when fetching every source
+const EXPORT_PAGE_SIZE: u32 = 100;
+
+/// Export every source as JSON lines (or a JSON array) to a file or stdout
+pub async fn export(
+    output_path: Option<&str>,
+    array: bool,
+    opts: &ClientOptions,
+) -> Result<()> {
+    let client = ApiClient::new(opts)?;
+
+    let mut writer: Box<dyn Write> = match output_path {
+        Some(path) => Box::new(BufWriter::new(
+            File::create(path).with_context(|| format!("Failed to create {}", path))?,
+        )),
+        None => Box::new(BufWriter::new(io::stdout().lock())),
+    };
+
+    let show_progress = io::stderr().is_terminal() && !prompt::non_interactive();
+    let mut all: Vec<Source> = Vec::new();
+    let mut exported = 0usize;
+    let mut cursor: Option<String> = None;
+
+    loop {
+        let page = client
+            .list_sources(EXPORT_PAGE_SIZE, cursor.as_deref(), None)
+            .await?;
+        let fetched = page.sources.len();
+
+        for source in page.sources {
+            if array {
+                all.push(source);
+            } else {
+                serde_json::to_writer(&mut writer, &source)?;
+                writeln!(writer)?;
+            }
+        }
+
+        exported += fetched;
+        if show_progress {
+            eprint!("\rExported {} of {} sources", exported, page.total);
+        }
+
+        cursor = page.next_cursor;
+        if cursor.is_none() || fetched == 0 {
+            break;
+        }
+    }
+
+    if array {
+        output::write_json(&mut writer, &all)?;
+        writeln!(writer)?;
+    }
+    writer.flush()?;
+
+    if show_progress {
+        eprintln!();
+    }
+    if let Some(path) = output_path {
+        output::print_success(&format!("Exported {} source(s) to {}", exported, path));
+    }
+
+    Ok(())
+}
+
+/// `--author` as the API's fields: `@handle` is a handle, anything else a name
+fn author_fields(author: &str) -> (Option<String>, Option<String>) {
+    let author = author.trim();
+    match author.strip_prefix('@') {
+        Some(handle) => (None, Some(handle.to_string())),
+        None => (Some(author.to_string()), None),
+    }
+}
 
-    if json {
-        output::print_sources_json(&response);
+/// Save a source from a URL and/or text content
+pub async fn add(
+    url: Option<&str>,
+    content: Option<&str>,
+    author: Option<&str>,
+    format: OutputFormat,
+    opts: &ClientOptions,
+) -> Result<()> {
+    let url = url.map(str::trim).filter(|u| !u.is_empty());
+    let content = content.filter(|c| !c.trim().is_empty());
+    if url.is_none() && content.is_none() {
+        return Err(anyhow::anyhow!("Provide --url or --content for the new source"));
+    }
+
+    let (author_name, author_handle) = author.map(author_fields).unwrap_or_default();
+    let new_source = NewSource {
+        source_url: url.map(str::to_string),
+        content: content.map(str::to_string),
+        author_name,
+        author_handle,
+    };
+
+    let client = ApiClient::new(opts)?;
+    let source = client.create_source(&new_source).await?.source;
+
+    if format.is_structured() {
+        output::print_serialized(&source, format)?;
     } else {
-        output::print_sources_table(&response.sources, response.total);
+        output::print_success(&format!("Saved source {}", source.id.cyan()));
+        output::print_sources_table(std::slice::from_ref(&source), 1, None);
     }
 
     Ok(())
 }
 
 /// Delete sources
-pub async fn delete(ids_str: &str, skip_confirm: bool, session: Option<String>, no_session: bool) -> Result<()> {
+pub async fn delete(
+    ids_str: &str,
+    skip_confirm: bool,
+    strict: bool,
+    opts: &ClientOptions,
+) -> Result<()> {
     let ids = parse_ids(ids_str);
     if ids.is_empty() {
         return Err(anyhow::anyhow!("No source IDs provided"));
@@ -45,19 +254,15 @@ pub async fn delete(ids_str: &str, skip_confirm: bool, session: Option<String>,
         for id in &ids {
             println!("  - {}", id);
         }
-        print!("\nAre you sure? [y/N]: ");
-        io::stdout().flush()?;
-
-        let mut input = String::new();
-        io::stdin().read_line(&mut input)?;
+        println!();
 
-        if !matches!(input.trim().to_lowercase().as_str(), "y" | "yes") {
+        if !prompt::confirm("Are you sure?", "delete")? {
             println!("{}", "Cancelled.".dimmed());
             return Ok(());
         }
     }
 
-    let client = ApiClient::new(session, no_session)?;
+    let client = ApiClient::new(opts)?;
     let response = client.delete_sources(ids).await?;
 
     // Report results
@@ -69,12 +274,148 @@ pub async fn delete(ids_str: &str, skip_confirm: bool, session: Option<String>,
         ));
     }
 
-    if !response.not_found.is_empty() {
-        output::print_warning(&format!(
-            "Not found: {}",
-            response.not_found.join(", ")
+    super::warn_not_found(&response.not_found);
+
+    super::check_strict(strict, &response.not_found, &[])
+}
+
+/// IDs of every source, following `nextCursor` to the end
+async fn all_source_ids(client: &ApiClient) -> Result<Vec<String>> {
+    let mut ids = Vec::new();
+    let mut cursor: Option<String> = None;
+    loop {
+        let page = client.list_sources(EXPORT_PAGE_SIZE, cursor.as_deref(), None).await?;
+        let fetched = page.sources.len();
+        ids.extend(page.sources.into_iter().map(|source| source.id));
+
+        cursor = page.next_cursor;
+        if cursor.is_none() || fetched == 0 {
+            return Ok(ids);
+        }
+    }
+}
+
+/// Counts reported by `sources delete --all`
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DeleteAllSummary {
+    deleted: usize,
+    not_found: usize,
+}
+
+/// Delete every source. `--yes` isn't enough for this: the user must type
+/// the number of sources, or pass `--yes-i-mean-it`, which is required when
+/// we can't prompt or the output is structured.
+pub async fn delete_all(
+    i_mean_it: bool,
+    strict: bool,
+    format: OutputFormat,
+    opts: &ClientOptions,
+) -> Result<()> {
+    if !i_mean_it && (format.is_structured() || !prompt::interactive()) {
+        return Err(anyhow::anyhow!(
+            "Refusing to delete every source without --yes-i-mean-it when not prompting"
         ));
     }
 
-    Ok(())
+    let client = ApiClient::new(opts)?;
+    let ids = all_source_ids(&client).await?;
+    if ids.is_empty() {
+        if format.is_structured() {
+            output::print_serialized(&DeleteAllSummary { deleted: 0, not_found: 0 }, format)?;
+        } else {
+            output::print_info("No sources to delete");
+        }
+        return Ok(());
+    }
+
+    if !i_mean_it {
+        println!(
+            "{}",
+            format!("This will permanently delete all {} source(s).", ids.len()).yellow()
+        );
+        let answer = prompt::read_line(
+            &format!("Type {} to confirm: ", ids.len()),
+            "pass --yes-i-mean-it",
+        )?;
+        if answer.trim() != ids.len().to_string() {
+            println!("{}", "Cancelled.".dimmed());
+            return Ok(());
+        }
+    }
+
+    let response = client.delete_sources(ids).await?;
+    if format.is_structured() {
+        let summary = DeleteAllSummary {
+            deleted: response.deleted.len(),
+            not_found: response.not_found.len(),
+        };
+        output::print_serialized(&summary, format)?;
+    } else {
+        output::print_success(&format!("Deleted {} source(s)", response.deleted.len()));
+        if !response.not_found.is_empty() {
+            output::print_warning(&format!(
+                "{} source(s) were already gone",
+                response.not_found.len()
+            ));
+        }
+    }
+
+    super::check_strict(strict, &response.not_found, &[])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(id: &str, handle: Option<&str>, name: Option<&str>, created_at: &str) -> Source {
+        Source {
+            id: id.to_string(),
+            source_type: "tweet".to_string(),
+            source_url: None,
+            content: None,
+            author_name: name.map(str::to_string),
+            author_handle: handle.map(str::to_string),
+            author_avatar_url: None,
+            published_at: None,
+            metadata: None,
+            needs_enrichment: None,
+            enriched_at: None,
+            created_at: created_at.to_string(),
+            updated_at: created_at.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_author_fields() {
+        assert_eq!(author_fields("@karpathy"), (None, Some("karpathy".to_string())));
+        assert_eq!(author_fields(" Ada Lovelace "), (Some("Ada Lovelace".to_string()), None));
+    }
+
+    fn ids(sources: &[Source]) -> Vec<&str> {
+        sources.iter().map(|s| s.id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_sort_by_date_uses_timestamps() {
+        // Lexical order would put the +02:00 timestamp last
+        let mut sources = vec![
+            source("b", None, None, "2026-01-01T12:00:00Z"),
+            source("a", None, None, "2026-01-01T13:00:00+02:00"),
+            source("bad", None, None, "garbage"),
+        ];
+        sort_sources(&mut sources, SourceSort::Date);
+        assert_eq!(ids(&sources), vec!["bad", "a", "b"]);
+    }
+
+    #[test]
+    fn test_sort_by_author_falls_back_to_name() {
+        let mut sources = vec![
+            source("z", Some("@zed"), None, ""),
+            source("m", None, Some("Mia"), ""),
+            source("none", None, None, ""),
+        ];
+        sort_sources(&mut sources, SourceSort::Author);
+        assert_eq!(ids(&sources), vec!["none", "m", "z"]);
+    }
 }
@@ -0,0 +1,54 @@
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::api::ClientOptions;
+use crate::config;
+use crate::output::{self, OutputFormat};
+
+/// Build metadata embedded by `build.rs`
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct VersionInfo {
+    version: &'static str,
+    git_commit: &'static str,
+    build_date: &'static str,
+    rustc: &'static str,
+    target: &'static str,
+    api_url: String,
+}
+
+/// Print the CLI version; with `full`, also build metadata and the API URL in use
+pub fn show(full: bool, format: OutputFormat, opts: &ClientOptions) -> Result<()> {
+    let version = env!("CARGO_PKG_VERSION");
+
+    if !full {
+        if format.is_structured() {
+            output::print_serialized(&serde_json::json!({ "version": version }), format)?;
+        } else {
+            println!("ck {}", version);
+        }
+        return Ok(());
+    }
+
+    let info = VersionInfo {
+        version,
+        git_commit: env!("CK_GIT_HASH"),
+        build_date: env!("CK_BUILD_DATE"),
+        rustc: env!("CK_RUSTC_VERSION"),
+        target: env!("CK_TARGET"),
+        api_url: config::resolve_api_url(opts.base_url.as_deref())?,
+    };
+
+    if format.is_structured() {
+        output::print_serialized(&info, format)?;
+    } else {
+        println!("ck {}", info.version);
+        println!("  Commit:  {}", info.git_commit);
+        println!("  Built:   {}", info.build_date);
+        println!("  Rustc:   {}", info.rustc);
+        println!("  Target:  {}", info.target);
+        println!("  API URL: {}", info.api_url);
+    }
+
+    Ok(())
+}
@@ -1,40 +1,253 @@
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::env;
 use std::fs;
 use std::path::PathBuf;
+use std::sync::OnceLock;
 
 const CONFIG_DIR: &str = ".candlekeep";
 const CONFIG_FILE: &str = "config.toml";
+const SESSION_FILE: &str = "session";
 const DEFAULT_API_URL: &str = "https://www.getcandlekeep.com";
-const API_URL_ENV: &str = "CANDLEKEEP_API_URL";
+pub const API_URL_ENV: &str = "CANDLEKEEP_API_URL";
+/// API version segment used in request paths (`/api/<version>/...`)
+pub const DEFAULT_API_VERSION: &str = "v1";
+/// Profile name for the top-level [auth] and [api] settings
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Profile chosen with `--profile` for this process
+static PROFILE_OVERRIDE: OnceLock<String> = OnceLock::new();
 
 #[derive(Debug, Serialize, Deserialize, Default)]
 pub struct Config {
+    /// Profile used when `--profile` isn't given (the top-level settings when unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active_profile: Option<String>,
     #[serde(default)]
     pub auth: AuthConfig,
     #[serde(default)]
     pub api: ApiConfig,
+    #[serde(default, skip_serializing_if = "LogConfig::is_empty")]
+    pub log: LogConfig,
+    #[serde(default, skip_serializing_if = "UploadConfig::is_empty")]
+    pub upload: UploadConfig,
+    /// Named API keys and URLs, managed with `ck profiles`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+/// A named API key and URL, selected with `--profile` or `ck profiles use`
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct Profile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+    /// Overrides [api] url while this profile is in use
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_url: Option<String>,
+    #[serde(flatten)]
+    pub cache: CachedUser,
+}
+
+impl Config {
+    /// Name of the profile in use: `--profile`, else the active one.
+    /// `None` means the top-level settings.
+    pub fn selected_profile_name(&self) -> Option<&str> {
+        PROFILE_OVERRIDE
+            .get()
+            .map(String::as_str)
+            .or(self.active_profile.as_deref())
+            .filter(|name| *name != DEFAULT_PROFILE)
+    }
+
+    /// The whoami cache of the profile in use; `None` for a profile that
+    /// isn't defined
+    pub fn cached_user(&self) -> Option<&CachedUser> {
+        match self.selected_profile_name() {
+            Some(name) => self.profiles.get(name).map(|profile| &profile.cache),
+            None => Some(&self.auth.cache),
+        }
+    }
+
+    /// Mutable `cached_user`, defining the profile in use if needed
+    pub fn cached_user_mut(&mut self) -> &mut CachedUser {
+        match self.selected_profile_name().map(str::to_string) {
+            Some(name) => &mut self.profiles.entry(name).or_default().cache,
+            None => &mut self.auth.cache,
+        }
+    }
+
+    /// The profile in use, if any; fails if it isn't defined
+    fn selected_profile(&self) -> Result<Option<(&str, &Profile)>> {
+        let Some(name) = self.selected_profile_name() else {
+            return Ok(None);
+        };
+        match self.profiles.get(name) {
+            Some(profile) => Ok(Some((name, profile))),
+            None => Err(anyhow::anyhow!(
+                "No profile named '{}'; see 'ck profiles list' or log in with 'ck auth login --profile {}'",
+                name,
+                name
+            )),
+        }
+    }
+}
+
+/// One row of `ck profiles list`
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileSummary {
+    pub name: String,
+    pub active: bool,
+    pub url: String,
+    /// Redacted
+    pub api_key: Option<String>,
+    /// Whether the key looks like a CandleKeep API key
+    pub key_valid: bool,
+}
+
+/// How this CLI is set up, as shown by `auth whoami --client`. Never holds
+/// the API key.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientSummary {
+    pub cli_version: &'static str,
+    pub api_url: String,
+    pub api_version: String,
+    pub profile: String,
+    /// Where settings and the API key are kept; always "file" for now
+    pub config_storage: &'static str,
+    pub config_path: String,
+    /// `CANDLEKEEP_*` variables that are set and change behavior
+    pub env_overrides: Vec<&'static str>,
+}
+
+/// Settings for `items add`
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct UploadConfig {
+    /// Refuse to upload files larger than this many bytes (unless `--force`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_size: Option<u64>,
+    /// More file types `items add` accepts, as extension = MIME type; these
+    /// add to (or replace) the built-in PDF, Markdown, and EPUB types
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub types: BTreeMap<String, String>,
+}
+
+impl UploadConfig {
+    fn is_empty(&self) -> bool {
+        self.max_size.is_none() && self.types.is_empty()
+    }
+}
+
+/// Settings for the `--log-file` request log
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct LogConfig {
+    /// Rotate the request log once it reaches this size in bytes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_bytes: Option<u64>,
+}
+
+impl LogConfig {
+    fn is_empty(&self) -> bool {
+        self.max_bytes.is_none()
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Default)]
 pub struct AuthConfig {
     pub api_key: Option<String>,
+    #[serde(flatten)]
+    pub cache: CachedUser,
+}
+
+/// The account from the last successful whoami, for offline display. Kept
+/// with each profile's key, so one account's details never show for another.
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct CachedUser {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cached_user_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cached_email: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cached_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cached_tier: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cached_item_limit: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cached_item_count: Option<i32>,
+    /// When the cached profile was fetched (RFC 3339)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cached_at: Option<String>,
+}
+
+impl CachedUser {
+    /// Forget the cached account, e.g. when the key changes
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ApiConfig {
     pub url: String,
+    /// Overall request timeout in seconds (no timeout when unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+    /// Time allowed to establish a connection, in seconds (no limit beyond
+    /// the overall timeout when unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub connect_timeout_secs: Option<u64>,
+    /// API version segment, e.g. "v1" (defaults to DEFAULT_API_VERSION)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
 }
 
 impl Default for ApiConfig {
     fn default() -> Self {
         Self {
             url: DEFAULT_API_URL.to_string(),
+            timeout_secs: None,
+            connect_timeout_secs: None,
+            version: None,
         }
     }
 }
 
+/// Keys accepted by `ck config set`
+pub const SETTABLE_KEYS: &[&str] = &[
+    "api.url",
+    "api.timeout_secs",
+    "api.connect_timeout_secs",
+    "api.version",
+    "auth.api_key",
+    "log.max_bytes",
+    "upload.max_size",
+];
+
+/// Where an effective config value came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ValueSource {
+    Env,
+    File,
+    Default,
+}
+
+/// A single resolved config value, as shown by `ck config show`
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EffectiveValue {
+    /// Where the value lives, e.g. `api.url` or `profiles.work.api_key`
+    pub key: String,
+    pub value: Option<String>,
+    pub source: ValueSource,
+    /// Environment variable that supplied the value, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub env_var: Option<&'static str>,
+}
+
 /// Get the path to the config directory (~/.candlekeep)
 pub fn config_dir() -> Result<PathBuf> {
     let home = dirs::home_dir().context("Could not determine home directory")?;
@@ -46,6 +259,11 @@ pub fn config_path() -> Result<PathBuf> {
     Ok(config_dir()?.join(CONFIG_FILE))
 }
 
+/// Get the path to the access session file (~/.candlekeep/session)
+pub fn session_path() -> Result<PathBuf> {
+    Ok(config_dir()?.join(SESSION_FILE))
+}
+
 /// Load config from file, creating defaults if it doesn't exist
 pub fn load_config() -> Result<Config> {
     let path = config_path()?;
@@ -63,6 +281,284 @@ pub fn load_config() -> Result<Config> {
     Ok(config)
 }
 
+/// Load the raw TOML table from the config file (empty if it doesn't exist)
+fn load_raw_config() -> Result<toml::Table> {
+    let path = config_path()?;
+
+    if !path.exists() {
+        return Ok(toml::Table::new());
+    }
+
+    let contents = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read config file: {}", path.display()))?;
+
+    toml::from_str(&contents).with_context(|| "Failed to parse config file")
+}
+
+/// Resolve every known setting along with where its value came from.
+/// The API key is redacted.
+pub fn effective_values() -> Result<Vec<EffectiveValue>> {
+    let config = load_config()?;
+    let raw = load_raw_config()?;
+    let in_file = |section: &str, key: &str| {
+        raw.get(section)
+            .and_then(|s| s.get(key))
+            .is_some()
+    };
+    // Show what a value expands to; an unexpandable one is shown as written
+    let shown = |key: &str, value: &str| expand_env(key, value).ok();
+
+    // With a profile in use, its key and URL are the ones requests use
+    let profile = config.selected_profile_name();
+    let profile_url = profile
+        .and_then(|name| Some((name, config.profiles.get(name)?.api_url.as_deref()?)));
+
+    let url = match (env::var(API_URL_ENV), profile_url) {
+        (Ok(url), _) => EffectiveValue {
+            key: "api.url".to_string(),
+            value: Some(url),
+            source: ValueSource::Env,
+            env_var: Some(API_URL_ENV),
+        },
+        (Err(_), Some((name, url))) => {
+            let key = format!("profiles.{}.api_url", name);
+            EffectiveValue {
+                value: Some(shown(&key, url).unwrap_or_else(|| url.to_string())),
+                key,
+                source: ValueSource::File,
+                env_var: None,
+            }
+        }
+        (Err(_), None) => EffectiveValue {
+            key: "api.url".to_string(),
+            value: Some(
+                shown("api.url", &config.api.url).unwrap_or_else(|| config.api.url.clone()),
+            ),
+            source: if in_file("api", "url") {
+                ValueSource::File
+            } else {
+                ValueSource::Default
+            },
+            env_var: None,
+        },
+    };
+
+    let timeout = EffectiveValue {
+        key: "api.timeout_secs".to_string(),
+        value: config.api.timeout_secs.map(|t| t.to_string()),
+        source: if in_file("api", "timeout_secs") {
+            ValueSource::File
+        } else {
+            ValueSource::Default
+        },
+        env_var: None,
+    };
+
+    let connect_timeout = EffectiveValue {
+        key: "api.connect_timeout_secs".to_string(),
+        value: config.api.connect_timeout_secs.map(|t| t.to_string()),
+        source: if in_file("api", "connect_timeout_secs") {
+            ValueSource::File
+        } else {
+            ValueSource::Default
+        },
+        env_var: None,
+    };
+
+    let version = EffectiveValue {
+        key: "api.version".to_string(),
+        value: Some(match config.api.version {
+            Some(ref v) => shown("api.version", v).unwrap_or_else(|| v.clone()),
+            None => DEFAULT_API_VERSION.to_string(),
+        }),
+        source: if in_file("api", "version") {
+            ValueSource::File
+        } else {
+            ValueSource::Default
+        },
+        env_var: None,
+    };
+
+    let (key_name, raw_key) = match profile {
+        Some(name) => (
+            format!("profiles.{}.api_key", name),
+            config.profiles.get(name).and_then(|p| p.api_key.as_deref()),
+        ),
+        None => ("auth.api_key".to_string(), config.auth.api_key.as_deref()),
+    };
+    let api_key = EffectiveValue {
+        value: raw_key.map(|key| match shown(&key_name, key) {
+            Some(expanded) => redact_api_key(&expanded),
+            None => key.to_string(),
+        }),
+        source: if raw_key.is_some() { ValueSource::File } else { ValueSource::Default },
+        key: key_name,
+        env_var: None,
+    };
+
+    let log_max_bytes = EffectiveValue {
+        key: "log.max_bytes".to_string(),
+        value: config.log.max_bytes.map(|b| b.to_string()),
+        source: if in_file("log", "max_bytes") {
+            ValueSource::File
+        } else {
+            ValueSource::Default
+        },
+        env_var: None,
+    };
+
+    let upload_max_size = EffectiveValue {
+        key: "upload.max_size".to_string(),
+        value: config.upload.max_size.map(|b| b.to_string()),
+        source: if in_file("upload", "max_size") {
+            ValueSource::File
+        } else {
+            ValueSource::Default
+        },
+        env_var: None,
+    };
+
+    let mut values = vec![
+        url,
+        timeout,
+        connect_timeout,
+        version,
+        api_key,
+        log_max_bytes,
+        upload_max_size,
+    ];
+    // Profile values last, so they print as one [profiles.<name>] section
+    values.sort_by_key(|v| v.key.starts_with("profiles."));
+    Ok(values)
+}
+
+/// Where `set_value` stores `key`: the profile in use holds its own
+/// `api.url` (as `api_url`) and `auth.api_key`
+pub fn stored_key(config: &Config, key: &str) -> String {
+    match (config.selected_profile_name(), key) {
+        (Some(name), "api.url") => format!("profiles.{}.api_url", name),
+        (Some(name), "auth.api_key") => format!("profiles.{}.api_key", name),
+        _ => key.to_string(),
+    }
+}
+
+/// Redact an API key for display, keeping only the `ck_` prefix
+pub fn redact_api_key(api_key: &str) -> String {
+    if api_key.starts_with("ck_") {
+        "ck_****".to_string()
+    } else {
+        "****".to_string()
+    }
+}
+
+/// Validate and apply a dotted `key = value` setting to the config
+pub fn set_value(config: &mut Config, key: &str, value: &str) -> Result<()> {
+    match key {
+        "api.url" => {
+            let url = reqwest::Url::parse(value)
+                .with_context(|| format!("Invalid URL: {}", value))?;
+            if !matches!(url.scheme(), "http" | "https") {
+                return Err(anyhow::anyhow!("API URL must use http or https: {}", value));
+            }
+            match config.selected_profile_name().map(str::to_string) {
+                Some(name) => {
+                    config.profiles.entry(name).or_default().api_url = Some(value.to_string())
+                }
+                None => config.api.url = value.to_string(),
+            }
+        }
+        "api.timeout_secs" => {
+            let secs: u64 = value
+                .parse()
+                .with_context(|| format!("Timeout must be a whole number of seconds: {}", value))?;
+            if secs == 0 {
+                return Err(anyhow::anyhow!("Timeout must be at least 1 second"));
+            }
+            config.api.timeout_secs = Some(secs);
+        }
+        "api.connect_timeout_secs" => {
+            let secs: u64 = value.parse().with_context(|| {
+                format!("Connect timeout must be a whole number of seconds: {}", value)
+            })?;
+            if secs == 0 {
+                return Err(anyhow::anyhow!("Connect timeout must be at least 1 second"));
+            }
+            config.api.connect_timeout_secs = Some(secs);
+        }
+        "api.version" => {
+            validate_api_version(value)?;
+            config.api.version = Some(value.to_string());
+        }
+        "upload.max_size" => {
+            let bytes = parse_size(value)?;
+            if bytes == 0 {
+                return Err(anyhow::anyhow!("upload.max_size must be greater than 0"));
+            }
+            config.upload.max_size = Some(bytes);
+        }
+        "log.max_bytes" => {
+            let bytes: u64 = value
+                .trim()
+                .parse()
+                .map_err(|_| anyhow::anyhow!("log.max_bytes must be a whole number of bytes"))?;
+            if bytes == 0 {
+                return Err(anyhow::anyhow!("log.max_bytes must be greater than 0"));
+            }
+            config.log.max_bytes = Some(bytes);
+        }
+        "auth.api_key" => {
+            if value.trim().is_empty() {
+                return Err(anyhow::anyhow!("API key cannot be empty"));
+            }
+            let api_key = Some(value.trim().to_string());
+            match config.selected_profile_name().map(str::to_string) {
+                Some(name) => config.profiles.entry(name).or_default().api_key = api_key,
+                None => config.auth.api_key = api_key,
+            }
+            config.cached_user_mut().clear();
+        }
+        _ => {
+            return Err(anyhow::anyhow!(
+                "Unknown config key: {}. Valid keys: {}",
+                key,
+                SETTABLE_KEYS.join(", ")
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Starting contents for a new config file opened by `ck config edit`:
+/// the defaults, with every optional setting commented out
+pub fn config_template() -> String {
+    format!(
+        "# CandleKeep CLI configuration. Uncomment the settings you need;
+# 'ck config show' lists the effective values.
+
+[api]
+url = \"{url}\"
+# timeout_secs = 30
+# connect_timeout_secs = 5
+# version = \"{version}\"
+
+# [auth]
+# api_key = \"ck_...\"  # 'ck auth login' sets this
+
+# [log]
+# max_bytes = 10485760
+
+# [upload]
+# max_size = 52428800
+#
+# [upload.types]  # More file types for 'ck items add': extension = MIME type
+# docx = \"application/vnd.openxmlformats-officedocument.wordprocessingml.document\"
+",
+        url = DEFAULT_API_URL,
+        version = DEFAULT_API_VERSION,
+    )
+}
+
 /// Save config to file, creating directory if needed
 pub fn save_config(config: &Config) -> Result<()> {
     let dir = config_dir()?;
@@ -82,33 +578,249 @@ pub fn save_config(config: &Config) -> Result<()> {
     Ok(())
 }
 
-/// Get the API key from config
+/// Expand `${VAR}` and `$VAR` references in the config value at `key`.
+/// `$$` is a literal `$`. Values are expanded when used, not when loaded, so
+/// saving the config never writes secrets from the environment into the file.
+pub fn expand_env(key: &str, value: &str) -> Result<String> {
+    expand_with(key, value, |name| env::var(name).ok())
+}
+
+fn expand_with(key: &str, value: &str, lookup: impl Fn(&str) -> Option<String>) -> Result<String> {
+    let is_name_char = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let mut out = String::with_capacity(value.len());
+    let mut rest = value;
+
+    while let Some(start) = rest.find('$') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+
+        let (name, remainder) = if let Some(braced) = after.strip_prefix('{') {
+            let end = braced.find('}').ok_or_else(|| {
+                anyhow::anyhow!("Unclosed '${{' in config value {}: {}", key, value)
+            })?;
+            (&braced[..end], &braced[end + 1..])
+        } else if let Some(literal) = after.strip_prefix('$') {
+            out.push('$');
+            rest = literal;
+            continue;
+        } else if !after.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+            // A lone `$`, e.g. in "costs $5"
+            out.push('$');
+            rest = after;
+            continue;
+        } else {
+            let end = after.find(|c: char| !is_name_char(c)).unwrap_or(after.len());
+            (&after[..end], &after[end..])
+        };
+
+        let valid = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            && name.chars().all(is_name_char);
+        if !valid {
+            return Err(anyhow::anyhow!(
+                "Invalid variable name '{}' in config value {}",
+                name,
+                key
+            ));
+        }
+
+        let expanded = lookup(name).ok_or_else(|| {
+            anyhow::anyhow!(
+                "Config value {} references ${}, which isn't set in the environment",
+                key,
+                name
+            )
+        })?;
+        out.push_str(&expanded);
+        rest = remainder;
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Use `name` instead of the active profile for the rest of the process
+/// (the `--profile` flag)
+pub fn set_profile(name: &str) {
+    let _ = PROFILE_OVERRIDE.set(name.to_string());
+}
+
+/// Check a profile name: letters, digits, '-' and '_'
+pub fn validate_profile_name(name: &str) -> Result<()> {
+    if !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!(
+            "Invalid profile name: '{}'. Use letters, digits, '-' and '_'",
+            name
+        ))
+    }
+}
+
+/// `--profile` value parser
+pub fn parse_profile_name(name: &str) -> std::result::Result<String, String> {
+    validate_profile_name(name).map(|()| name.to_string()).map_err(|e| e.to_string())
+}
+
+/// Get the API key of the profile in use from config
 pub fn get_api_key() -> Result<Option<String>> {
     let config = load_config()?;
-    Ok(config.auth.api_key)
+    match config.selected_profile()? {
+        Some((name, profile)) => profile
+            .api_key
+            .as_deref()
+            .map(|key| expand_env(&format!("profiles.{}.api_key", name), key))
+            .transpose(),
+        None => config
+            .auth
+            .api_key
+            .map(|key| expand_env("auth.api_key", &key))
+            .transpose(),
+    }
+}
+
+/// Strip trailing slashes so paths can be appended without producing `//`
+pub fn normalize_base_url(url: &str) -> String {
+    url.trim().trim_end_matches('/').to_string()
 }
 
 /// Get the API URL from environment variable or config
 pub fn get_api_url() -> Result<String> {
     // Environment variable takes precedence
     if let Ok(url) = env::var(API_URL_ENV) {
-        return Ok(url);
+        return Ok(normalize_base_url(&url));
     }
     let config = load_config()?;
-    Ok(config.api.url)
+    // A profile that doesn't exist yet (`auth login --profile new`) uses [api] url
+    if let Some(name) = config.selected_profile_name() {
+        if let Some(url) = config.profiles.get(name).and_then(|p| p.api_url.as_deref()) {
+            let key = format!("profiles.{}.api_url", name);
+            return Ok(normalize_base_url(&expand_env(&key, url)?));
+        }
+    }
+    Ok(normalize_base_url(&expand_env("api.url", &config.api.url)?))
+}
+
+/// Size at which the request log rotates, if configured
+pub fn get_log_max_bytes() -> Result<Option<u64>> {
+    let config = load_config()?;
+    Ok(config.log.max_bytes)
+}
+
+/// Upload size limit from config, in bytes
+pub fn get_upload_max_size() -> Result<Option<u64>> {
+    let config = load_config()?;
+    Ok(config.upload.max_size)
+}
+
+/// Extra upload types from `[upload.types]`, extension to MIME type
+pub fn get_upload_types() -> Result<BTreeMap<String, String>> {
+    let config = load_config()?;
+    Ok(config.upload.types)
 }
 
-/// Save API key to config
+/// Parse a size like `1048576`, `500KB`, `20MB` or `1.5GB` into bytes.
+/// Units are binary (1 KB = 1024 bytes) and case-insensitive.
+pub fn parse_size(value: &str) -> Result<u64> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+
+    let multiplier: u64 = match unit.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 1,
+        "K" | "KB" | "KIB" => 1024,
+        "M" | "MB" | "MIB" => 1024 * 1024,
+        "G" | "GB" | "GIB" => 1024 * 1024 * 1024,
+        _ => return Err(anyhow::anyhow!("Unknown size unit in '{}' (use B, KB, MB or GB)", value)),
+    };
+    let number: f64 = number
+        .parse()
+        .map_err(|_| anyhow::anyhow!("Invalid size: '{}' (e.g. 500KB, 20MB, 1.5GB)", value))?;
+
+    Ok((number * multiplier as f64).round() as u64)
+}
+
+/// Get the request timeout from config
+pub fn get_timeout_secs() -> Result<Option<u64>> {
+    let config = load_config()?;
+    Ok(config.api.timeout_secs)
+}
+
+/// Get the connect timeout from config
+pub fn get_connect_timeout_secs() -> Result<Option<u64>> {
+    let config = load_config()?;
+    Ok(config.api.connect_timeout_secs)
+}
+
+/// Get the API URL, preferring an explicit override (the `--base-url` flag)
+/// over the environment variable and config file
+pub fn resolve_api_url(override_url: Option<&str>) -> Result<String> {
+    match override_url {
+        Some(url) => {
+            let parsed = reqwest::Url::parse(url)
+                .with_context(|| format!("Invalid --base-url: {}", url))?;
+            if !matches!(parsed.scheme(), "http" | "https") {
+                return Err(anyhow::anyhow!("--base-url must use http or https: {}", url));
+            }
+            Ok(normalize_base_url(url))
+        }
+        None => get_api_url(),
+    }
+}
+
+/// Check that an API version looks like `v<number>` (e.g. "v1", "v2")
+pub fn validate_api_version(version: &str) -> Result<()> {
+    let valid = version
+        .strip_prefix('v')
+        .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()));
+
+    if valid {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!(
+            "Invalid API version: '{}'. Expected a version like 'v1' or 'v2'",
+            version
+        ))
+    }
+}
+
+/// Get the API version, preferring an explicit override (the `--api-version`
+/// flag) over the config file, falling back to DEFAULT_API_VERSION
+pub fn resolve_api_version(override_version: Option<&str>) -> Result<String> {
+    let version = match override_version {
+        Some(v) => v.to_string(),
+        None => match load_config()?.api.version {
+            Some(v) => expand_env("api.version", &v)?,
+            None => DEFAULT_API_VERSION.to_string(),
+        },
+    };
+    validate_api_version(&version)?;
+    Ok(version)
+}
+
+/// Save API key to config, in the profile in use (creating it if needed)
 pub fn save_api_key(api_key: &str) -> Result<()> {
     let mut config = load_config()?;
-    config.auth.api_key = Some(api_key.to_string());
+    match config.selected_profile_name().map(str::to_string) {
+        Some(name) => config.profiles.entry(name).or_default().api_key = Some(api_key.to_string()),
+        None => config.auth.api_key = Some(api_key.to_string()),
+    }
+    config.cached_user_mut().clear();
     save_config(&config)
 }
 
-/// Clear all credentials from config
+/// Clear the credentials of the profile in use from config
 pub fn clear_config() -> Result<()> {
     let mut config = load_config()?;
-    config.auth.api_key = None;
+    match config.selected_profile_name().map(str::to_string) {
+        Some(name) => {
+            if let Some(profile) = config.profiles.get_mut(&name) {
+                profile.api_key = None;
+            }
+        }
+        None => config.auth.api_key = None,
+    }
+    config.cached_user_mut().clear();
     save_config(&config)
 }
 
@@ -141,4 +853,160 @@ mod tests {
             Some("ck_test123".to_string())
         );
     }
+
+    #[test]
+    fn test_profiles_round_trip_and_selection() {
+        let mut config = Config {
+            active_profile: Some("work".to_string()),
+            ..Default::default()
+        };
+        config.profiles.insert(
+            "work".to_string(),
+            Profile { api_key: Some("ck_work".to_string()), ..Default::default() },
+        );
+
+        let serialized = toml::to_string_pretty(&config).unwrap();
+        let deserialized: Config = toml::from_str(&serialized).unwrap();
+        assert_eq!(deserialized.selected_profile_name(), Some("work"));
+        let (_, profile) = deserialized.selected_profile().unwrap().unwrap();
+        assert_eq!(profile.api_key.as_deref(), Some("ck_work"));
+
+        config.active_profile = Some(DEFAULT_PROFILE.to_string());
+        assert_eq!(config.selected_profile_name(), None);
+        config.active_profile = Some("gone".to_string());
+        assert!(config.selected_profile().is_err());
+
+        assert!(validate_profile_name("ci-2_x").is_ok());
+        assert!(validate_profile_name("a b").is_err());
+        assert!(validate_profile_name("").is_err());
+    }
+
+    #[test]
+    fn test_whoami_cache_is_kept_per_profile() {
+        let mut config: Config = toml::from_str(
+            r#"
+            active_profile = "work"
+
+            [auth]
+            api_key = "ck_home"
+            cached_email = "home@example.com"
+
+            [profiles.work]
+            api_key = "ck_work"
+            cached_email = "work@example.com"
+            cached_item_limit = 50
+            "#,
+        )
+        .unwrap();
+        let email = |config: &Config| config.cached_user()?.cached_email.clone();
+        assert_eq!(email(&config).as_deref(), Some("work@example.com"));
+        assert_eq!(config.cached_user().unwrap().cached_item_limit, Some(50));
+
+        config.cached_user_mut().clear();
+        config.active_profile = None;
+        assert_eq!(email(&config).as_deref(), Some("home@example.com"));
+
+        // The file layout is unchanged: cached_* stay next to each key
+        let serialized = toml::to_string_pretty(&config).unwrap();
+        assert!(serialized.contains("cached_email = \"home@example.com\""), "{}", serialized);
+        assert!(!serialized.contains("work@example.com"), "{}", serialized);
+    }
+
+    #[test]
+    fn test_set_value() {
+        let mut config = Config::default();
+        set_value(&mut config, "api.url", "http://localhost:3000").unwrap();
+        set_value(&mut config, "api.timeout_secs", "30").unwrap();
+
+        assert_eq!(config.api.url, "http://localhost:3000");
+        assert_eq!(config.api.timeout_secs, Some(30));
+    }
+
+    #[test]
+    fn test_set_value_writes_to_the_profile_in_use() {
+        let mut config = Config { active_profile: Some("work".to_string()), ..Default::default() };
+        set_value(&mut config, "auth.api_key", "ck_work1234").unwrap();
+        set_value(&mut config, "api.url", "http://localhost:3000").unwrap();
+        set_value(&mut config, "api.timeout_secs", "30").unwrap();
+
+        let work = &config.profiles["work"];
+        assert_eq!(work.api_key.as_deref(), Some("ck_work1234"));
+        assert_eq!(work.api_url.as_deref(), Some("http://localhost:3000"));
+        assert!(config.auth.api_key.is_none());
+        assert_eq!(config.api.url, DEFAULT_API_URL);
+        assert_eq!(config.api.timeout_secs, Some(30));
+        assert_eq!(stored_key(&config, "auth.api_key"), "profiles.work.api_key");
+        assert_eq!(stored_key(&config, "api.timeout_secs"), "api.timeout_secs");
+    }
+
+    #[test]
+    fn test_set_value_rejects_invalid() {
+        let mut config = Config::default();
+        assert!(set_value(&mut config, "api.url", "not a url").is_err());
+        assert!(set_value(&mut config, "api.timeout_secs", "0").is_err());
+        assert!(set_value(&mut config, "api.timeout_secs", "soon").is_err());
+        assert!(set_value(&mut config, "api.connect_timeout_secs", "0").is_err());
+        assert!(set_value(&mut config, "log.max_bytes", "0").is_err());
+        assert!(set_value(&mut config, "log.max_bytes", "big").is_err());
+
+        let err = set_value(&mut config, "api.color", "red").unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("api.url, api.timeout_secs, api.connect_timeout_secs, api.version"));
+    }
+
+    #[test]
+    fn test_expand_env_references() {
+        let lookup = |name: &str| match name {
+            "CK_KEY" => Some("ck_secret".to_string()),
+            "HOST" => Some("example.com".to_string()),
+            _ => None,
+        };
+        let expand = |value| expand_with("auth.api_key", value, lookup);
+
+        assert_eq!(expand("${CK_KEY}").unwrap(), "ck_secret");
+        assert_eq!(expand("https://$HOST/api").unwrap(), "https://example.com/api");
+        assert_eq!(expand("plain").unwrap(), "plain");
+        assert_eq!(expand("$$HOST costs $5").unwrap(), "$HOST costs $5");
+
+        let err = expand("${MISSING}").unwrap_err().to_string();
+        assert!(err.contains("auth.api_key") && err.contains("$MISSING"), "{}", err);
+        assert!(expand("${CK_KEY").is_err());
+        assert!(expand("${1X}").is_err());
+    }
+
+    #[test]
+    fn test_config_template_parses_to_defaults() {
+        let config: Config = toml::from_str(&config_template()).unwrap();
+        assert_eq!(config.api.url, DEFAULT_API_URL);
+        assert!(config.api.timeout_secs.is_none());
+        assert!(config.auth.api_key.is_none());
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("2048").unwrap(), 2048);
+        assert_eq!(parse_size("500KB").unwrap(), 500 * 1024);
+        assert_eq!(parse_size("20 mb").unwrap(), 20 * 1024 * 1024);
+        assert_eq!(parse_size("1.5GB").unwrap(), 3 * 512 * 1024 * 1024);
+        assert!(parse_size("20TB").is_err());
+        assert!(parse_size("MB").is_err());
+    }
+
+    #[test]
+    fn test_validate_api_version() {
+        assert!(validate_api_version("v1").is_ok());
+        assert!(validate_api_version("v12").is_ok());
+        assert!(validate_api_version("").is_err());
+        assert!(validate_api_version("v").is_err());
+        assert!(validate_api_version("1").is_err());
+        assert!(validate_api_version("v2beta").is_err());
+        assert!(validate_api_version("v1/../admin").is_err());
+    }
+
+    #[test]
+    fn test_redact_api_key() {
+        assert_eq!(redact_api_key("ck_secret"), "ck_****");
+        assert_eq!(redact_api_key("secret"), "****");
+    }
 }
@@ -0,0 +1,481 @@
+//! Local daemon that keeps API connections warm between `ck` invocations.
+//!
+//! `ck daemon start` listens on a Unix socket (`~/.candlekeep/daemon.sock`).
+//! With `--daemon`, each API request a command makes is forwarded there and
+//! sent over the daemon's long-lived HTTP client, so repeated invocations
+//! skip the TCP/TLS handshake. The command itself still runs in the calling
+//! process; only the HTTP exchange moves. If no daemon answers, requests go
+//! out directly as usual.
+//!
+//! Protocol: the client writes one JSON object per line and reads one JSON
+//! reply per line; a connection may carry any number of exchanges.
+//!
+//! - `{"op":"health"}` replies `{"result":"health","protocol":2,"version":…,
+//!   "pid":…,"uptimeSecs":…,"requests":…}`
+//! - `{"op":"shutdown"}` replies `{"result":"shutdown"}`, then the daemon exits
+//! - `{"op":"http","method":"GET","url":…,"headers":[[name,value],…],
+//!   "body":…,"timeoutMs":…,"maxBodyBytes":…}` replies `{"result":"http",
+//!   "status":200,"headers":[…],"body":…}`; `body`, `timeoutMs` and
+//!   `maxBodyBytes` are optional and bodies are UTF-8 text. A response body
+//!   that isn't text replies `{"result":"unforwardable"}`, and the client
+//!   sends the request directly; one over `maxBodyBytes` replies an error
+//!   instead of being read in full.
+//! - any failure replies `{"result":"error","message":…,"detail":…,
+//!   "transient":…}`; `transient` marks failures worth retrying
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+
+use crate::api::ClientOptions;
+use crate::config;
+
+/// Bumped when the wire format changes incompatibly
+pub const PROTOCOL_VERSION: u32 = 2;
+/// Default for `ck daemon start --idle-timeout`
+pub const DEFAULT_IDLE_TIMEOUT_SECS: u64 = 900;
+
+/// Set once connecting fails, so the rest of the invocation skips the socket
+static UNAVAILABLE: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum DaemonRequest {
+    Health,
+    Shutdown,
+    Http(HttpRequest),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "result", rename_all = "lowercase")]
+pub enum DaemonReply {
+    Health(Health),
+    Shutdown,
+    Http(HttpResponse),
+    /// The response can't be carried as text; send the request directly
+    Unforwardable,
+    Error {
+        message: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        detail: Option<String>,
+        #[serde(default)]
+        transient: bool,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HttpRequest {
+    pub method: String,
+    pub url: String,
+    #[serde(default)]
+    pub headers: Vec<(String, String)>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+    /// The caller's `--max-response-size`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_body_bytes: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HttpResponse {
+    pub status: u16,
+    #[serde(default)]
+    pub headers: Vec<(String, String)>,
+    #[serde(default)]
+    pub body: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Health {
+    pub protocol: u32,
+    pub version: String,
+    pub pid: u32,
+    pub uptime_secs: u64,
+    /// HTTP requests forwarded since the daemon started
+    pub requests: u64,
+}
+
+/// A request the daemon sent but got no response to, mirroring the
+/// transport errors of a direct request
+#[derive(Debug)]
+pub struct ForwardError {
+    pub detail: String,
+    pub transient: bool,
+}
+
+impl std::fmt::Display for ForwardError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (via daemon)", self.detail)
+    }
+}
+
+impl std::error::Error for ForwardError {}
+
+/// The daemon socket (~/.candlekeep/daemon.sock)
+pub fn socket_path() -> Result<PathBuf> {
+    Ok(config::config_dir()?.join("daemon.sock"))
+}
+
+#[cfg(unix)]
+type Stream = tokio::net::UnixStream;
+#[cfg(not(unix))]
+type Stream = tokio::io::DuplexStream;
+
+#[cfg(unix)]
+async fn connect(path: &Path) -> std::io::Result<Stream> {
+    tokio::net::UnixStream::connect(path).await
+}
+
+#[cfg(not(unix))]
+async fn connect(_path: &Path) -> std::io::Result<Stream> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "the daemon needs Unix domain sockets",
+    ))
+}
+
+/// Send one request and read its reply
+async fn exchange<S: AsyncRead + AsyncWrite + Unpin>(
+    stream: S,
+    request: &DaemonRequest,
+) -> Result<DaemonReply> {
+    let mut stream = BufReader::new(stream);
+    let mut line = serde_json::to_string(request)?;
+    line.push('\n');
+    stream.get_mut().write_all(line.as_bytes()).await?;
+
+    let mut reply = String::new();
+    if stream.read_line(&mut reply).await? == 0 {
+        return Err(anyhow::anyhow!("The daemon closed the connection without replying"));
+    }
+    serde_json::from_str(&reply).context("Failed to parse the daemon's reply")
+}
+
+/// Send a control request (health or shutdown) to the daemon at `path`.
+/// Returns None if no daemon is listening there.
+pub async fn call(path: &Path, request: &DaemonRequest) -> Result<Option<DaemonReply>> {
+    match connect(path).await {
+        Ok(stream) => exchange(stream, request).await.map(Some),
+        Err(_) => Ok(None),
+    }
+}
+
+/// Send `request` through the daemon at `path`.
+///
+/// Returns None when the request should go out directly instead: no daemon
+/// is running, or the request or its response has a body that can't be
+/// forwarded (streamed or binary). `timeout` is the caller's overall request
+/// timeout and `max_body` its largest response body.
+pub async fn forward(
+    path: &Path,
+    request: &reqwest::Request,
+    timeout: Option<Duration>,
+    max_body: u64,
+) -> Option<Result<reqwest::Response>> {
+    if UNAVAILABLE.load(Ordering::Relaxed) {
+        return None;
+    }
+    let http = to_http_request(request, timeout, max_body)?;
+    let stream = match connect(path).await {
+        Ok(stream) => stream,
+        Err(_) => {
+            UNAVAILABLE.store(true, Ordering::Relaxed);
+            return None;
+        }
+    };
+
+    let reply = match exchange(stream, &DaemonRequest::Http(http)).await {
+        Ok(reply) => reply,
+        Err(e) => return Some(Err(e.context("Daemon request failed"))),
+    };
+    Some(match reply {
+        DaemonReply::Http(response) => from_http_response(response),
+        DaemonReply::Unforwardable => return None,
+        DaemonReply::Error { message, detail, transient } => Err(match detail {
+            Some(detail) => anyhow::Error::new(ForwardError { detail, transient }).context(message),
+            None => anyhow::Error::new(ForwardError { detail: message, transient }),
+        }),
+        other => Err(anyhow::anyhow!("Unexpected reply from the daemon: {:?}", other)),
+    })
+}
+
+/// The wire form of `request`, or None if its body can't be sent as text
+fn to_http_request(
+    request: &reqwest::Request,
+    timeout: Option<Duration>,
+    max_body: u64,
+) -> Option<HttpRequest> {
+    let body = match request.body() {
+        None => None,
+        Some(body) => Some(String::from_utf8(body.as_bytes()?.to_vec()).ok()?),
+    };
+    let headers = request
+        .headers()
+        .iter()
+        .map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+        .collect::<Option<Vec<_>>>()?;
+
+    Some(HttpRequest {
+        method: request.method().to_string(),
+        url: request.url().to_string(),
+        headers,
+        body,
+        timeout_ms: request
+            .timeout()
+            .copied()
+            .or(timeout)
+            .map(|t| t.as_millis() as u64),
+        max_body_bytes: Some(max_body),
+    })
+}
+
+fn from_http_response(response: HttpResponse) -> Result<reqwest::Response> {
+    let mut builder = http::Response::builder().status(response.status);
+    for (name, value) in &response.headers {
+        builder = builder.header(name, value);
+    }
+    let response = builder
+        .body(response.body)
+        .context("The daemon sent an invalid response")?;
+    Ok(reqwest::Response::from(response))
+}
+
+/// Shared by every connection the daemon serves
+#[cfg(unix)]
+struct State {
+    client: reqwest::Client,
+    started: std::time::Instant,
+    requests: std::sync::atomic::AtomicU64,
+    connections: std::sync::atomic::AtomicUsize,
+    shutdown: tokio::sync::Notify,
+}
+
+/// Listen on `path` until a shutdown request or Ctrl-C arrives, or no client
+/// has been connected for `idle_timeout` (never, if None). The socket file
+/// is removed on exit.
+#[cfg(unix)]
+pub async fn serve(
+    path: &Path,
+    idle_timeout: Option<Duration>,
+    opts: &ClientOptions,
+) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    use std::sync::Arc;
+
+    if path.exists() {
+        if connect(path).await.is_ok() {
+            return Err(anyhow::anyhow!("A daemon is already running at {}", path.display()));
+        }
+        // Left behind by a daemon that didn't exit cleanly
+        std::fs::remove_file(path)
+            .with_context(|| format!("Failed to remove stale socket {}", path.display()))?;
+    }
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)?;
+    }
+
+    let listener = tokio::net::UnixListener::bind(path)
+        .with_context(|| format!("Failed to listen on {}", path.display()))?;
+    // Requests carry the API key, so only the owner may connect
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+
+    let state = Arc::new(State {
+        client: crate::api::ApiClient::http_client(opts)?,
+        started: std::time::Instant::now(),
+        requests: Default::default(),
+        connections: Default::default(),
+        shutdown: tokio::sync::Notify::new(),
+    });
+
+    loop {
+        let wait = idle_timeout.unwrap_or(Duration::MAX);
+        tokio::select! {
+            accepted = tokio::time::timeout(wait, listener.accept()) => match accepted {
+                Ok(Ok((stream, _))) => {
+                    let state = Arc::clone(&state);
+                    tokio::spawn(async move { serve_connection(stream, &state).await });
+                }
+                Ok(Err(e)) => eprintln!("Failed to accept a connection: {}", e),
+                // Idle only counts while nobody is connected
+                Err(_) if state.connections.load(Ordering::SeqCst) == 0 => {
+                    eprintln!("Idle for {}s; exiting", wait.as_secs());
+                    break;
+                }
+                Err(_) => {}
+            },
+            _ = state.shutdown.notified() => break,
+            _ = tokio::signal::ctrl_c() => {
+                eprintln!("Interrupted; exiting");
+                break;
+            }
+        }
+    }
+
+    let _ = std::fs::remove_file(path);
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub async fn serve(
+    _path: &Path,
+    _idle_timeout: Option<Duration>,
+    _opts: &ClientOptions,
+) -> Result<()> {
+    Err(anyhow::anyhow!(
+        "The daemon needs Unix domain sockets, which this platform doesn't have"
+    ))
+}
+
+#[cfg(unix)]
+async fn serve_connection(stream: tokio::net::UnixStream, state: &State) {
+    state.connections.fetch_add(1, Ordering::SeqCst);
+    let (read, mut write) = stream.into_split();
+    let mut lines = BufReader::new(read).lines();
+
+    while let Ok(Some(line)) = lines.next_line().await {
+        let (reply, shutdown) = match serde_json::from_str::<DaemonRequest>(&line) {
+            Ok(DaemonRequest::Health) => (
+                DaemonReply::Health(Health {
+                    protocol: PROTOCOL_VERSION,
+                    version: env!("CARGO_PKG_VERSION").to_string(),
+                    pid: std::process::id(),
+                    uptime_secs: state.started.elapsed().as_secs(),
+                    requests: state.requests.load(Ordering::Relaxed),
+                }),
+                false,
+            ),
+            Ok(DaemonRequest::Shutdown) => (DaemonReply::Shutdown, true),
+            Ok(DaemonRequest::Http(request)) => {
+                state.requests.fetch_add(1, Ordering::Relaxed);
+                (send(&state.client, request).await, false)
+            }
+            Err(e) => (
+                DaemonReply::Error {
+                    message: format!("Invalid request: {}", e),
+                    detail: None,
+                    transient: false,
+                },
+                false,
+            ),
+        };
+
+        let Ok(mut out) = serde_json::to_string(&reply) else {
+            break;
+        };
+        out.push('\n');
+        if write.write_all(out.as_bytes()).await.is_err() {
+            break;
+        }
+        if shutdown {
+            state.shutdown.notify_one();
+            break;
+        }
+    }
+    state.connections.fetch_sub(1, Ordering::SeqCst);
+}
+
+/// Make a forwarded request with the daemon's warm client
+#[cfg(unix)]
+async fn send(client: &reqwest::Client, request: HttpRequest) -> DaemonReply {
+    let invalid = |message: String| DaemonReply::Error { message, detail: None, transient: false };
+    let max_body = request.max_body_bytes.unwrap_or(u64::MAX);
+    let Ok(method) = reqwest::Method::from_bytes(request.method.as_bytes()) else {
+        return invalid(format!("Invalid method: {}", request.method));
+    };
+    let mut builder = client.request(method, &request.url);
+    for (name, value) in &request.headers {
+        builder = builder.header(name, value);
+    }
+    if let Some(body) = request.body {
+        builder = builder.body(body);
+    }
+    if let Some(ms) = request.timeout_ms {
+        builder = builder.timeout(Duration::from_millis(ms));
+    }
+
+    let response = match builder.send().await {
+        Ok(response) => response,
+        Err(e) => {
+            let host = reqwest::Url::parse(&request.url)
+                .ok()
+                .and_then(|url| url.host_str().map(str::to_string))
+                .unwrap_or_default();
+            return DaemonReply::Error {
+                message: crate::api::transport_error_message(&e, &host),
+                transient: e.is_connect() || e.is_timeout() || e.is_request(),
+                detail: Some(e.to_string()),
+            };
+        }
+    };
+
+    let status = response.status().as_u16();
+    let headers = response
+        .headers()
+        .iter()
+        .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+        .collect();
+    match crate::api::read_capped(response, max_body).await.map(String::from_utf8) {
+        Ok(Ok(body)) => DaemonReply::Http(HttpResponse { status, headers, body }),
+        Ok(Err(_)) => DaemonReply::Unforwardable,
+        // Over the size limit; retrying won't help
+        Err(e) if !e.chain().any(|cause| cause.is::<reqwest::Error>()) => invalid(e.to_string()),
+        Err(e) => DaemonReply::Error {
+            message: "Failed to read the response".to_string(),
+            detail: Some(format!("{:#}", e)),
+            transient: true,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_protocol_round_trip() {
+        let request: DaemonRequest = serde_json::from_str(
+            r#"{"op":"http","method":"GET","url":"https://host/api/v1/items","headers":[["a","b"]]}"#,
+        )
+        .unwrap();
+        let DaemonRequest::Http(http) = request else {
+            panic!("expected an http request");
+        };
+        assert_eq!(http.headers, vec![("a".to_string(), "b".to_string())]);
+        assert_eq!(http.body, None);
+
+        let reply = serde_json::to_string(&DaemonReply::Error {
+            message: "Couldn't reach host".to_string(),
+            detail: None,
+            transient: true,
+        })
+        .unwrap();
+        assert_eq!(reply, r#"{"result":"error","message":"Couldn't reach host","transient":true}"#);
+        assert!(serde_json::from_str::<DaemonRequest>(r#"{"op":"restart"}"#).is_err());
+    }
+
+    #[test]
+    fn test_only_text_bodies_are_forwarded() {
+        let client = reqwest::Client::new();
+        let request = client
+            .post("https://host/api/v1/items/markdown")
+            .header("Authorization", "Bearer ck_test")
+            .body("{\"title\":\"T\"}")
+            .build()
+            .unwrap();
+        let http = to_http_request(&request, Some(Duration::from_secs(5)), 1024).unwrap();
+        assert_eq!(http.method, "POST");
+        assert_eq!(http.body.as_deref(), Some("{\"title\":\"T\"}"));
+        assert_eq!(http.timeout_ms, Some(5000));
+        assert_eq!(http.max_body_bytes, Some(1024));
+
+        let binary = client.post("https://host/").body(vec![0xff, 0xfe]).build().unwrap();
+        assert!(to_http_request(&binary, None, 1024).is_none());
+    }
+}
@@ -0,0 +1,119 @@
+use serde::Serialize;
+use similar::{ChangeTag, TextDiff};
+
+/// Lines of unchanged context around each hunk, as in `diff -u`
+const CONTEXT_LINES: usize = 3;
+
+/// Line-level comparison of a stored document against local content
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentDiff {
+    /// "identical" or "differs"
+    pub status: &'static str,
+    pub hunks: Vec<Hunk>,
+}
+
+/// One unified-diff hunk; line numbers are 1-based as in `@@` headers
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Hunk {
+    pub old_start: usize,
+    pub old_lines: usize,
+    pub new_start: usize,
+    pub new_lines: usize,
+    pub lines: Vec<DiffLine>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffLine {
+    /// "context", "added" or "removed"
+    pub kind: &'static str,
+    pub text: String,
+}
+
+impl DocumentDiff {
+    pub fn is_identical(&self) -> bool {
+        self.hunks.is_empty()
+    }
+}
+
+impl Hunk {
+    /// The `@@ -a,b +c,d @@` header line
+    pub fn header(&self) -> String {
+        format!(
+            "@@ -{},{} +{},{} @@",
+            self.old_start, self.old_lines, self.new_start, self.new_lines
+        )
+    }
+}
+
+/// Diff `old` (stored) against `new` (local) line by line
+pub fn diff_documents(old: &str, new: &str) -> DocumentDiff {
+    let diff = TextDiff::from_lines(old, new);
+
+    let hunks: Vec<Hunk> = diff
+        .grouped_ops(CONTEXT_LINES)
+        .iter()
+        .filter_map(|group| {
+            let first = group.first()?;
+            let last = group.last()?;
+            let old_range = first.old_range().start..last.old_range().end;
+            let new_range = first.new_range().start..last.new_range().end;
+
+            let lines = group
+                .iter()
+                .flat_map(|op| diff.iter_changes(op))
+                .map(|change| DiffLine {
+                    kind: match change.tag() {
+                        ChangeTag::Equal => "context",
+                        ChangeTag::Insert => "added",
+                        ChangeTag::Delete => "removed",
+                    },
+                    text: change.value().trim_end_matches(['\n', '\r']).to_string(),
+                })
+                .collect();
+
+            // Empty ranges start at the line before, like GNU diff
+            Some(Hunk {
+                old_start: old_range.start + usize::from(!old_range.is_empty()),
+                old_lines: old_range.len(),
+                new_start: new_range.start + usize::from(!new_range.is_empty()),
+                new_lines: new_range.len(),
+                lines,
+            })
+        })
+        .collect();
+
+    DocumentDiff {
+        status: if hunks.is_empty() { "identical" } else { "differs" },
+        hunks,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identical_documents_have_no_hunks() {
+        let diff = diff_documents("a\nb\n", "a\nb\n");
+        assert!(diff.is_identical());
+        assert_eq!(diff.status, "identical");
+    }
+
+    #[test]
+    fn test_changed_line_produces_one_hunk() {
+        let diff = diff_documents("a\nb\nc\n", "a\nB\nc\n");
+        assert_eq!(diff.status, "differs");
+        assert_eq!(diff.hunks.len(), 1);
+
+        let hunk = &diff.hunks[0];
+        assert_eq!(hunk.header(), "@@ -1,3 +1,3 @@");
+        let kinds: Vec<_> = hunk.lines.iter().map(|l| (l.kind, l.text.as_str())).collect();
+        assert_eq!(
+            kinds,
+            vec![("context", "a"), ("removed", "b"), ("added", "B"), ("context", "c")]
+        );
+    }
+}
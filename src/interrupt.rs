@@ -0,0 +1,139 @@
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::api::{ApiClient, ClientOptions};
+
+/// Exit code after Ctrl-C, as shells report for SIGINT
+const EXIT_INTERRUPTED: i32 = 130;
+/// Time allowed for the network part of cleanup before exiting anyway
+const CLEANUP_BUDGET: Duration = Duration::from_secs(2);
+
+/// What an interrupted command leaves behind
+#[derive(Default)]
+struct Pending {
+    /// Temp files to remove
+    temp_files: Vec<PathBuf>,
+    /// Items created for an upload that hasn't been confirmed yet
+    uploads: Vec<String>,
+    /// A research session this command opened
+    session: Option<String>,
+}
+
+static PENDING: Mutex<Pending> = Mutex::new(Pending {
+    temp_files: Vec::new(),
+    uploads: Vec::new(),
+    session: None,
+});
+
+/// Set while a child such as the pager owns the terminal; Ctrl-C is meant
+/// for it then (e.g. to cancel a search in `less`), not for us
+static SUSPENDED: AtomicBool = AtomicBool::new(false);
+
+fn pending() -> std::sync::MutexGuard<'static, Pending> {
+    PENDING.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Remove `path` if the command is interrupted, until `forget_temp_file`
+pub fn track_temp_file(path: PathBuf) {
+    pending().temp_files.push(path);
+}
+
+pub fn forget_temp_file(path: &std::path::Path) {
+    pending().temp_files.retain(|p| p != path);
+}
+
+/// Complete `session_id` and remove the session file if the command is
+/// interrupted; only for a session the command opened itself
+pub fn track_session(session_id: &str) {
+    pending().session = Some(session_id.to_string());
+}
+
+/// Ignores Ctrl-C until dropped
+pub struct Suspended(());
+
+impl Suspended {
+    pub fn new() -> Self {
+        SUSPENDED.store(true, Ordering::SeqCst);
+        Self(())
+    }
+}
+
+impl Drop for Suspended {
+    fn drop(&mut self) {
+        SUSPENDED.store(false, Ordering::SeqCst);
+    }
+}
+
+/// Marks an upload as in flight; if the command is interrupted before this
+/// is dropped, the unconfirmed item is deleted
+pub struct PendingUpload(String);
+
+impl PendingUpload {
+    pub fn new(item_id: &str) -> Self {
+        pending().uploads.push(item_id.to_string());
+        Self(item_id.to_string())
+    }
+}
+
+impl Drop for PendingUpload {
+    fn drop(&mut self) {
+        pending().uploads.retain(|id| id != &self.0);
+    }
+}
+
+/// Handle Ctrl-C for the rest of the process: clean up, then exit with 130.
+///
+/// The listener runs as its own task, so it fires even while the command is
+/// blocked, and the process exits without waiting for the command. Ctrl-C
+/// is ignored while `Suspended`.
+pub fn install(opts: ClientOptions) {
+    tokio::spawn(async move {
+        while tokio::signal::ctrl_c().await.is_ok() {
+            if SUSPENDED.load(Ordering::SeqCst) {
+                continue;
+            }
+            eprintln!();
+            eprintln!("Interrupted");
+            let _ = tokio::time::timeout(CLEANUP_BUDGET, cleanup(&opts)).await;
+            std::process::exit(EXIT_INTERRUPTED);
+        }
+    });
+}
+
+/// Best-effort cleanup; every step ignores failures
+async fn cleanup(opts: &ClientOptions) {
+    let (temp_files, uploads, session) = {
+        let mut pending = pending();
+        (
+            std::mem::take(&mut pending.temp_files),
+            std::mem::take(&mut pending.uploads),
+            pending.session.take(),
+        )
+    };
+    for path in temp_files {
+        let _ = std::fs::remove_file(path);
+    }
+
+    // A session some earlier command opened belongs to the research that's
+    // still going on; only one opened by this command is closed
+    if uploads.is_empty() && session.is_none() {
+        return;
+    }
+
+    if let Some(ref session_id) = session {
+        ApiClient::delete_session_file(opts);
+        eprintln!("Closing session {}", session_id);
+    }
+    let Ok(client) = ApiClient::new(opts) else {
+        return;
+    };
+    if !uploads.is_empty() {
+        eprintln!("Removing unfinished upload(s): {}", uploads.join(", "));
+        let _ = client.delete_items(uploads).await;
+    }
+    if let Some(session_id) = session {
+        let _ = client.complete_session(&session_id, None).await;
+    }
+}
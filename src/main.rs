@@ -1,11 +1,23 @@
 mod api;
 mod commands;
 mod config;
+mod daemon;
+mod diff;
+mod interrupt;
+mod markdown;
 mod output;
+mod pager;
+mod prompt;
+mod read_cache;
+mod request_log;
+mod schema;
+mod spinner;
 
 use anyhow::Result;
-use clap::{Parser, Subcommand};
+use api::{ClientOptions, MetadataUpdate};
+use clap::{CommandFactory, Parser, Subcommand};
 use commands::{access, auth, items, sources};
+use output::{ColorChoice, OutputFormat};
 
 #[derive(Parser)]
 #[command(name = "ck")]
@@ -15,14 +27,104 @@ struct Cli {
     #[command(subcommand)]
     command: Commands,
 
-    /// Output in JSON format
-    #[arg(long, global = true)]
+    /// Output in JSON format (shorthand for --format json)
+    #[arg(long, global = true, conflicts_with = "format")]
     json: bool,
 
+    /// Output format
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Table)]
+    format: OutputFormat,
+
+    /// Write JSON on a single line instead of pretty-printing it
+    #[arg(long, global = true)]
+    compact: bool,
+
+    /// Never pipe long output through $PAGER (also: CANDLEKEEP_NO_PAGER=1)
+    #[arg(long, global = true)]
+    no_pager: bool,
+
+    /// When to color output (NO_COLOR=1 and --json always mean never)
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        value_name = "WHEN",
+        default_value_t = ColorChoice::Auto
+    )]
+    color: ColorChoice,
+
+    /// Same as --color never
+    #[arg(long, global = true, conflicts_with = "color")]
+    no_color: bool,
+
+    /// Never prompt, page, or animate; fail instead of waiting for input
+    /// (also: CANDLEKEEP_NON_INTERACTIVE=1)
+    #[arg(long, global = true)]
+    non_interactive: bool,
+
+    /// Assume "yes" for every confirmation prompt in this invocation
+    #[arg(long, short, global = true)]
+    yes: bool,
+
+    /// API base URL for this invocation (overrides CANDLEKEEP_API_URL and config)
+    #[arg(long, global = true)]
+    base_url: Option<String>,
+
+    /// API version to target, e.g. v2 (overrides [api] version in config)
+    #[arg(long, global = true)]
+    api_version: Option<String>,
+
+    /// Append a JSON line per API call to this file (also: CANDLEKEEP_LOG_FILE)
+    #[arg(long, global = true, value_name = "PATH")]
+    log_file: Option<std::path::PathBuf>,
+
+    /// Request timeout in seconds (overrides [api] timeout_secs in config)
+    #[arg(
+        long,
+        global = true,
+        value_name = "SECS",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    timeout: Option<u64>,
+
+    /// Seconds allowed to connect to the server (overrides [api] connect_timeout_secs)
+    #[arg(
+        long,
+        global = true,
+        value_name = "SECS",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    connect_timeout: Option<u64>,
+
+    /// Fail on API responses larger than this instead of reading them
+    /// (e.g. 50MB) [default: 256MB]
+    #[arg(long, global = true, value_name = "SIZE", value_parser = api::parse_max_response_size)]
+    max_response_size: Option<u64>,
+
+    /// Send this trace ID with every request instead of a random one
+    /// (printed with errors for support)
+    #[arg(long, global = true, value_name = "ID", value_parser = api::parse_trace_id)]
+    trace_id: Option<String>,
+
+    /// Use this saved profile's API key and URL instead of the active one
+    /// (see 'ck profiles'; 'ck auth login --profile NAME' creates one)
+    #[arg(long, global = true, value_name = "NAME", value_parser = config::parse_profile_name)]
+    profile: Option<String>,
+
+    /// Send API requests through a running 'ck daemon' to reuse its connections
+    /// (falls back to direct requests when none is running)
+    #[arg(long, global = true)]
+    daemon: bool,
+
     /// Use specific session ID (hidden, used by agents)
     #[arg(long, global = true, hide = true)]
     session: Option<String>,
 
+    /// Read and write the session ID in this file instead of
+    /// ~/.candlekeep/session (hidden, used by agent orchestrators)
+    #[arg(long, global = true, hide = true, value_name = "PATH")]
+    session_file: Option<std::path::PathBuf>,
+
     /// Disable session tracking (hidden, used by book-enricher)
     #[arg(long, global = true, hide = true)]
     no_session: bool,
@@ -45,6 +147,33 @@ enum Commands {
         #[command(subcommand)]
         command: SourcesCommands,
     },
+    /// View and change CLI configuration
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommands,
+    },
+    /// Manage saved profiles (API key and URL pairs)
+    Profiles {
+        #[command(subcommand)]
+        command: ProfilesCommands,
+    },
+    /// Show version information
+    Version {
+        /// Include git commit, build date, toolchain, target, and API URL
+        #[arg(long)]
+        full: bool,
+    },
+    /// Check your setup: config, API key, server connection, and clock
+    Doctor {
+        /// Print nothing (just {"ok": ...} with --json) and report through the exit code
+        #[arg(long, short)]
+        quiet: bool,
+    },
+    /// Keep API connections warm for commands run with --daemon
+    Daemon {
+        #[command(subcommand)]
+        command: DaemonCommands,
+    },
     /// Access session tracking (hidden, used by agents)
     #[command(hide = true)]
     Access {
@@ -59,38 +188,165 @@ enum AuthCommands {
     Login,
     /// Remove stored credentials
     Logout,
+    /// Rotate the stored API key (or revalidate it if the server can't rotate)
+    Refresh,
     /// Show current user information
-    Whoami,
+    Whoami {
+        /// Show the profile cached at the last fetch instead of calling the API
+        #[arg(long)]
+        cached: bool,
+        /// Print only this field's value, undecorated (fails if it isn't set)
+        #[arg(long, value_enum, value_name = "FIELD")]
+        raw: Option<auth::WhoamiField>,
+        /// Exit non-zero when the library is at its item limit (adds atLimit to JSON)
+        #[arg(long)]
+        check_limit: bool,
+        /// With --check-limit, fail once usage reaches this fraction of the limit (e.g. 0.9)
+        #[arg(
+            long,
+            requires = "check_limit",
+            value_name = "RATIO",
+            value_parser = items::parse_ratio
+        )]
+        threshold: Option<f64>,
+        /// Also describe this CLI: version, API URL, profile, config location,
+        /// and env overrides (a "client" object with --json; never the key)
+        #[arg(long, conflicts_with = "raw")]
+        client: bool,
+        /// Poll until the tier or item limit changes (e.g. after upgrading), then exit
+        #[arg(long, conflicts_with_all = ["cached", "raw", "check_limit", "client"])]
+        watch: bool,
+        /// Seconds between polls with --watch
+        #[arg(
+            long,
+            requires = "watch",
+            value_name = "SECS",
+            default_value_t = 5,
+            value_parser = clap::value_parser!(u64).range(1..)
+        )]
+        interval: u64,
+        /// With --watch, give up after this many seconds
+        #[arg(long, requires = "watch", value_name = "SECS")]
+        watch_timeout: Option<u64>,
+    },
+    /// Show whether you're logged in, using the cached profile
+    Status {
+        /// Fetch the profile live and refresh the cache
+        #[arg(long)]
+        refresh: bool,
+    },
 }
 
 #[derive(Subcommand)]
 enum ItemsCommands {
     /// List all items in your library
-    List,
+    List {
+        #[command(flatten)]
+        list: items::ListOptions,
+    },
     /// Show table of contents for items
     Toc {
         /// Comma-separated item IDs
         ids: String,
+        /// Only show entries up to this heading level (entries without a level count as top-level)
+        #[arg(long, value_parser = clap::value_parser!(i32).range(0..))]
+        depth: Option<i32>,
+        /// One line per entry with its page range and the matching `items read`
+        /// argument (pageStart/pageEnd with --json)
+        #[arg(long)]
+        flat: bool,
+        #[command(flatten)]
+        batch: items::BatchOptions,
     },
     /// Read content from items
+    #[command(
+        long_about = "Read content from items.\n\n\
+            Every ID must carry a page range after a colon; use 'all' for every page,\n\
+            or pass plain IDs with --chapter to read a section by its TOC title.",
+        after_help = items::read_after_help()
+    )]
     Read {
-        /// Item IDs with page ranges (e.g., "id:1-5,id2:all")
+        /// Item IDs with page ranges (e.g., "id:1-5,id2:all"); plain IDs with --chapter
         ids: String,
+        #[command(flatten)]
+        read: items::ReadOptions,
+        #[command(flatten)]
+        batch: items::BatchOptions,
+    },
+    /// Check that an item's extraction worked: status, pages with text, and TOC
+    /// (exits 1 if it looks broken)
+    Verify {
+        /// Item ID
+        id: String,
+        /// Share of pages (0-1) that must have text to pass
+        #[arg(
+            long,
+            value_name = "RATIO",
+            default_value_t = items::DEFAULT_MIN_CONTENT_RATIO,
+            value_parser = items::parse_ratio
+        )]
+        min_content_ratio: f64,
+        /// Print nothing (just {"ok": ...} with --json) and report through the exit code
+        #[arg(long, short)]
+        quiet: bool,
+    },
+    /// Read the last pages of an item (handy for growing documents)
+    Tail {
+        /// Item ID
+        id: String,
+        /// Number of pages from the end
+        #[arg(
+            long,
+            short = 'n',
+            default_value_t = 5,
+            value_parser = clap::value_parser!(u32).range(1..)
+        )]
+        pages: u32,
     },
-    /// Upload a PDF to your library
+    /// Upload a PDF, Markdown, or EPUB file to your library
+    #[command(after_help = "Examples:
+  ck items add ./paper.pdf
+  ck items add ./notes.md
+  ck items add --from-url https://example.com/paper.pdf")]
     Add {
-        /// Path to PDF file
-        file: String,
+        /// Paths to the files; glob patterns like "*.pdf" are expanded
+        #[arg(required_unless_present = "from_url", conflicts_with = "from_url")]
+        files: Vec<String>,
+        /// Download the document from a URL instead of reading a local file
+        #[arg(long, value_name = "URL")]
+        from_url: Option<String>,
+        #[command(flatten)]
+        add: items::AddOptions,
+    },
+    /// Export your library as a single JSON document
+    Export {
+        /// Write to this file instead of stdout
+        #[arg(long, short, conflicts_with = "output_dir")]
+        output: Option<String>,
+        /// Include document content (markdown bodies and extracted pages)
+        #[arg(long)]
+        include_content: bool,
+        #[command(flatten)]
+        files: commands::file_names::OutputDirOptions,
     },
     /// Remove items from your library
     Remove {
-        /// Comma-separated item IDs
-        ids: String,
-        /// Skip confirmation prompt
-        #[arg(long, short)]
-        yes: bool,
+        /// Comma-separated item IDs (omit to pick interactively)
+        ids: Option<String>,
+        /// Pick items to remove from a checkbox list
+        #[arg(long, short, conflicts_with = "ids")]
+        interactive: bool,
+        /// Exit non-zero if any ID isn't found or storage cleanup fails
+        #[arg(long)]
+        strict: bool,
     },
     /// Enrich item metadata (title, author, description, table of contents)
+    #[command(after_help = "Examples:
+  ck items enrich itm_abc --title \"Deep Learning\" --author \"Ian Goodfellow\"
+  ck items enrich itm_abc --confidence 0.9 \\
+    --toc '[{\"title\":\"Introduction\",\"page\":1,\"level\":1},{\"title\":\"Notation\",\"page\":3,\"level\":2}]'
+  ck items enrich itm_abc --merge-toc --toc '[{\"title\":\"Appendix\",\"page\":40,\"level\":1}]'
+  ck items enrich itm_abc --clear author,description")]
     Enrich {
         /// Item ID
         id: String,
@@ -109,6 +365,26 @@ enum ItemsCommands {
         /// Table of contents as JSON array: [{"title":"Chapter 1","page":1,"level":1}]
         #[arg(long)]
         toc: Option<String>,
+        /// Add the --toc entries to the existing TOC instead of replacing it
+        #[arg(long, requires = "toc")]
+        merge_toc: bool,
+        /// Comma-separated tags
+        #[arg(long, value_delimiter = ',')]
+        tags: Vec<String>,
+        /// Comma-separated fields to reset on the server
+        #[arg(long, value_enum, value_delimiter = ',', value_name = "FIELDS")]
+        clear: Vec<items::ClearField>,
+        /// Check the flags and --toc JSON, print "valid" or the problems, and
+        /// exit (1 if invalid) without calling the API
+        #[arg(long)]
+        validate_only: bool,
+    },
+    /// Change an item's title
+    Rename {
+        /// Item ID
+        id: String,
+        /// New title
+        title: String,
     },
     /// Flag item as needing metadata enrichment
     Flag {
@@ -125,11 +401,43 @@ enum ItemsCommands {
         /// Initial content
         #[arg(long, short)]
         content: Option<String>,
+        /// Comma-separated tags
+        #[arg(long, value_delimiter = ',')]
+        tags: Vec<String>,
+        /// What to do when an item with exactly this title (case-sensitive) exists
+        #[arg(long, value_enum, value_name = "POLICY", default_value_t = items::IfExists::Error)]
+        if_exists: items::IfExists,
     },
     /// Get full content of a document (outputs to stdout)
     Get {
         /// Item ID
         id: String,
+        /// Print character, word, page, and estimated token counts instead of content
+        #[arg(long, conflicts_with_all = ["line_ending", "bom"])]
+        stats: bool,
+        /// Render the markdown as plain text (headings, emphasis, links, and
+        /// code fences stripped); with --json, the document with a "plainText"
+        /// field next to the raw content
+        #[arg(long)]
+        strip_markdown: bool,
+        #[command(flatten)]
+        encoding: items::EncodingOptions,
+    },
+    /// Show how a local file differs from the stored document (exits 1 if they
+    /// differ, 2 on errors)
+    Diff {
+        /// Item ID
+        id: String,
+        /// Local file to compare against
+        file: String,
+    },
+    /// Reorder a document's pages without re-uploading it
+    Reorder {
+        /// Item ID
+        id: String,
+        /// Every current page number, in the new order (e.g. 3,1,2)
+        #[arg(long)]
+        order: String,
     },
     /// Replace document content (from file or stdin)
     Put {
@@ -139,23 +447,83 @@ enum ItemsCommands {
         #[arg(long, short)]
         file: Option<String>,
     },
+    /// Run enrich, create and remove commands listed in a file, one JSON object per line
+    #[command(after_help = "Each line is a JSON object with a \"cmd\" of enrich, create or remove;
+blank lines and lines starting with # are skipped.
+
+Examples:
+  {\"cmd\":\"enrich\",\"id\":\"itm_abc\",\"title\":\"Deep Learning\",\"author\":\"Ian Goodfellow\"}
+  {\"cmd\":\"create\",\"title\":\"Notes\",\"content\":\"# Notes\",\"tags\":[\"ml\"]}
+  {\"cmd\":\"remove\",\"ids\":[\"itm_old1\",\"itm_old2\"]}")]
+    Batch {
+        /// File of command specs ("-" for stdin)
+        file: String,
+        /// Run every command even after a failure, and exit zero
+        #[arg(long)]
+        continue_on_error: bool,
+    },
 }
 
 #[derive(Subcommand)]
 enum SourcesCommands {
     /// List saved sources
     List {
-        /// Maximum number of sources to return
+        #[command(flatten)]
+        list: sources::ListOptions,
+    },
+    /// Export all sources as JSON lines
+    Export {
+        /// Write to this file instead of stdout
+        #[arg(long, short)]
+        output: Option<String>,
+        /// Write a single JSON array instead of one object per line
+        #[arg(long)]
+        array: bool,
+    },
+    /// Save a source from a URL or text
+    Add {
+        /// Where the source lives
+        #[arg(long, required_unless_present = "content")]
+        url: Option<String>,
+        /// The source's text (e.g. a quote or post body)
         #[arg(long)]
-        limit: Option<u32>,
+        content: Option<String>,
+        /// Author: @handle or name
+        #[arg(long)]
+        author: Option<String>,
     },
     /// Delete sources
     Delete {
         /// Comma-separated source IDs
-        ids: String,
-        /// Skip confirmation prompt
-        #[arg(long, short)]
-        yes: bool,
+        #[arg(required_unless_present = "all", conflicts_with = "all")]
+        ids: Option<String>,
+        /// Delete every source; asks you to type the count, and --yes doesn't skip that
+        #[arg(long)]
+        all: bool,
+        /// With --all, skip the typed confirmation (required when not prompting)
+        #[arg(long, conflicts_with = "ids")]
+        yes_i_mean_it: bool,
+        /// Exit non-zero if any ID isn't found
+        #[arg(long)]
+        strict: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigCommands {
+    /// Show the effective configuration (API key redacted)
+    Show,
+    /// Print the config and session file locations
+    Path,
+    /// Open the config file in $VISUAL or $EDITOR and check it on save
+    Edit,
+    /// Set a configuration value
+    Set {
+        /// Dotted key: api.url, api.timeout_secs, api.connect_timeout_secs,
+        /// api.version, auth.api_key, log.max_bytes, upload.max_size
+        key: String,
+        /// New value
+        value: String,
     },
 }
 
@@ -168,25 +536,179 @@ enum AccessCommands {
         intent: Option<String>,
     },
     /// Complete the current research session
-    Complete,
+    Complete {
+        /// What the research found, recorded with the session ("-" reads stdin)
+        #[arg(long, conflicts_with = "summary_file")]
+        summary: Option<String>,
+        /// Read the summary from this file
+        #[arg(long, value_name = "PATH")]
+        summary_file: Option<std::path::PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum DaemonCommands {
+    /// Run the daemon in the foreground on ~/.candlekeep/daemon.sock
+    Start {
+        /// Exit after this many seconds without a connected client (0: never)
+        #[arg(long, value_name = "SECS", default_value_t = daemon::DEFAULT_IDLE_TIMEOUT_SECS)]
+        idle_timeout: u64,
+    },
+    /// Check whether the daemon is running
+    Status,
+    /// Ask the daemon to exit
+    Stop,
+}
+
+#[derive(Subcommand)]
+enum ProfilesCommands {
+    /// List profiles with their URLs and redacted keys; * marks the one in use
+    List,
+    /// Make a profile active ("default" for the top-level settings)
+    Use {
+        /// Profile name
+        name: String,
+    },
+    /// Delete a profile and its saved key
+    Remove {
+        /// Profile name
+        name: String,
+    },
+    /// Rename a profile
+    Rename {
+        /// Current name
+        old: String,
+        /// New name
+        new: String,
+    },
 }
 
+/// Hidden flag that prints the command tree as JSON for wrappers; handled
+/// before parsing so it works without a subcommand. Only recognized as the
+/// first argument, so an option value that happens to match still works.
+const HELP_JSON_FLAG: &str = "--help-json";
+
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() {
+    if std::env::args_os().nth(1).is_some_and(|a| a == HELP_JSON_FLAG) {
+        let schema = schema::schema(&Cli::command());
+        if let Err(e) = output::print_serialized(&schema, OutputFormat::Json) {
+            eprintln!("Error: {:?}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
     let cli = Cli::parse();
+    let trace_id = cli.trace_id.clone().unwrap_or_else(api::new_trace_id);
+
+    if let Err(e) = run(cli, &trace_id).await {
+        print_error(&e, &trace_id);
+        std::process::exit(1);
+    }
+}
+
+/// Report a failed command on stderr, with the trace ID for support
+fn print_error(e: &anyhow::Error, trace_id: &str) {
+    eprintln!("Error: {:?}", e);
+    eprintln!("trace: {}", trace_id);
+}
+
+async fn run(cli: Cli, trace_id: &str) -> Result<()> {
+    if let Some(ref version) = cli.api_version {
+        config::validate_api_version(version)?;
+    }
+    if let Some(ref profile) = cli.profile {
+        config::set_profile(profile);
+    }
+    let opts = ClientOptions {
+        session: cli.session.clone(),
+        no_session: cli.no_session,
+        session_file: cli.session_file.clone(),
+        base_url: cli.base_url.clone(),
+        api_version: cli.api_version.clone(),
+        timeout_secs: cli.timeout,
+        connect_timeout_secs: cli.connect_timeout,
+        trace_id: Some(trace_id.to_string()),
+        daemon: cli.daemon,
+        max_response_size: cli.max_response_size,
+    };
+    let format = if cli.json { OutputFormat::Json } else { cli.format };
+    if cli.compact {
+        output::set_compact_json();
+    }
+    if cli.no_pager {
+        pager::disable();
+    }
+    if cli.non_interactive {
+        prompt::set_non_interactive();
+    }
+    output::set_color(if cli.no_color { ColorChoice::Never } else { cli.color }, format);
+    request_log::init(cli.log_file.clone());
+    // The daemon stops on Ctrl-C by itself; session cleanup would end the
+    // research session of whichever agent is using it
+    if !matches!(cli.command, Commands::Daemon { command: DaemonCommands::Start { .. } }) {
+        interrupt::install(opts.clone());
+    }
 
     match cli.command {
         Commands::Auth { command } => match command {
-            AuthCommands::Login => auth::login(cli.session.clone(), cli.no_session).await?,
+            AuthCommands::Login => auth::login(&opts).await?,
             AuthCommands::Logout => auth::logout()?,
-            AuthCommands::Whoami => auth::whoami(cli.json, cli.session.clone(), cli.no_session).await?,
+            AuthCommands::Refresh => auth::refresh(&opts).await?,
+            AuthCommands::Whoami {
+                watch: true,
+                interval,
+                watch_timeout,
+                ..
+            } => {
+                let interval = std::time::Duration::from_secs(interval);
+                let timeout = watch_timeout.map(std::time::Duration::from_secs);
+                auth::watch(interval, timeout, format, &opts).await?
+            }
+            AuthCommands::Whoami {
+                cached,
+                raw,
+                check_limit,
+                threshold,
+                client,
+                ..
+            } => {
+                let check = check_limit.then_some(auth::LimitCheck { threshold });
+                auth::whoami(cached, raw, check, client, format, &opts).await?
+            }
+            AuthCommands::Status { refresh } => auth::status(refresh, format, &opts).await?,
         },
         Commands::Items { command } => match command {
-            ItemsCommands::List => items::list(cli.json, cli.session.clone(), cli.no_session).await?,
-            ItemsCommands::Toc { ids } => items::toc(&ids, cli.json, cli.session.clone(), cli.no_session).await?,
-            ItemsCommands::Read { ids } => items::read(&ids, cli.json, cli.session.clone(), cli.no_session).await?,
-            ItemsCommands::Add { file } => items::add(&file, cli.session.clone(), cli.no_session).await?,
-            ItemsCommands::Remove { ids, yes } => items::remove(&ids, yes, cli.session.clone(), cli.no_session).await?,
+            ItemsCommands::List { list } => items::list(&list, format, &opts).await?,
+            ItemsCommands::Toc {
+                ids,
+                depth,
+                flat,
+                batch,
+            } => items::toc(&ids, depth, flat, batch, format, &opts).await?,
+            ItemsCommands::Read { ids, read, batch } => {
+                items::read(&ids, read, batch, format, &opts).await?
+            }
+            ItemsCommands::Verify { id, min_content_ratio, quiet } => {
+                if !items::verify(&id, min_content_ratio, quiet, format, &opts).await? {
+                    std::process::exit(1);
+                }
+            }
+            ItemsCommands::Tail { id, pages } => items::tail(&id, pages, format, &opts).await?,
+            ItemsCommands::Add {
+                files,
+                from_url,
+                add,
+            } => items::add(&files, from_url.as_deref(), &add, format, &opts).await?,
+            ItemsCommands::Export { output, include_content, files } => {
+                items::export(output.as_deref(), include_content, &files, format, &opts).await?
+            }
+            ItemsCommands::Remove {
+                ids,
+                interactive,
+                strict,
+            } => items::remove(ids.as_deref(), interactive, cli.yes, strict, format, &opts).await?,
             ItemsCommands::Enrich {
                 id,
                 title,
@@ -194,40 +716,120 @@ async fn main() -> Result<()> {
                 description,
                 confidence,
                 toc,
+                merge_toc,
+                tags,
+                clear,
+                validate_only,
             } => {
-                items::enrich(
-                    &id,
-                    title.as_deref(),
-                    author.as_deref(),
-                    description.as_deref(),
+                let update = MetadataUpdate {
+                    title,
+                    author: author.map(Some),
+                    description: description.map(Some),
                     confidence,
-                    toc.as_deref(),
-                    cli.session.clone(),
-                    cli.no_session,
-                )
-                .await?
+                    toc: None,
+                    tags: items::parse_tags(tags),
+                };
+                if validate_only {
+                    if !items::validate_enrichment(&update, toc.as_deref(), &clear, format)? {
+                        std::process::exit(1);
+                    }
+                } else {
+                    items::enrich(&id, update, toc.as_deref(), merge_toc, &clear, &opts).await?
+                }
             }
-            ItemsCommands::Flag { id } => items::flag(&id, cli.session.clone(), cli.no_session).await?,
+            ItemsCommands::Rename { id, title } => items::rename(&id, &title, format, &opts).await?,
+            ItemsCommands::Flag { id } => items::flag(&id, &opts).await?,
             ItemsCommands::Create {
                 title,
                 description,
                 content,
+                tags,
+                if_exists,
             } => {
-                items::create(&title, description.as_deref(), content.as_deref(), cli.json, cli.session.clone(), cli.no_session).await?
+                let tags = items::parse_tags(tags).unwrap_or_default();
+                items::create(
+                    &title,
+                    description.as_deref(),
+                    content.as_deref(),
+                    &tags,
+                    if_exists,
+                    format,
+                    &opts,
+                )
+                .await?
+            }
+            ItemsCommands::Get {
+                id,
+                stats,
+                strip_markdown,
+                encoding,
+            } => items::get(&id, stats, strip_markdown, &encoding, format, &opts).await?,
+            // Like diff(1): 1 when they differ, 2 when the comparison failed
+            ItemsCommands::Diff { id, file } => match items::diff(&id, &file, format, &opts).await {
+                Ok(true) => {}
+                Ok(false) => std::process::exit(1),
+                Err(e) => {
+                    print_error(&e, trace_id);
+                    std::process::exit(2);
+                }
+            },
+            ItemsCommands::Reorder { id, order } => items::reorder(&id, &order, format, &opts).await?,
+            ItemsCommands::Put { id, file } => items::put(&id, file.as_deref(), &opts).await?,
+            ItemsCommands::Batch { file, continue_on_error } => {
+                if !items::batch(&file, continue_on_error, cli.yes, format, &opts).await? {
+                    std::process::exit(1);
+                }
             }
-            ItemsCommands::Get { id } => items::get(&id, cli.session.clone(), cli.no_session).await?,
-            ItemsCommands::Put { id, file } => items::put(&id, file.as_deref(), cli.session.clone(), cli.no_session).await?,
         },
         Commands::Sources { command } => match command {
-            SourcesCommands::List { limit } => sources::list(cli.json, limit, cli.session.clone(), cli.no_session).await?,
-            SourcesCommands::Delete { ids, yes } => sources::delete(&ids, yes, cli.session.clone(), cli.no_session).await?,
+            SourcesCommands::List { list } => sources::list(&list, format, &opts).await?,
+            SourcesCommands::Export { output, array } => {
+                sources::export(output.as_deref(), array, &opts).await?
+            }
+            SourcesCommands::Add { url, content, author } => {
+                sources::add(url.as_deref(), content.as_deref(), author.as_deref(), format, &opts)
+                    .await?
+            }
+            SourcesCommands::Delete { ids, all, yes_i_mean_it, strict } => {
+                if all {
+                    sources::delete_all(yes_i_mean_it, strict, format, &opts).await?
+                } else {
+                    sources::delete(ids.as_deref().unwrap_or_default(), cli.yes, strict, &opts)
+                        .await?
+                }
+            }
+        },
+        Commands::Config { command } => match command {
+            ConfigCommands::Show => commands::config::show(format)?,
+            ConfigCommands::Path => commands::config::path(format, &opts)?,
+            ConfigCommands::Edit => commands::config::edit()?,
+            ConfigCommands::Set { key, value } => commands::config::set(&key, &value)?,
         },
+        Commands::Profiles { command } => match command {
+            ProfilesCommands::List => commands::profiles::list(format)?,
+            ProfilesCommands::Use { name } => commands::profiles::use_profile(&name)?,
+            ProfilesCommands::Remove { name } => commands::profiles::remove(&name, cli.yes)?,
+            ProfilesCommands::Rename { old, new } => commands::profiles::rename(&old, &new)?,
+        },
+        Commands::Version { full } => commands::version::show(full, format, &opts)?,
+        Commands::Daemon { command } => match command {
+            DaemonCommands::Start { idle_timeout } => {
+                commands::daemon::start(idle_timeout, &opts).await?
+            }
+            DaemonCommands::Status => commands::daemon::status(format).await?,
+            DaemonCommands::Stop => commands::daemon::stop().await?,
+        },
+        Commands::Doctor { quiet } => {
+            if !commands::doctor::run(format, quiet, &opts).await? {
+                std::process::exit(1);
+            }
+        }
         Commands::Access { command } => match command {
             AccessCommands::Start { intent } => {
-                access::start(intent.as_deref(), cli.json, cli.session.clone(), cli.no_session).await?
+                access::start(intent.as_deref(), format, &opts).await?
             }
-            AccessCommands::Complete => {
-                access::complete(cli.json, cli.session.clone(), cli.no_session).await?
+            AccessCommands::Complete { summary, summary_file } => {
+                access::complete(summary.as_deref(), summary_file.as_deref(), format, &opts).await?
             }
         },
     }
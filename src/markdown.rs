@@ -0,0 +1,111 @@
+use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
+
+/// End the current line, unless the output is empty or already at one
+fn end_line(out: &mut String) {
+    if !out.is_empty() && !out.ends_with('\n') {
+        out.push('\n');
+    }
+}
+
+/// End the current block with a blank line
+fn end_block(out: &mut String) {
+    end_line(out);
+    if !out.is_empty() && !out.ends_with("\n\n") {
+        out.push('\n');
+    }
+}
+
+/// Render markdown as plain prose: heading markers, emphasis, code fences,
+/// and HTML are dropped, links become their text and images their alt text.
+/// List markers are kept so items stay apart; table cells are tab-separated.
+pub fn to_plain_text(markdown: &str) -> String {
+    let options =
+        Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+    let mut out = String::new();
+    // One entry per open list: the next number for ordered lists
+    let mut lists: Vec<Option<u64>> = Vec::new();
+
+    for event in Parser::new_ext(markdown, options) {
+        match event {
+            Event::Start(Tag::List(start)) => {
+                end_line(&mut out);
+                lists.push(start);
+            }
+            Event::Start(Tag::Item) => {
+                end_line(&mut out);
+                out.push_str(&"  ".repeat(lists.len().saturating_sub(1)));
+                match lists.last_mut() {
+                    Some(Some(n)) => {
+                        out.push_str(&format!("{}. ", n));
+                        *n += 1;
+                    }
+                    _ => out.push_str("- "),
+                }
+            }
+            Event::Start(Tag::CodeBlock(_)) => end_line(&mut out),
+            Event::End(TagEnd::List(_)) => {
+                lists.pop();
+                if lists.is_empty() {
+                    end_block(&mut out);
+                } else {
+                    end_line(&mut out);
+                }
+            }
+            Event::End(TagEnd::Item) => end_line(&mut out),
+            // Paragraphs inside list items stay on their own lines
+            Event::End(TagEnd::Paragraph | TagEnd::CodeBlock) if !lists.is_empty() => {
+                end_line(&mut out)
+            }
+            Event::End(
+                TagEnd::Paragraph
+                | TagEnd::Heading(_)
+                | TagEnd::CodeBlock
+                | TagEnd::BlockQuote(_)
+                | TagEnd::Table,
+            ) => end_block(&mut out),
+            Event::End(TagEnd::TableCell) => out.push('\t'),
+            Event::End(TagEnd::TableHead | TagEnd::TableRow) => {
+                let trimmed = out.trim_end_matches('\t').len();
+                out.truncate(trimmed);
+                out.push('\n');
+            }
+            Event::Text(text) | Event::Code(text) => out.push_str(&text),
+            Event::InlineMath(text) | Event::DisplayMath(text) => out.push_str(&text),
+            Event::SoftBreak => out.push(' '),
+            Event::HardBreak => out.push('\n'),
+            Event::Rule => end_block(&mut out),
+            Event::TaskListMarker(checked) => out.push_str(if checked { "[x] " } else { "[ ] " }),
+            Event::FootnoteReference(label) => out.push_str(&format!("[{}]", label)),
+            _ => {}
+        }
+    }
+
+    out.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_plain_text_strips_syntax() {
+        let markdown = "# Title\n\nSome *emphasis* and **bold**, a [link](https://x.test), \
+                        and `code`.\nSame paragraph.\n\n```rust\nfn main() {}\n```\n\n\
+                        ![A diagram](fig.png)\n\n<div>html</div>\n\n---\n\n> Quoted";
+        assert_eq!(
+            to_plain_text(markdown),
+            "Title\n\nSome emphasis and bold, a link, and code. Same paragraph.\n\n\
+             fn main() {}\n\nA diagram\n\nQuoted"
+        );
+    }
+
+    #[test]
+    fn test_to_plain_text_lists_and_tables() {
+        let markdown =
+            "- one\n- two\n  1. first\n  2. second\n\n| A | B |\n|---|---|\n| 1 | 2 |\n\nEnd";
+        assert_eq!(
+            to_plain_text(markdown),
+            "- one\n- two\n  1. first\n  2. second\n\nA\tB\n1\t2\n\nEnd"
+        );
+    }
+}
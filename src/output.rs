@@ -1,9 +1,322 @@
 #![allow(dead_code)]
 
+use anyhow::Result;
+use clap::ValueEnum;
 use colored::Colorize;
 use comfy_table::{presets::UTF8_FULL, Cell, Color, ContentArrangement, Table};
+use regex::Regex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::{self, IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use unicode_segmentation::UnicodeSegmentation;
 
-use crate::api::{EnrichmentQueueItem, Item, ItemsResponse, ItemWithPages, ItemWithToc, Source, SourcesResponse, TocEntry, WhoamiResponse};
+use crate::api::{EnrichmentQueueItem, Item, ItemWithPages, ItemWithToc, Source, TocEntry, WhoamiResponse};
+use crate::config::{ClientSummary, EffectiveValue, ProfileSummary, ValueSource};
+use crate::diff::DocumentDiff;
+use crate::pager;
+
+/// `println!` into a `String` buffer; writing to a `String` can't fail
+macro_rules! outln {
+    ($out:expr) => {
+        $out.push('\n')
+    };
+    ($out:expr, $($arg:tt)*) => {{
+        use std::fmt::Write as _;
+        let _ = writeln!($out, $($arg)*);
+    }};
+}
+
+/// How command results are written to stdout
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable tables and text
+    #[default]
+    Table,
+    /// Pretty-printed JSON
+    Json,
+    /// YAML
+    Yaml,
+}
+
+impl OutputFormat {
+    /// True for the machine-readable formats (JSON, YAML)
+    pub fn is_structured(self) -> bool {
+        self != OutputFormat::Table
+    }
+}
+
+/// When to use ANSI colors (`--color`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum ColorChoice {
+    /// Color when writing to a terminal
+    #[default]
+    Auto,
+    /// Color even when piped (e.g. into `less -R`)
+    Always,
+    /// Never color
+    Never,
+}
+
+/// Apply `--color` for the rest of the process: text, tables, and progress
+/// bars. `NO_COLOR` and the structured formats always win and turn colors off.
+pub fn set_color(choice: ColorChoice, format: OutputFormat) {
+    let no_color_env = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+    let enabled = match choice {
+        _ if no_color_env || format.is_structured() => false,
+        ColorChoice::Auto => return,
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+    };
+    colored::control::set_override(enabled);
+    console::set_colors_enabled(enabled);
+    console::set_colors_enabled_stderr(enabled);
+}
+
+/// Set by `--compact`
+static COMPACT_JSON: AtomicBool = AtomicBool::new(false);
+
+/// Write JSON on a single line for the rest of this process
+pub fn set_compact_json() {
+    COMPACT_JSON.store(true, Ordering::Relaxed);
+}
+
+/// Serialize `value` as JSON: pretty-printed, or one line with `--compact`
+pub fn to_json<T: Serialize + ?Sized>(value: &T) -> Result<String> {
+    if COMPACT_JSON.load(Ordering::Relaxed) {
+        Ok(serde_json::to_string(value)?)
+    } else {
+        Ok(serde_json::to_string_pretty(value)?)
+    }
+}
+
+/// `to_json` straight into `writer`, for output too large to build in memory
+pub fn write_json<W: std::io::Write, T: Serialize + ?Sized>(writer: W, value: &T) -> Result<()> {
+    if COMPACT_JSON.load(Ordering::Relaxed) {
+        serde_json::to_writer(writer, value)?;
+    } else {
+        serde_json::to_writer_pretty(writer, value)?;
+    }
+    Ok(())
+}
+
+/// Print any serializable value in a structured `format`.
+/// `Table` has no generic rendering, so it falls back to JSON.
+pub fn print_serialized<T: Serialize + ?Sized>(value: &T, format: OutputFormat) -> Result<()> {
+    match format {
+        OutputFormat::Yaml => print!("{}", serde_yaml::to_string(value)?),
+        OutputFormat::Json | OutputFormat::Table => println!("{}", to_json(value)?),
+    }
+    Ok(())
+}
+
+/// Print one record of an event stream: a single JSON line, or a `---`
+/// separated YAML document
+pub fn print_event<T: Serialize + ?Sized>(value: &T, format: OutputFormat) -> Result<()> {
+    match format {
+        OutputFormat::Yaml => print!("---\n{}", serde_yaml::to_string(value)?),
+        OutputFormat::Json | OutputFormat::Table => println!("{}", serde_json::to_string(value)?),
+    }
+    io::Write::flush(&mut io::stdout())?;
+    Ok(())
+}
+
+/// Rule width used when stdout is not a terminal, so piped output is stable
+const DEFAULT_RULE_WIDTH: usize = 60;
+/// Upper bound on rule width for very wide terminals
+const MAX_RULE_WIDTH: usize = 100;
+
+/// Width for horizontal rules: the terminal width clamped to a max, or a
+/// fixed default when stdout is not a terminal
+fn rule_width() -> usize {
+    if !io::stdout().is_terminal() {
+        return DEFAULT_RULE_WIDTH;
+    }
+
+    terminal_size::terminal_size()
+        .map(|(terminal_size::Width(w), _)| (w as usize).min(MAX_RULE_WIDTH))
+        .unwrap_or(DEFAULT_RULE_WIDTH)
+}
+
+/// Horizontal rule made of `ch`, sized by `rule_width`
+fn rule(ch: &str) -> String {
+    ch.repeat(rule_width())
+}
+
+/// Truncate `text` to at most `max` graphemes, ending with `…` when cut.
+/// Never splits a multibyte character.
+pub fn truncate_display(text: &str, max: usize) -> String {
+    if text.graphemes(true).count() <= max {
+        return text.to_string();
+    }
+
+    let kept: String = text.graphemes(true).take(max.saturating_sub(1)).collect();
+    format!("{}…", kept)
+}
+
+/// The `YYYY-MM-DD` prefix of an ISO 8601 timestamp, or "-" if the value
+/// doesn't start with a date
+pub fn short_date(timestamp: &str) -> &str {
+    match timestamp.get(..10) {
+        Some(date)
+            if date
+                .bytes()
+                .enumerate()
+                .all(|(i, b)| if i == 4 || i == 7 { b == b'-' } else { b.is_ascii_digit() }) =>
+        {
+            date
+        }
+        _ => "-",
+    }
+}
+
+/// Expand `\n`, `\t`, `\f` and `\\` in a user-supplied separator or
+/// template; other backslashes are kept as-is
+pub fn unescape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some('f') => out.push('\x0c'),
+            Some('\\') => out.push('\\'),
+            Some(other) => {
+                out.push('\\');
+                out.push(other);
+            }
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+/// Line endings for `--line-ending`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum LineEnding {
+    Lf,
+    Crlf,
+}
+
+/// How raw document text is rewritten before it's written out.
+/// The default leaves it exactly as received.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TextEncoding {
+    pub line_ending: Option<LineEnding>,
+    /// Start the output with a UTF-8 byte order mark
+    pub bom: bool,
+}
+
+impl TextEncoding {
+    pub fn apply(&self, text: &str) -> String {
+        let mut out = String::with_capacity(text.len() + 3);
+        if self.bom && !text.starts_with('\u{feff}') {
+            out.push('\u{feff}');
+        }
+        match self.line_ending {
+            None => out.push_str(text),
+            Some(ending) => {
+                let newline = match ending {
+                    LineEnding::Lf => "\n",
+                    LineEnding::Crlf => "\r\n",
+                };
+                // Normalize first so existing CRLFs aren't doubled
+                for (i, line) in text.split('\n').enumerate() {
+                    if i > 0 {
+                        out.push_str(newline);
+                    }
+                    out.push_str(line.strip_suffix('\r').unwrap_or(line));
+                }
+            }
+        }
+        out
+    }
+}
+
+/// A `--template` string: literal text with `{field}` placeholders, rendered
+/// once per row. `{{` and `}}` give literal braces; escapes follow [`unescape`].
+#[derive(Debug)]
+pub struct Template {
+    parts: Vec<TemplatePart>,
+}
+
+#[derive(Debug, PartialEq)]
+enum TemplatePart {
+    Text(String),
+    Field(String),
+}
+
+impl Template {
+    /// Parse `template`, rejecting placeholders that aren't in `fields`
+    pub fn parse(template: &str, fields: &[&str]) -> Result<Self> {
+        let template = unescape(template);
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        let mut chars = template.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' | '}' if chars.peek() == Some(&c) => {
+                    chars.next();
+                    text.push(c);
+                }
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => name.push(c),
+                            None => return Err(anyhow::anyhow!("Unclosed '{{' in template")),
+                        }
+                    }
+                    let name = name.trim();
+                    if !fields.contains(&name) {
+                        return Err(anyhow::anyhow!(
+                            "Unknown template field '{{{}}}'; valid fields: {}",
+                            name,
+                            fields.join(", ")
+                        ));
+                    }
+                    if !text.is_empty() {
+                        parts.push(TemplatePart::Text(std::mem::take(&mut text)));
+                    }
+                    parts.push(TemplatePart::Field(name.to_string()));
+                }
+                '}' => return Err(anyhow::anyhow!("Unmatched '}}' in template (use '}}}}')")),
+                c => text.push(c),
+            }
+        }
+        if !text.is_empty() {
+            parts.push(TemplatePart::Text(text));
+        }
+
+        Ok(Self { parts })
+    }
+
+    /// Fill in the placeholders, looking each field up with `value`
+    pub fn render(&self, value: impl Fn(&str) -> String) -> String {
+        self.parts
+            .iter()
+            .map(|part| match part {
+                TemplatePart::Text(text) => text.clone(),
+                TemplatePart::Field(name) => value(name),
+            })
+            .collect()
+    }
+}
+
+/// Item statuses with what each means, in lifecycle order; `status_color`
+/// gives their colors
+pub const ITEM_STATUSES: &[(&str, &str)] = &[
+    ("DRAFT", "upload not confirmed"),
+    ("PROCESSING", "extracting pages"),
+    ("READY", "ready to read"),
+    ("FAILED", "processing failed"),
+];
 
 /// Status color mapping for Item.status field
 fn status_color(status: &str) -> Color {
@@ -18,21 +331,33 @@ fn status_color(status: &str) -> Color {
 
 /// Format item status with color
 pub fn format_status(status: &str) -> String {
-    match status.to_uppercase().as_str() {
-        "READY" => status.green().to_string(),
-        "DRAFT" => status.yellow().to_string(),
-        "PROCESSING" => status.cyan().to_string(),
-        "FAILED" => status.red().to_string(),
-        _ => status.to_string(),
-    }
+    paint(status, status_color(status)).to_string()
 }
 
-/// Print user info as table
-pub fn print_whoami(info: &WhoamiResponse) {
+/// Whether ANSI colors are on for this process (see [`set_color`]; in auto
+/// mode, off when stdout isn't a terminal)
+pub fn colors_enabled() -> bool {
+    colored::control::SHOULD_COLORIZE.should_colorize()
+}
+
+/// A table in the standard style; cell colors follow [`colors_enabled`]
+fn new_table() -> Table {
     let mut table = Table::new();
     table
         .load_preset(UTF8_FULL)
         .set_content_arrangement(ContentArrangement::Dynamic);
+    if !colors_enabled() {
+        table.force_no_tty();
+    } else {
+        // Colors may be forced on while piped (`--color always`)
+        table.enforce_styling();
+    }
+    table
+}
+
+/// Print user info as table
+pub fn print_whoami(info: &WhoamiResponse) {
+    let mut table = new_table();
 
     table.add_row(vec!["Email", &info.email]);
     if let Some(ref name) = info.name {
@@ -48,180 +373,716 @@ pub fn print_whoami(info: &WhoamiResponse) {
     println!("{table}");
 }
 
-/// Print user info as JSON
-pub fn print_whoami_json(info: &WhoamiResponse) {
-    println!("{}", serde_json::to_string_pretty(info).unwrap());
+/// The `auth whoami --client` table
+pub fn print_client_summary(client: &ClientSummary) {
+    let mut table = new_table();
+
+    table.add_row(vec!["CLI version", client.cli_version]);
+    table.add_row(vec!["API URL", &client.api_url]);
+    table.add_row(vec!["API version", &client.api_version]);
+    table.add_row(vec!["Profile", &client.profile]);
+    table.add_row(vec!["Config", &format!("{} ({})", client.config_path, client.config_storage)]);
+    let overrides = if client.env_overrides.is_empty() {
+        "none".to_string()
+    } else {
+        client.env_overrides.join(", ")
+    };
+    table.add_row(vec!["Env overrides", &overrides]);
+
+    println!("{table}");
 }
 
-/// Print items as table
-pub fn print_items_table(items: &[Item], enrichment_queue: &Option<Vec<EnrichmentQueueItem>>) {
-    if items.is_empty() {
-        println!("{}", "No items found.".dimmed());
-        return;
+/// A column `print_items_table` can show
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ItemField {
+    Id,
+    Title,
+    Author,
+    Description,
+    Type,
+    Tags,
+    Pages,
+    Status,
+    Enrich,
+    Created,
+    Updated,
+}
+
+/// Columns shown when `--fields` isn't given
+pub const DEFAULT_ITEM_FIELDS: &[ItemField] = &[
+    ItemField::Id,
+    ItemField::Title,
+    ItemField::Pages,
+    ItemField::Status,
+    ItemField::Enrich,
+];
+
+impl ItemField {
+    fn header(self) -> &'static str {
+        match self {
+            ItemField::Id => "ID",
+            ItemField::Title => "Title",
+            ItemField::Author => "Author",
+            ItemField::Description => "Description",
+            ItemField::Type => "Type",
+            ItemField::Tags => "Tags",
+            ItemField::Pages => "Pages",
+            ItemField::Status => "Status",
+            ItemField::Enrich => "Enrich",
+            ItemField::Created => "Created",
+            ItemField::Updated => "Updated",
+        }
     }
 
-    let mut table = Table::new();
-    table
-        .load_preset(UTF8_FULL)
-        .set_content_arrangement(ContentArrangement::Dynamic)
-        .set_header(vec![
-            Cell::new("ID").fg(Color::Cyan),
-            Cell::new("Title").fg(Color::Cyan),
-            Cell::new("Pages").fg(Color::Cyan),
-            Cell::new("Status").fg(Color::Cyan),
-            Cell::new("Enrich").fg(Color::Cyan),
-        ]);
+    /// Names accepted by `--fields`, which are also the `--template` placeholders
+    pub fn names() -> Vec<&'static str> {
+        ItemField::value_variants()
+            .iter()
+            .map(|field| field.name())
+            .collect()
+    }
 
-    for item in items {
-        let status = &item.status;
-        let status_cell = Cell::new(status).fg(status_color(status));
+    fn name(self) -> &'static str {
+        match self {
+            ItemField::Id => "id",
+            ItemField::Title => "title",
+            ItemField::Author => "author",
+            ItemField::Description => "description",
+            ItemField::Type => "type",
+            ItemField::Tags => "tags",
+            ItemField::Pages => "pages",
+            ItemField::Status => "status",
+            ItemField::Enrich => "enrich",
+            ItemField::Created => "created",
+            ItemField::Updated => "updated",
+        }
+    }
 
-        let enrich_status = if item.needs_enrichment.unwrap_or(false) {
-            Cell::new("⚠").fg(Color::Yellow)
+    /// The field as plain text, untruncated; missing values are "-"
+    pub fn text(self, item: &Item) -> String {
+        match self {
+            ItemField::Id => item.id.clone(),
+            ItemField::Title => item.title.clone(),
+            ItemField::Author => item.author.clone().unwrap_or_else(|| "-".to_string()),
+            ItemField::Description => {
+                item.description.clone().unwrap_or_else(|| "-".to_string())
+            }
+            ItemField::Type => item.source_type.clone(),
+            ItemField::Tags if item.tags.is_empty() => "-".to_string(),
+            ItemField::Tags => item.tags.join(", "),
+            ItemField::Pages => item.page_count.to_string(),
+            ItemField::Status => item.status.clone(),
+            ItemField::Enrich => EnrichState::of(item).symbol().to_string(),
+            ItemField::Created => short_date(&item.created_at).to_string(),
+            ItemField::Updated => short_date(&item.updated_at).to_string(),
+        }
+    }
+
+    fn cell(self, item: &Item, title_width: usize) -> Cell {
+        match self {
+            ItemField::Title | ItemField::Description => {
+                Cell::new(truncate_display(&self.text(item), title_width))
+            }
+            ItemField::Status => Cell::new(&item.status).fg(status_color(&item.status)),
+            ItemField::Enrich => {
+                let state = EnrichState::of(item);
+                Cell::new(state.symbol()).fg(state.color())
+            }
+            _ => Cell::new(self.text(item)),
+        }
+    }
+}
+
+/// What the Enrich column shows for an item
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EnrichState {
+    Needed,
+    Enriched,
+    None,
+}
+
+impl EnrichState {
+    /// Every state, in legend order
+    const ALL: [EnrichState; 3] = [EnrichState::Needed, EnrichState::Enriched, EnrichState::None];
+
+    fn of(item: &Item) -> Self {
+        if item.needs_enrichment.unwrap_or(false) {
+            EnrichState::Needed
         } else if item.enrichment_confidence.is_some() {
-            Cell::new("✓").fg(Color::Green)
+            EnrichState::Enriched
         } else {
-            Cell::new("-").fg(Color::DarkGrey)
-        };
+            EnrichState::None
+        }
+    }
 
-        table.add_row(vec![
-            Cell::new(&item.id),
-            Cell::new(&item.title),
-            Cell::new(item.page_count),
-            status_cell,
-            enrich_status,
-        ]);
+    fn symbol(self) -> &'static str {
+        match self {
+            EnrichState::Needed => "⚠",
+            EnrichState::Enriched => "✓",
+            EnrichState::None => "-",
+        }
     }
 
-    println!("{table}");
-    println!(
+    fn color(self) -> Color {
+        match self {
+            EnrichState::Needed => Color::Yellow,
+            EnrichState::Enriched => Color::Green,
+            EnrichState::None => Color::DarkGrey,
+        }
+    }
+
+    fn meaning(self) -> &'static str {
+        match self {
+            EnrichState::Needed => "needs enrichment",
+            EnrichState::Enriched => "enriched",
+            EnrichState::None => "not enriched",
+        }
+    }
+}
+
+/// Color `text` with a table cell color, for output outside tables
+fn paint(text: &str, color: Color) -> colored::ColoredString {
+    match color {
+        Color::Green => text.green(),
+        Color::Yellow => text.yellow(),
+        Color::Cyan => text.cyan(),
+        Color::Red => text.red(),
+        Color::DarkGrey => text.dimmed(),
+        _ => text.normal(),
+    }
+}
+
+/// Legend lines explaining the Enrich symbols and status colors, for the
+/// columns in `fields`; plain text unless `color`
+fn items_legend(fields: &[ItemField], color: bool) -> Vec<String> {
+    let paint = |text: &str, c: Color| {
+        if color {
+            paint(text, c).to_string()
+        } else {
+            text.to_string()
+        }
+    };
+    let mut lines = Vec::new();
+    if fields.contains(&ItemField::Enrich) {
+        let entries: Vec<String> = EnrichState::ALL
+            .iter()
+            .map(|state| format!("{} {}", paint(state.symbol(), state.color()), state.meaning()))
+            .collect();
+        lines.push(format!("{} {}", paint("Enrich:", Color::DarkGrey), entries.join("  ")));
+    }
+    if fields.contains(&ItemField::Status) {
+        let entries: Vec<String> = ITEM_STATUSES
+            .iter()
+            .map(|(status, meaning)| format!("{} {}", paint(status, status_color(status)), meaning))
+            .collect();
+        lines.push(format!("{} {}", paint("Status:", Color::DarkGrey), entries.join("  ")));
+    }
+    lines
+}
+
+/// Print one item ID per line, optionally followed by a tab and the title
+pub fn print_item_ids(items: &[Item], with_title: bool) {
+    for item in items {
+        if with_title {
+            println!("{}\t{}", item.id, item.title);
+        } else {
+            println!("{}", item.id);
+        }
+    }
+}
+
+/// Print items as table with the given columns, truncating titles to
+/// `title_width` graphemes. With `legend`, explain the Enrich symbols and
+/// status colors after the table (only when colors are on).
+pub fn print_items_table(
+    items: &[Item],
+    enrichment_queue: &Option<Vec<EnrichmentQueueItem>>,
+    fields: &[ItemField],
+    title_width: usize,
+    legend: bool,
+) {
+    if items.is_empty() {
+        println!("{}", "No items found.".dimmed());
+        return;
+    }
+
+    let mut out = String::new();
+
+    let mut table = new_table();
+    table.set_header(
+        fields
+            .iter()
+            .map(|f| Cell::new(f.header()).fg(Color::Cyan))
+            .collect::<Vec<_>>(),
+    );
+
+    for item in items {
+        table.add_row(
+            fields
+                .iter()
+                .map(|f| f.cell(item, title_width))
+                .collect::<Vec<_>>(),
+        );
+    }
+
+    outln!(out, "{table}");
+    outln!(
+        out,
         "\n{} {}",
         items.len().to_string().bold(),
         if items.len() == 1 { "item" } else { "items" }
     );
 
+    if legend && colors_enabled() {
+        let lines = items_legend(fields, true);
+        if !lines.is_empty() {
+            outln!(out);
+            for line in lines {
+                outln!(out, "{}", line);
+            }
+        }
+    }
+
     // Print enrichment queue if present
     if let Some(queue) = enrichment_queue {
         if !queue.is_empty() {
-            println!();
-            println!("{}", "Enrichment Queue:".yellow().bold());
+            outln!(out);
+            outln!(out, "{}", "Enrichment Queue:".yellow().bold());
             for item in queue {
-                println!(
+                outln!(
+                    out,
                     "  {} {} ({} pages)",
-                    "⚠".yellow(),
+                    paint(EnrichState::Needed.symbol(), EnrichState::Needed.color()),
                     item.title.dimmed(),
                     item.page_count
                 );
             }
         }
     }
+
+    pager::show(&out);
+}
+
+/// Options controlling how `print_item_content` renders pages
+#[derive(Default)]
+pub struct ContentDisplay {
+    /// Highlight matches of these patterns in page content (when color is enabled)
+    pub highlight: Vec<Regex>,
+    /// Number of pages fetched per item before filtering, keyed by item ID.
+    /// Items listed here get an "N of M pages matched" note.
+    pub pages_before_filter: HashMap<String, usize>,
+    /// Print only raw page content, separated by this string: no titles,
+    /// rules, page markers, or colors
+    pub raw_delimiter: Option<String>,
+    /// Show item and page metadata under their headers
+    pub include_metadata: bool,
+    /// Line ending and BOM rewriting for raw content
+    pub encoding: TextEncoding,
+}
+
+/// `key: value` lines for a metadata object, one per top-level field.
+/// Strings print bare; nested values print as compact JSON. Nulls are skipped.
+fn metadata_lines(metadata: &serde_json::Value) -> Vec<String> {
+    let value_text = |value: &serde_json::Value| match value {
+        serde_json::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    match metadata {
+        serde_json::Value::Null => Vec::new(),
+        serde_json::Value::Object(fields) => fields
+            .iter()
+            .filter(|(_, value)| !value.is_null())
+            .map(|(key, value)| format!("{}: {}", key, value_text(value)))
+            .collect(),
+        other => vec![value_text(other)],
+    }
+}
+
+/// Append dimmed metadata lines, if there are any
+fn write_metadata(out: &mut String, metadata: Option<&serde_json::Value>) {
+    for line in metadata.map(metadata_lines).unwrap_or_default() {
+        outln!(out, "{}", line.dimmed());
+    }
+}
+
+/// Highlight backgrounds for `--grep`/`--highlight` patterns, in order
+const HIGHLIGHT_COLORS: [colored::Color; 5] = [
+    colored::Color::Yellow,
+    colored::Color::Cyan,
+    colored::Color::Magenta,
+    colored::Color::Green,
+    colored::Color::Red,
+];
+
+/// Byte ranges in `content` to highlight, with the index of the pattern that
+/// matched. Where matches overlap, the one starting first (then the earlier
+/// pattern) wins.
+fn highlight_spans(content: &str, patterns: &[Regex]) -> Vec<(std::ops::Range<usize>, usize)> {
+    let mut matches: Vec<_> = patterns
+        .iter()
+        .enumerate()
+        .flat_map(|(i, pattern)| {
+            pattern
+                .find_iter(content)
+                .filter(|m| !m.is_empty())
+                .map(move |m| (m.range(), i))
+        })
+        .collect();
+    matches.sort_by_key(|(range, i)| (range.start, *i));
+
+    let mut end = 0;
+    matches.retain(|(range, _)| {
+        let keep = range.start >= end;
+        if keep {
+            end = range.end;
+        }
+        keep
+    });
+    matches
 }
 
-/// Print items as JSON
-pub fn print_items_json(response: &ItemsResponse) {
-    println!("{}", serde_json::to_string_pretty(response).unwrap());
+/// Wrap every match of `patterns` in `content` with highlight styling,
+/// a different color per pattern
+fn highlight_matches(content: &str, patterns: &[Regex]) -> String {
+    let mut out = String::with_capacity(content.len());
+    let mut pos = 0;
+    for (range, i) in highlight_spans(content, patterns) {
+        out.push_str(&content[pos..range.start]);
+        let color = HIGHLIGHT_COLORS[i % HIGHLIGHT_COLORS.len()];
+        out.push_str(&content[range.clone()].black().on_color(color).to_string());
+        pos = range.end;
+    }
+    out.push_str(&content[pos..]);
+    out
 }
 
 /// Print item content with page numbers
 /// Output format is designed to be clean for both terminal use and agent consumption.
 /// The markdown content is printed raw, allowing agents to read it directly.
-pub fn print_item_content(items: &[ItemWithPages], not_found: &Option<Vec<String>>) {
+pub fn print_item_content(items: &[ItemWithPages], display: &ContentDisplay) {
+    if let Some(ref delimiter) = display.raw_delimiter {
+        return print_raw_content(items, delimiter, display.encoding);
+    }
+
+    let highlight = Some(display.highlight.as_slice())
+        .filter(|patterns| !patterns.is_empty())
+        .filter(|_| colored::control::SHOULD_COLORIZE.should_colorize());
+    let mut out = String::new();
+
     for item in items {
         // Header section with book info
-        println!();
-        println!("{}", "─".repeat(60).dimmed());
-        println!("{}", item.title.bold().cyan());
-        println!(
+        outln!(out);
+        outln!(out, "{}", rule("─").dimmed());
+        outln!(out, "{}", item.title.bold().cyan());
+        outln!(
+            out,
             "{} | {} pages",
             format!("ID: {}", item.id).dimmed(),
             item.page_count
         );
-        println!("{}", "─".repeat(60).dimmed());
+        if let Some(total) = display.pages_before_filter.get(&item.id) {
+            outln!(
+                out,
+                "{}",
+                format!("{} of {} pages matched", item.pages.len(), total).dimmed()
+            );
+        }
+        if let Some(skipped) = item.skipped_empty_pages.filter(|&n| n > 0) {
+            let pages = if skipped == 1 { "page" } else { "pages" };
+            outln!(out, "{}", format!("(skipped {} empty {})", skipped, pages).dimmed());
+        }
+        if display.include_metadata {
+            write_metadata(&mut out, item.metadata.as_ref());
+        }
+        outln!(out, "{}", rule("─").dimmed());
 
         if item.pages.is_empty() {
-            println!("{}", "No pages available.".yellow());
+            outln!(out, "{}", "No pages available.".yellow());
             continue;
         }
 
         for page in &item.pages {
             // Page separator - clean format that works in markdown and terminal
-            println!();
-            println!("{}", format!("── Page {} ──", page.page_num).blue().bold());
-            println!();
+            outln!(out);
+            let marker = match page.chapter {
+                Some(ref chapter) => format!("── Page {} · {} ──", page.page_num, chapter),
+                None => format!("── Page {} ──", page.page_num),
+            };
+            outln!(out, "{}", marker.blue().bold());
+            if display.include_metadata {
+                write_metadata(&mut out, page.metadata.as_ref());
+            }
+            outln!(out);
 
             // Output raw markdown content (no transformation)
-            if let Some(ref content) = page.content {
-                println!("{}", content);
-            } else {
-                println!("{}", "(No content)".dimmed());
+            match (&page.content, highlight) {
+                (Some(content), Some(patterns)) => {
+                    outln!(out, "{}", highlight_matches(content, patterns))
+                }
+                (Some(content), None) => outln!(out, "{}", content),
+                (None, _) => outln!(out, "{}", "(No content)".dimmed()),
             }
         }
     }
 
-    if let Some(ref not_found_ids) = not_found {
-        if !not_found_ids.is_empty() {
-            println!(
-                "\n{}: {}",
-                "Items not found".yellow(),
-                not_found_ids.join(", ")
-            );
+    pager::show(&out);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+    /// Not run, because an earlier check failed or there was nothing to check
+    Skip,
+}
+
+/// One line of a checklist, as printed by `doctor` and `items verify`
+#[derive(Debug, Serialize)]
+pub struct CheckResult {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    pub detail: String,
+    /// What to do about a warning or failure
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
+}
+
+impl CheckResult {
+    pub fn pass(name: &'static str, detail: impl Into<String>) -> Self {
+        Self { name, status: CheckStatus::Pass, detail: detail.into(), hint: None }
+    }
+
+    pub fn warn(name: &'static str, detail: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self { name, status: CheckStatus::Warn, detail: detail.into(), hint: Some(hint.into()) }
+    }
+
+    pub fn fail(name: &'static str, detail: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self { name, status: CheckStatus::Fail, detail: detail.into(), hint: Some(hint.into()) }
+    }
+
+    pub fn skip(name: &'static str, detail: impl Into<String>) -> Self {
+        Self { name, status: CheckStatus::Skip, detail: detail.into(), hint: None }
+    }
+}
+
+/// How many checks failed; warnings and skips don't count
+pub fn failed_checks(checks: &[CheckResult]) -> usize {
+    checks.iter().filter(|c| c.status == CheckStatus::Fail).count()
+}
+
+/// Print a ✓/!/✗ checklist with each hint under its check
+pub fn print_checks(checks: &[CheckResult]) {
+    let width = checks.iter().map(|c| c.name.chars().count()).max().unwrap_or(0) + 2;
+    for check in checks {
+        let symbol = match check.status {
+            CheckStatus::Pass => "✓".green().bold(),
+            CheckStatus::Warn => "!".yellow().bold(),
+            CheckStatus::Fail => "✗".red().bold(),
+            CheckStatus::Skip => "-".dimmed(),
+        };
+        println!("{} {:<width$} {}", symbol, check.name, check.detail, width = width);
+        if let Some(ref hint) = check.hint {
+            println!("  {} {}", "→".dimmed(), hint);
+        }
+    }
+}
+
+/// The only output of `--quiet` with `--json`/`--yaml`: whether everything passed
+pub fn print_check_status(ok: bool, format: OutputFormat) -> Result<()> {
+    #[derive(Serialize)]
+    struct Status {
+        ok: bool,
+    }
+    print_serialized(&Status { ok }, format)
+}
+
+/// Size figures for a document, for planning LLM context budgets
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContentStats {
+    pub id: String,
+    pub title: String,
+    pub pages: usize,
+    pub characters: usize,
+    pub words: usize,
+    /// Rough token estimate (characters / 4)
+    pub estimated_tokens: usize,
+}
+
+impl ContentStats {
+    /// Compute stats over the given page texts
+    pub fn from_pages<'a>(id: &str, title: &str, pages: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut stats = Self {
+            id: id.to_string(),
+            title: title.to_string(),
+            pages: 0,
+            characters: 0,
+            words: 0,
+            estimated_tokens: 0,
+        };
+
+        for text in pages {
+            stats.pages += 1;
+            stats.characters += text.chars().count();
+            stats.words += text.split_whitespace().count();
         }
+        stats.estimated_tokens = stats.characters.div_ceil(4);
+
+        stats
     }
 }
 
-/// Print item content as JSON
-pub fn print_item_content_json(items: &[ItemWithPages], not_found: &Option<Vec<String>>) {
-    #[derive(serde::Serialize)]
+/// Print content stats as table
+pub fn print_content_stats(stats: &[ContentStats]) {
+    let mut table = new_table();
+    table.set_header(vec![
+        Cell::new("ID").fg(Color::Cyan),
+        Cell::new("Title").fg(Color::Cyan),
+        Cell::new("Pages").fg(Color::Cyan),
+        Cell::new("Chars").fg(Color::Cyan),
+        Cell::new("Words").fg(Color::Cyan),
+        Cell::new("~Tokens").fg(Color::Cyan),
+    ]);
+
+    for s in stats {
+        table.add_row(vec![
+            Cell::new(&s.id),
+            Cell::new(truncate_display(&s.title, 60)),
+            Cell::new(s.pages),
+            Cell::new(s.characters),
+            Cell::new(s.words),
+            Cell::new(s.estimated_tokens),
+        ]);
+    }
+
+    println!("{table}");
+}
+
+/// Print `items read --stats` in a structured format, with the IDs that
+/// weren't found and how many there were
+pub fn print_content_stats_serialized(
+    stats: &[ContentStats],
+    not_found: &[String],
+    format: OutputFormat,
+) -> Result<()> {
+    #[derive(Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Output<'a> {
+        items: &'a [ContentStats],
+        not_found: &'a [String],
+        not_found_count: usize,
+    }
+
+    print_serialized(&Output { items: stats, not_found, not_found_count: not_found.len() }, format)
+}
+
+/// Print every page's content as-is, pages separated by `delimiter`
+fn print_raw_content(items: &[ItemWithPages], delimiter: &str, encoding: TextEncoding) {
+    let pages: Vec<&str> = items
+        .iter()
+        .flat_map(|item| &item.pages)
+        .map(|page| page.content.as_deref().unwrap_or(""))
+        .collect();
+
+    let mut out = pages.join(delimiter);
+    if !out.is_empty() && !out.ends_with('\n') {
+        out.push('\n');
+    }
+    pager::show(&encoding.apply(&out));
+}
+
+/// The last line of `items read --ndjson`, after one line per item
+pub fn print_ndjson_end(
+    not_found: &[String],
+    invalid_ids: &[String],
+    window: Option<&PageWindow>,
+) -> Result<()> {
+    #[derive(Serialize)]
+    struct End<'a> {
+        not_found: &'a [String],
+        #[serde(skip_serializing_if = "<[String]>::is_empty")]
+        invalid_ids: &'a [String],
+        #[serde(flatten, skip_serializing_if = "Option::is_none")]
+        window: Option<&'a PageWindow>,
+    }
+    print_event(&End { not_found, invalid_ids, window }, OutputFormat::Json)
+}
+
+/// Print item content as JSON or YAML
+pub fn print_item_content_serialized(
+    items: &[ItemWithPages],
+    not_found: &Option<Vec<String>>,
+    invalid_ids: &[String],
+    window: Option<&PageWindow>,
+    format: OutputFormat,
+) -> Result<()> {
+    #[derive(Serialize)]
     struct Output<'a> {
         items: &'a [ItemWithPages],
         #[serde(skip_serializing_if = "Option::is_none")]
         not_found: &'a Option<Vec<String>>,
+        /// The subset of `not_found` that isn't shaped like an ID at all
+        #[serde(skip_serializing_if = "<[String]>::is_empty")]
+        invalid_ids: &'a [String],
+        #[serde(flatten, skip_serializing_if = "Option::is_none")]
+        window: Option<&'a PageWindow>,
     }
 
-    let output = Output { items, not_found };
-    println!("{}", serde_json::to_string_pretty(&output).unwrap());
+    print_serialized(&Output { items, not_found, invalid_ids, window }, format)
+}
+
+/// Where an `items read --window` read sits in the document. The cursor is
+/// the number of pages before the window (0 for the first page).
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PageWindow {
+    pub cursor: u32,
+    pub window: u32,
+    pub page_count: i32,
+    /// Cursor for the following window, if there are pages left
+    pub next_cursor: Option<u32>,
+    pub has_more: bool,
 }
 
 /// Print table of contents
-pub fn print_toc(items: &[ItemWithToc], not_found: &Option<Vec<String>>) {
+pub fn print_toc(items: &[ItemWithToc], flat: bool) {
+    let mut out = String::new();
     for item in items {
-        println!(
-            "\n{} {}",
-            "=".repeat(60).dimmed(),
-            item.title.bold().cyan()
-        );
-        println!(
+        outln!(out);
+        outln!(out, "{}", rule("=").dimmed());
+        outln!(out, "{}", item.title.bold().cyan());
+        outln!(
+            out,
             "{} | {} pages",
             format!("ID: {}", item.id).dimmed(),
             item.page_count
         );
-        println!("{}", "=".repeat(80).dimmed());
+        outln!(out, "{}", rule("=").dimmed());
 
         match &item.toc {
             Some(toc) if !toc.is_empty() => {
-                print_toc_entries(toc);
+                if flat {
+                    write_flat_toc_entries(&mut out, &flat_toc(item));
+                } else {
+                    write_toc_entries(&mut out, toc);
+                }
             }
             _ => {
-                println!("{}", "No table of contents available.".yellow());
+                outln!(out, "{}", "No table of contents available.".yellow());
             }
         }
     }
 
-    if let Some(ref not_found_ids) = not_found {
-        if !not_found_ids.is_empty() {
-            println!(
-                "\n{}: {}",
-                "Items not found".yellow(),
-                not_found_ids.join(", ")
-            );
-        }
-    }
+    pager::show(&out);
 }
 
-fn print_toc_entries(entries: &[TocEntry]) {
+fn write_toc_entries(out: &mut String, entries: &[TocEntry]) {
     for entry in entries {
         let indent = "  ".repeat(entry.level.unwrap_or(0) as usize);
-        println!(
+        outln!(
+            out,
             "{}{}{}",
             indent,
             entry.title,
@@ -230,17 +1091,105 @@ fn print_toc_entries(entries: &[TocEntry]) {
     }
 }
 
-/// Print TOC as JSON
-pub fn print_toc_json(items: &[ItemWithToc], not_found: &Option<Vec<String>>) {
-    #[derive(serde::Serialize)]
-    struct Output<'a> {
-        items: &'a [ItemWithToc],
+/// Pages covered by `toc[index]`: from its page up to the page before the
+/// next entry at the same or a higher level, or to the last page. Entries
+/// without a level count as top-level.
+pub fn toc_entry_range(toc: &[TocEntry], index: usize, page_count: i32) -> (i32, i32) {
+    let entry = &toc[index];
+    let level = entry.level.unwrap_or(0);
+    let end = toc[index + 1..]
+        .iter()
+        .find(|next| next.level.unwrap_or(0) <= level)
+        .map(|next| next.page - 1)
+        .unwrap_or(page_count);
+    (entry.page, end.max(entry.page))
+}
+
+/// A TOC entry with its page range, as `items toc --flat` prints it
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FlatTocEntry<'a> {
+    pub title: &'a str,
+    pub level: Option<i32>,
+    pub page_start: i32,
+    pub page_end: i32,
+    /// The `items read` argument for these pages
+    pub read: String,
+}
+
+/// An item's TOC entries, each with its page range
+pub fn flat_toc(item: &ItemWithToc) -> Vec<FlatTocEntry<'_>> {
+    let toc = item.toc.as_deref().unwrap_or_default();
+    (0..toc.len())
+        .map(|i| {
+            let (page_start, page_end) = toc_entry_range(toc, i, item.page_count);
+            let pages = if page_start == page_end {
+                page_start.to_string()
+            } else {
+                format!("{}-{}", page_start, page_end)
+            };
+            FlatTocEntry {
+                title: &toc[i].title,
+                level: toc[i].level,
+                page_start,
+                page_end,
+                read: format!("{}:{}", item.id, pages),
+            }
+        })
+        .collect()
+}
+
+/// Read spec first so it lines up for copying, then the indented title
+fn write_flat_toc_entries(out: &mut String, entries: &[FlatTocEntry]) {
+    let width = entries.iter().map(|e| e.read.len()).max().unwrap_or(0);
+    for entry in entries {
+        let indent = "  ".repeat(entry.level.unwrap_or(0).max(0) as usize);
+        outln!(out, "{:<width$}  {}{}", entry.read, indent, entry.title, width = width);
+    }
+}
+
+/// Print TOC as JSON or YAML; with `flat`, each entry has its page range
+/// and read spec
+pub fn print_toc_serialized(
+    items: &[ItemWithToc],
+    flat: bool,
+    not_found: &Option<Vec<String>>,
+    invalid_ids: &[String],
+    format: OutputFormat,
+) -> Result<()> {
+    #[derive(Serialize)]
+    struct Output<'a, T> {
+        items: T,
         #[serde(skip_serializing_if = "Option::is_none")]
         not_found: &'a Option<Vec<String>>,
+        /// The subset of `not_found` that isn't shaped like an ID at all
+        #[serde(skip_serializing_if = "<[String]>::is_empty")]
+        invalid_ids: &'a [String],
     }
 
-    let output = Output { items, not_found };
-    println!("{}", serde_json::to_string_pretty(&output).unwrap());
+    #[derive(Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct FlatItem<'a> {
+        id: &'a str,
+        title: &'a str,
+        page_count: i32,
+        toc: Vec<FlatTocEntry<'a>>,
+    }
+
+    if flat {
+        let items: Vec<FlatItem> = items
+            .iter()
+            .map(|item| FlatItem {
+                id: &item.id,
+                title: &item.title,
+                page_count: item.page_count,
+                toc: flat_toc(item),
+            })
+            .collect();
+        print_serialized(&Output { items, not_found, invalid_ids }, format)
+    } else {
+        print_serialized(&Output { items, not_found, invalid_ids }, format)
+    }
 }
 
 /// Print success message
@@ -258,46 +1207,86 @@ pub fn print_warning(message: &str) {
     println!("{} {}", "!".yellow().bold(), message);
 }
 
+/// Print warning message to stderr, keeping stdout clean for piped content
+pub fn eprint_warning(message: &str) {
+    eprintln!("{} {}", "!".yellow().bold(), message);
+}
+
 /// Print info message
 pub fn print_info(message: &str) {
     println!("{} {}", "i".cyan().bold(), message);
 }
 
-/// Print sources as table
-pub fn print_sources_table(sources: &[Source], total: i64) {
+/// The author shown for a source: handle, falling back to name
+pub fn source_author(source: &Source) -> Option<&str> {
+    source
+        .author_handle
+        .as_deref()
+        .or(source.author_name.as_deref())
+}
+
+/// Print one source ID per line, or NUL-terminated for `xargs -0`
+pub fn print_source_ids(sources: &[Source], null_separated: bool) -> Result<()> {
+    let terminator = if null_separated { '\0' } else { '\n' };
+    let mut stdout = io::stdout().lock();
+    for source in sources {
+        write!(stdout, "{}{}", source.id, terminator)?;
+    }
+    stdout.flush()?;
+    Ok(())
+}
+
+/// Placeholders `sources list --template` accepts
+pub const SOURCE_TEMPLATE_FIELDS: &[&str] = &[
+    "id", "type", "author", "handle", "content", "url", "published", "created", "updated",
+];
+
+/// A source field as plain text for `--template`; missing values are "-"
+pub fn source_template_value(source: &Source, field: &str) -> String {
+    let or_dash = |value: Option<&str>| value.unwrap_or("-").to_string();
+    match field {
+        "id" => source.id.clone(),
+        "type" => source.source_type.clone(),
+        "author" => or_dash(source_author(source)),
+        "handle" => or_dash(source.author_handle.as_deref()),
+        "content" => or_dash(source.content.as_deref()),
+        "url" => or_dash(source.source_url.as_deref()),
+        "published" => or_dash(source.published_at.as_deref().map(short_date)),
+        "created" => short_date(&source.created_at).to_string(),
+        "updated" => short_date(&source.updated_at).to_string(),
+        _ => String::new(),
+    }
+}
+
+/// Print sources as table. `author` is the active author filter, if any.
+pub fn print_sources_table(sources: &[Source], total: i64, author: Option<&str>) {
     if sources.is_empty() {
         println!("{}", "No sources found.".dimmed());
         return;
     }
 
-    let mut table = Table::new();
-    table
-        .load_preset(UTF8_FULL)
-        .set_content_arrangement(ContentArrangement::Dynamic)
-        .set_header(vec![
-            Cell::new("ID").fg(Color::Cyan),
-            Cell::new("Author").fg(Color::Cyan),
-            Cell::new("Content").fg(Color::Cyan),
-            Cell::new("URL").fg(Color::Cyan),
-            Cell::new("Date").fg(Color::Cyan),
-        ]);
+    let mut out = String::new();
+
+    let mut table = new_table();
+    table.set_header(vec![
+        Cell::new("ID").fg(Color::Cyan),
+        Cell::new("Author").fg(Color::Cyan),
+        Cell::new("Content").fg(Color::Cyan),
+        Cell::new("URL").fg(Color::Cyan),
+        Cell::new("Date").fg(Color::Cyan),
+    ]);
 
     for source in sources {
-        let author = source
-            .author_handle
-            .as_deref()
-            .or(source.author_name.as_deref())
-            .unwrap_or("-");
+        let author = source_author(source).unwrap_or("-");
 
         let content = match &source.content {
-            Some(c) if c.len() > 50 => format!("{}...", &c[..47]),
-            Some(c) => c.clone(),
+            Some(c) => truncate_display(c, 50),
             None => "-".to_string(),
         };
 
         let url = source.source_url.as_deref().unwrap_or("-");
 
-        let date = &source.created_at[..10]; // YYYY-MM-DD
+        let date = short_date(&source.created_at);
 
         table.add_row(vec![
             Cell::new(&source.id),
@@ -308,16 +1297,261 @@ pub fn print_sources_table(sources: &[Source], total: i64) {
         ]);
     }
 
+    outln!(out, "{table}");
+    match author {
+        Some(author) => outln!(
+            out,
+            "\nshowing {} of {} {} matching author '{}'",
+            sources.len().to_string().bold(),
+            total,
+            if total == 1 { "source" } else { "sources" },
+            author
+        ),
+        None => outln!(
+            out,
+            "\n{} {} (showing {})",
+            total.to_string().bold(),
+            if total == 1 { "source" } else { "sources" },
+            sources.len()
+        ),
+    }
+
+    pager::show(&out);
+}
+
+/// Print a unified diff between a stored document and a local file
+pub fn print_diff(diff: &DocumentDiff, id: &str, file: &str) {
+    if diff.is_identical() {
+        println!("{}", format!("{} and {} are identical", id, file).dimmed());
+        return;
+    }
+
+    let mut out = String::new();
+    outln!(out, "{}", format!("--- {} (stored)", id).bold());
+    outln!(out, "{}", format!("+++ {}", file).bold());
+    for hunk in &diff.hunks {
+        outln!(out, "{}", hunk.header().cyan());
+        for line in &hunk.lines {
+            match line.kind {
+                "added" => outln!(out, "{}", format!("+{}", line.text).green()),
+                "removed" => outln!(out, "{}", format!("-{}", line.text).red()),
+                _ => outln!(out, " {}", line.text),
+            }
+        }
+    }
+
+    pager::show(&out);
+}
+
+/// Print effective config as TOML, annotated with where each value came from
+pub fn print_config(values: &[EffectiveValue]) {
+    let mut current_section = "";
+
+    for entry in values {
+        let (section, key) = entry.key.rsplit_once('.').unwrap_or(("", &entry.key));
+        if section != current_section {
+            if !current_section.is_empty() {
+                println!();
+            }
+            println!("[{}]", section);
+            current_section = section;
+        }
+
+        let source = match (entry.source, entry.env_var) {
+            (ValueSource::Env, Some(var)) => format!("env ({})", var),
+            (ValueSource::Env, None) => "env".to_string(),
+            (ValueSource::File, _) => "config file".to_string(),
+            (ValueSource::Default, _) => "default".to_string(),
+        };
+
+        match &entry.value {
+            Some(value) => {
+                let value = if value.parse::<u64>().is_ok() {
+                    value.clone()
+                } else {
+                    format!("{:?}", value)
+                };
+                println!("{} = {}  {}", key, value, format!("# {}", source).dimmed());
+            }
+            None => println!("{}", format!("# {} (not set)", key).dimmed()),
+        }
+    }
+}
+
+/// Print profiles as a table; the one in use is marked with *
+pub fn print_profiles(profiles: &[ProfileSummary]) {
+    let mut table = new_table();
+    table.set_header(vec![
+        Cell::new("").fg(Color::Cyan),
+        Cell::new("Profile").fg(Color::Cyan),
+        Cell::new("URL").fg(Color::Cyan),
+        Cell::new("API key").fg(Color::Cyan),
+    ]);
+
+    for profile in profiles {
+        let key = match (&profile.api_key, profile.key_valid) {
+            (None, _) => Cell::new("-"),
+            (Some(key), true) => Cell::new(key),
+            (Some(key), false) => Cell::new(format!("{} (invalid)", key)).fg(Color::Yellow),
+        };
+        table.add_row(vec![
+            if profile.active { Cell::new("*").fg(Color::Green) } else { Cell::new("") },
+            Cell::new(&profile.name),
+            Cell::new(&profile.url),
+            key,
+        ]);
+    }
+
     println!("{table}");
-    println!(
-        "\n{} {} (showing {})",
-        total.to_string().bold(),
-        if total == 1 { "source" } else { "sources" },
-        sources.len()
-    );
 }
 
-/// Print sources as JSON
-pub fn print_sources_json(response: &SourcesResponse) {
-    println!("{}", serde_json::to_string_pretty(response).unwrap());
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metadata_lines() {
+        let metadata = serde_json::json!({
+            "section": "Intro",
+            "bbox": [0, 0, 612, 792],
+            "ocr": null
+        });
+        assert_eq!(
+            metadata_lines(&metadata),
+            vec!["bbox: [0,0,612,792]".to_string(), "section: Intro".to_string()]
+        );
+        assert!(metadata_lines(&serde_json::Value::Null).is_empty());
+    }
+
+    #[test]
+    fn test_items_legend_follows_columns() {
+        let legend = items_legend(&[ItemField::Id, ItemField::Enrich], false);
+        assert_eq!(
+            legend,
+            vec!["Enrich: ⚠ needs enrichment  ✓ enriched  - not enriched".to_string()]
+        );
+        assert_eq!(items_legend(DEFAULT_ITEM_FIELDS, false).len(), 2);
+        assert!(items_legend(&[ItemField::Id], false).is_empty());
+    }
+
+    #[test]
+    fn test_highlight_spans_skip_overlaps() {
+        let patterns = [Regex::new("neural").unwrap(), Regex::new("net(work)?").unwrap()];
+        let spans = highlight_spans("neural networks, neural net", &patterns);
+        assert_eq!(spans, vec![(0..6, 0), (7..14, 1), (17..23, 0), (24..27, 1)]);
+
+        // "ural" starts inside the first pattern's match, so it's dropped
+        let overlapping = [Regex::new("neural").unwrap(), Regex::new("ural n").unwrap()];
+        assert_eq!(highlight_spans("neural net", &overlapping), vec![(0..6, 0)]);
+    }
+
+    #[test]
+    fn test_text_encoding() {
+        let keep = TextEncoding::default();
+        assert_eq!(keep.apply("a\r\nb\n"), "a\r\nb\n");
+
+        let crlf = TextEncoding { line_ending: Some(LineEnding::Crlf), bom: false };
+        assert_eq!(crlf.apply("a\nb\r\nc"), "a\r\nb\r\nc");
+
+        let lf = TextEncoding { line_ending: Some(LineEnding::Lf), bom: true };
+        assert_eq!(lf.apply("a\r\nb\n"), "\u{feff}a\nb\n");
+        assert_eq!(lf.apply("\u{feff}x"), "\u{feff}x");
+    }
+
+    #[test]
+    fn test_unescape() {
+        assert_eq!(unescape("\\f"), "\x0c");
+        assert_eq!(unescape("\\n---\\n"), "\n---\n");
+        assert_eq!(unescape("a\\\\b\\x"), "a\\b\\x");
+    }
+
+    #[test]
+    fn test_template_render() {
+        let template =
+            Template::parse("{id}\\t{title} ({pages}p) {{x}}", &["id", "title", "pages"]).unwrap();
+        let rendered = template.render(|name| match name {
+            "id" => "itm_1".to_string(),
+            "title" => "Doc".to_string(),
+            _ => "3".to_string(),
+        });
+        assert_eq!(rendered, "itm_1\tDoc (3p) {x}");
+    }
+
+    #[test]
+    fn test_template_rejects_unknown_fields() {
+        let err = Template::parse("{id} {size}", &["id", "title"]).unwrap_err().to_string();
+        assert_eq!(err, "Unknown template field '{size}'; valid fields: id, title");
+        assert!(Template::parse("{id", &["id"]).is_err());
+        assert!(Template::parse("id}", &["id"]).is_err());
+    }
+
+    #[test]
+    fn test_truncate_display_ascii() {
+        assert_eq!(truncate_display("short", 10), "short");
+        assert_eq!(truncate_display("exactly10!", 10), "exactly10!");
+        assert_eq!(truncate_display("this is too long", 8), "this is…");
+    }
+
+    #[test]
+    fn test_truncate_display_multibyte() {
+        // Each of these is multiple bytes; byte slicing would panic mid-character
+        assert_eq!(truncate_display("café au lait", 5), "café…");
+        assert_eq!(truncate_display("日本語のタイトル", 4), "日本語…");
+        assert_eq!(truncate_display("👍🏽👍🏽👍🏽", 2), "👍🏽…");
+        assert_eq!(truncate_display("naïve", 5), "naïve");
+    }
+
+    #[test]
+    fn test_flat_toc_ranges_and_read_specs() {
+        let entry = |title: &str, page, level| TocEntry {
+            title: title.to_string(),
+            page,
+            level,
+        };
+        let item = ItemWithToc {
+            id: "itm_1".to_string(),
+            title: "Doc".to_string(),
+            page_count: 20,
+            toc: Some(vec![
+                entry("Intro", 1, Some(0)),
+                entry("Chapter 2", 3, Some(0)),
+                entry("2.1", 3, Some(1)),
+                entry("2.2", 6, Some(1)),
+                entry("Appendix", 15, None),
+            ]),
+        };
+
+        let flat = flat_toc(&item);
+        let ranges: Vec<(i32, i32, &str)> =
+            flat.iter().map(|e| (e.page_start, e.page_end, e.read.as_str())).collect();
+        assert_eq!(
+            ranges,
+            vec![
+                (1, 2, "itm_1:1-2"),
+                (3, 14, "itm_1:3-14"),
+                (3, 5, "itm_1:3-5"),
+                (6, 14, "itm_1:6-14"),
+                (15, 20, "itm_1:15-20"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_short_date() {
+        assert_eq!(short_date("2026-02-14T10:30:00.000Z"), "2026-02-14");
+        assert_eq!(short_date("2026-02-14"), "2026-02-14");
+        assert_eq!(short_date("2026-02"), "-");
+        assert_eq!(short_date(""), "-");
+        assert_eq!(short_date("yesterday at noon"), "-");
+        assert_eq!(short_date("2026-ü2-14T00:00"), "-");
+    }
+
+    #[test]
+    fn test_content_stats() {
+        let stats = ContentStats::from_pages("id", "t", ["one two three", "", "café"]);
+        assert_eq!(stats.pages, 3);
+        assert_eq!(stats.words, 4);
+        assert_eq!(stats.characters, 17);
+        assert_eq!(stats.estimated_tokens, 5);
+    }
 }
@@ -0,0 +1,97 @@
+use std::io::{self, IsTerminal, Write};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::prompt;
+
+/// Pager used when `$PAGER` is unset; `-R` keeps colors intact
+const DEFAULT_PAGER: &str = "less -R";
+/// Env var that turns paging off like `--no-pager`
+pub const NO_PAGER_ENV: &str = "CANDLEKEEP_NO_PAGER";
+
+/// Set by `--no-pager`
+static DISABLED: AtomicBool = AtomicBool::new(false);
+
+/// Turn paging off for the rest of this process
+pub fn disable() {
+    DISABLED.store(true, Ordering::Relaxed);
+}
+
+/// Whether paging is allowed at all: not disabled by flag,
+/// `CANDLEKEEP_NO_PAGER`, or non-interactive mode, and stdout is a terminal
+fn enabled() -> bool {
+    if DISABLED.load(Ordering::Relaxed) || prompt::non_interactive() {
+        return false;
+    }
+    if std::env::var_os(NO_PAGER_ENV).is_some_and(|v| !v.is_empty()) {
+        return false;
+    }
+    io::stdout().is_terminal()
+}
+
+/// Rows `text` would take on a terminal `width` columns wide, counting
+/// wrapped lines
+fn rendered_rows(text: &str, width: usize) -> usize {
+    text.lines()
+        .map(|line| line.chars().count().div_ceil(width.max(1)).max(1))
+        .sum()
+}
+
+/// Print `text` to stdout, through `$PAGER` when it's taller than the
+/// terminal. Falls back to printing directly if the pager can't be started.
+pub fn show(text: &str) {
+    if enabled() {
+        if let Some((terminal_size::Width(w), terminal_size::Height(h))) =
+            terminal_size::terminal_size()
+        {
+            if rendered_rows(text, w as usize) > h as usize && spawn(text) {
+                return;
+            }
+        }
+    }
+
+    print!("{}", text);
+}
+
+/// Pipe `text` into the pager and wait for it to exit.
+/// Returns false if no pager could be started.
+fn spawn(text: &str) -> bool {
+    let pager = std::env::var("PAGER").unwrap_or_else(|_| DEFAULT_PAGER.to_string());
+    let mut parts = pager.split_whitespace();
+    let Some(program) = parts.next() else {
+        return false;
+    };
+    if program == "cat" {
+        return false;
+    }
+
+    let mut child = match Command::new(program)
+        .args(parts)
+        .stdin(Stdio::piped())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(_) => return false,
+    };
+
+    // Ctrl-C in the pager (e.g. cancelling a search in less) is for the pager
+    let _suspended = crate::interrupt::Suspended::new();
+    if let Some(mut stdin) = child.stdin.take() {
+        // A broken pipe just means the user quit the pager early
+        let _ = stdin.write_all(text.as_bytes());
+    }
+    let _ = child.wait();
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rendered_rows_counts_wrapping() {
+        assert_eq!(rendered_rows("a\nb\n", 80), 2);
+        assert_eq!(rendered_rows(&"x".repeat(200), 80), 3);
+        assert_eq!(rendered_rows("a\n\nb", 80), 3);
+    }
+}
@@ -0,0 +1,97 @@
+use anyhow::Result;
+use dialoguer::{theme::ColorfulTheme, MultiSelect};
+use std::io::{self, IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Env var that turns on non-interactive mode like `--non-interactive`
+pub const NON_INTERACTIVE_ENV: &str = "CANDLEKEEP_NON_INTERACTIVE";
+
+/// Set by `--non-interactive`
+static NON_INTERACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Promise zero interactive IO for the rest of this process: no prompts,
+/// pagers, or animated progress
+pub fn set_non_interactive() {
+    NON_INTERACTIVE.store(true, Ordering::Relaxed);
+}
+
+/// Whether `--non-interactive` or `CANDLEKEEP_NON_INTERACTIVE` is in effect
+pub fn non_interactive() -> bool {
+    NON_INTERACTIVE.load(Ordering::Relaxed)
+        || std::env::var_os(NON_INTERACTIVE_ENV).is_some_and(|v| !v.is_empty())
+}
+
+/// Whether we may read answers from the user: not in non-interactive mode,
+/// and stdin is a terminal
+pub fn interactive() -> bool {
+    !non_interactive() && io::stdin().is_terminal()
+}
+
+/// Ask a yes/no question and return true only on an explicit "y" or "yes".
+///
+/// When we can't prompt (non-interactive mode, piped input, CI) no line is
+/// read; instead an error is returned telling the user to pass `--yes` for
+/// `action`.
+pub fn confirm(question: &str, action: &str) -> Result<bool> {
+    if !interactive() {
+        return Err(anyhow::anyhow!(
+            "Refusing to {} without --yes in non-interactive mode",
+            action
+        ));
+    }
+
+    print!("{} [y/N]: ", question);
+    io::stdout().flush()?;
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+
+    Ok(matches!(input.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Read one line of user input after printing `question`.
+/// Errors instead of waiting when we can't prompt; `hint` says what to do instead.
+pub fn read_line(question: &str, hint: &str) -> Result<String> {
+    if !interactive() {
+        return Err(anyhow::anyhow!("Can't prompt in non-interactive mode; {}", hint));
+    }
+
+    print!("{}", question);
+    io::stdout().flush()?;
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    Ok(input)
+}
+
+/// Read all of stdin. In non-interactive mode a terminal on stdin fails
+/// with `terminal_error` instead of waiting for typed input.
+pub fn read_stdin(terminal_error: &str) -> Result<String> {
+    if non_interactive() && io::stdin().is_terminal() {
+        return Err(anyhow::anyhow!("{}", terminal_error));
+    }
+    Ok(io::read_to_string(io::stdin())?)
+}
+
+/// Error unless we may prompt and stdout is a terminal, as checkbox selection needs
+pub fn ensure_can_select() -> Result<()> {
+    if !interactive() || !io::stdout().is_terminal() {
+        return Err(anyhow::anyhow!(
+            "Interactive selection needs a terminal; pass IDs explicitly instead"
+        ));
+    }
+    Ok(())
+}
+
+/// Let the user pick any number of `options` with a checkbox list.
+/// Returns the chosen indices; empty if the user selected nothing or pressed Esc.
+pub fn select_many(question: &str, options: &[String]) -> Result<Vec<usize>> {
+    ensure_can_select()?;
+
+    let chosen = MultiSelect::with_theme(&ColorfulTheme::default())
+        .with_prompt(question)
+        .items(options)
+        .interact_opt()?;
+
+    Ok(chosen.unwrap_or_default())
+}
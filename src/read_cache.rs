@@ -0,0 +1,346 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use crate::api::{ClientOptions, ItemReadRequest, ItemWithPages};
+use crate::config;
+
+/// How long `items read --cache-read` serves cached pages, unless `--cache-ttl`
+pub const DEFAULT_TTL_SECS: u64 = 24 * 60 * 60;
+
+/// Set after the first failed write so we only warn once per process
+static WARNED: AtomicBool = AtomicBool::new(false);
+
+/// One cached `items read` result, for one item and page range
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Entry {
+    cached_at: String,
+    item: ItemWithPages,
+}
+
+/// `Entry` as written
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NewEntry<'a> {
+    cached_at: String,
+    item: &'a ItemWithPages,
+}
+
+/// Cached reads under ~/.candlekeep/cache/read/<scope>, one file per item and
+/// page range, where the scope is the profile and API URL they were read with
+pub struct ReadCache {
+    dir: PathBuf,
+    /// Entries older than this are stale; `None` serves any age without
+    /// calling the API (offline)
+    ttl: Option<Duration>,
+    /// Each item's current `updatedAt`, fetched the first time there's an
+    /// entry to check; an entry for an older version is stale. Holds `None`
+    /// when the check failed, so entries are fetched again.
+    versions: tokio::sync::OnceCell<Option<HashMap<String, String>>>,
+}
+
+/// Name of the directory for reads made with `profile` against `api_url`, so
+/// one account's pages are never served to another. A stable FNV-1a hash
+/// keeps it a valid file name whatever the URL.
+fn scope_dir(profile: &str, api_url: &str) -> String {
+    let hash = format!("{}\n{}", profile, api_url).bytes().fold(0xcbf29ce484222325u64, |hash, b| {
+        (hash ^ u64::from(b)).wrapping_mul(0x100000001b3)
+    });
+    format!("{:016x}", hash)
+}
+
+/// Directory holding the cached reads for the profile and API URL in use
+fn cache_dir(opts: &ClientOptions) -> Result<PathBuf> {
+    let config = config::load_config()?;
+    let profile = config.selected_profile_name().unwrap_or(config::DEFAULT_PROFILE);
+    let api_url = config::resolve_api_url(opts.base_url.as_deref())?;
+    Ok(config::config_dir()?.join("cache").join("read").join(scope_dir(profile, &api_url)))
+}
+
+/// Write `contents` to a new file only the owner can read, as entries hold
+/// library content
+fn write_private(path: &std::path::Path, contents: &[u8]) -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::io::Write;
+        use std::os::unix::fs::OpenOptionsExt;
+        let mut file =
+            fs::OpenOptions::new().write(true).create(true).truncate(true).mode(0o600).open(path)?;
+        file.write_all(contents)
+    }
+    #[cfg(not(unix))]
+    {
+        fs::write(path, contents)
+    }
+}
+
+/// `id:pages` as written on the command line
+fn describe(request: &ItemReadRequest) -> String {
+    format!("{}:{}", request.id, request.pages.as_deref().unwrap_or("all"))
+}
+
+impl ReadCache {
+    /// Serve entries that are younger than `ttl` and whose item hasn't changed
+    pub fn online(opts: &ClientOptions, ttl: Duration) -> Result<Self> {
+        Ok(Self { dir: cache_dir(opts)?, ttl: Some(ttl), versions: Default::default() })
+    }
+
+    /// Serve any entry, however old
+    pub fn offline(opts: &ClientOptions) -> Result<Self> {
+        Ok(Self { dir: cache_dir(opts)?, ttl: None, versions: Default::default() })
+    }
+
+    /// File for a request; `None` when the ID or range can't be used in a
+    /// file name, so the request isn't cached
+    fn entry_path(&self, request: &ItemReadRequest) -> Option<PathBuf> {
+        let pages = request.pages.as_deref().unwrap_or("all");
+        let id_ok = !request.id.is_empty()
+            && request.id.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+        let pages_ok = pages.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == ',');
+        (id_ok && pages_ok)
+            .then(|| self.dir.join(format!("{}@{}.json", request.id, pages.replace(',', "_"))))
+    }
+
+    /// The cached item for `request`, if there's a fresh entry
+    fn lookup(&self, request: &ItemReadRequest, now: DateTime<Utc>) -> Option<ItemWithPages> {
+        let contents = fs::read_to_string(self.entry_path(request)?).ok()?;
+        let entry: Entry = serde_json::from_str(&contents).ok()?;
+
+        if let Some(ttl) = self.ttl {
+            let cached_at = DateTime::parse_from_rfc3339(&entry.cached_at).ok()?;
+            let age = (now - cached_at.with_timezone(&Utc)).to_std().unwrap_or_default();
+            if age > ttl {
+                return None;
+            }
+        }
+        Some(entry.item)
+    }
+
+    /// Save a fetched item. Failures never fail the command; the first one
+    /// is reported on stderr.
+    fn store(&self, request: &ItemReadRequest, item: &ItemWithPages) {
+        let Some(path) = self.entry_path(request) else {
+            return;
+        };
+        let entry = NewEntry { cached_at: Utc::now().to_rfc3339(), item };
+        let result = (|| -> Result<()> {
+            fs::create_dir_all(&self.dir)
+                .with_context(|| format!("Failed to create {}", self.dir.display()))?;
+            // Write then rename, so a concurrent read never sees half an entry
+            let tmp = path.with_extension("json.tmp");
+            write_private(&tmp, &serde_json::to_vec(&entry)?)?;
+            fs::rename(&tmp, &path)?;
+            Ok(())
+        })();
+
+        if let Err(e) = result {
+            if !WARNED.swap(true, Ordering::Relaxed) {
+                eprintln!("Warning: could not write the read cache: {:#}", e);
+            }
+        }
+    }
+
+    /// Fetch one chunk of `items read` requests, serving what's cached and
+    /// sending the rest with `fetch`, whose results are cached. Items come
+    /// back in request order. Offline, anything not cached is an error.
+    ///
+    /// Online, `versions` lists each item's current `updatedAt`. It's called
+    /// once per cache, and only when an entry is found.
+    pub async fn read_through<F, Fut, V, VFut>(
+        &self,
+        chunk: Vec<ItemReadRequest>,
+        fetch: F,
+        versions: V,
+    ) -> Result<(Vec<ItemWithPages>, Option<Vec<String>>)>
+    where
+        F: FnOnce(Vec<ItemReadRequest>) -> Fut,
+        Fut: std::future::Future<Output = Result<(Vec<ItemWithPages>, Option<Vec<String>>)>>,
+        V: FnOnce() -> VFut,
+        VFut: std::future::Future<Output = Result<HashMap<String, String>>>,
+    {
+        let now = Utc::now();
+        let mut slots: Vec<Option<ItemWithPages>> =
+            chunk.iter().map(|request| self.lookup(request, now)).collect();
+
+        // Entries for an item that changed since they were cached are stale
+        if self.ttl.is_some() && slots.iter().any(Option::is_some) {
+            let versions = self
+                .versions
+                .get_or_init(|| async {
+                    versions()
+                        .await
+                        .map_err(|e| {
+                            eprintln!(
+                                "Warning: could not check cached items for changes, \
+                                 fetching them again: {:#}",
+                                e
+                            )
+                        })
+                        .ok()
+                })
+                .await;
+            for (request, slot) in chunk.iter().zip(slots.iter_mut()) {
+                let current = versions.as_ref().and_then(|v| v.get(&request.id));
+                if slot.as_ref().is_some_and(|item| current != Some(&item.updated_at)) {
+                    *slot = None;
+                }
+            }
+        }
+        let misses: Vec<ItemReadRequest> = chunk
+            .into_iter()
+            .zip(&slots)
+            .filter(|(_, slot)| slot.is_none())
+            .map(|(request, _)| request)
+            .collect();
+        if misses.is_empty() {
+            return Ok((slots.into_iter().flatten().collect(), None));
+        }
+        // Offline
+        if self.ttl.is_none() {
+            let missing: Vec<String> = misses.iter().map(describe).collect();
+            return Err(anyhow::anyhow!(
+                "Not in the read cache: {} (run without --offline to fetch it)",
+                missing.join(", ")
+            ));
+        }
+
+        let (mut fetched, not_found) = fetch(misses.clone()).await?;
+        for request in &misses {
+            // An item read with two ranges at once can't be told apart
+            let unique = misses.iter().filter(|r| r.id == request.id).count() == 1;
+            if let Some(item) = fetched.iter().find(|item| unique && item.id == request.id) {
+                self.store(request, item);
+            }
+        }
+
+        let mut misses = misses.into_iter();
+        for slot in slots.iter_mut().filter(|slot| slot.is_none()) {
+            let Some(request) = misses.next() else {
+                break;
+            };
+            if let Some(pos) = fetched.iter().position(|item| item.id == request.id) {
+                *slot = Some(fetched.remove(pos));
+            }
+        }
+        let mut items: Vec<ItemWithPages> = slots.into_iter().flatten().collect();
+        items.extend(fetched);
+        Ok((items, not_found))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(id: &str, updated_at: &str, content: &str) -> ItemWithPages {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "title": "Doc",
+            "description": null,
+            "sourceType": "pdf",
+            "metadata": null,
+            "createdAt": "2026-01-01T00:00:00Z",
+            "updatedAt": updated_at,
+            "pageCount": 1,
+            "pages": [{"id": "p1", "pageNum": 1, "content": content, "metadata": null}],
+            "status": "READY"
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_scope_dir_separates_profiles_and_urls() {
+        let home = scope_dir("default", "https://www.getcandlekeep.com");
+        assert_eq!(home, scope_dir("default", "https://www.getcandlekeep.com"));
+        assert_eq!(home.len(), 16);
+        assert_ne!(home, scope_dir("work", "https://www.getcandlekeep.com"));
+        assert_ne!(home, scope_dir("default", "http://localhost:3000"));
+    }
+
+    fn request(id: &str) -> ItemReadRequest {
+        ItemReadRequest { id: id.to_string(), pages: Some("1".to_string()) }
+    }
+
+    #[tokio::test]
+    async fn test_read_through_serves_fresh_entries_and_refetches_changed_items() {
+        let dir = std::env::temp_dir().join(format!("ck-read-cache-test-{}", std::process::id()));
+        let cache = || ReadCache {
+            dir: dir.clone(),
+            ttl: Some(Duration::from_secs(60)),
+            versions: Default::default(),
+        };
+        let checks = std::sync::atomic::AtomicUsize::new(0);
+        let versions = |v: &'static str| {
+            let checks = &checks;
+            move || async move {
+                checks.fetch_add(1, Ordering::Relaxed);
+                Ok(HashMap::from([("itm_1".to_string(), v.to_string())]))
+            }
+        };
+
+        // Nothing cached yet, so there's nothing to check
+        let (items, _) = cache()
+            .read_through(
+                vec![request("itm_1")],
+                |_| async { Ok((vec![item("itm_1", "v1", "first")], None)) },
+                versions("v1"),
+            )
+            .await
+            .unwrap();
+        assert_eq!(items[0].pages[0].content.as_deref(), Some("first"));
+        assert_eq!(checks.load(Ordering::Relaxed), 0);
+
+        // Served from the cache: fetching would fail
+        let (items, _) = cache()
+            .read_through(
+                vec![request("itm_1")],
+                |_| async { Err(anyhow::anyhow!("fetched")) },
+                versions("v1"),
+            )
+            .await
+            .unwrap();
+        assert_eq!(items[0].pages[0].content.as_deref(), Some("first"));
+        assert_eq!(checks.load(Ordering::Relaxed), 1);
+
+        // The item changed on the server, so the entry is stale
+        let (items, _) = cache()
+            .read_through(
+                vec![request("itm_1")],
+                |_| async { Ok((vec![item("itm_1", "v2", "second")], None)) },
+                versions("v2"),
+            )
+            .await
+            .unwrap();
+        assert_eq!(items[0].pages[0].content.as_deref(), Some("second"));
+
+        // The check failed, so the entry is fetched again
+        let (items, _) = cache()
+            .read_through(
+                vec![request("itm_1")],
+                |_| async { Ok((vec![item("itm_1", "v2", "third")], None)) },
+                || async { Err(anyhow::anyhow!("list failed")) },
+            )
+            .await
+            .unwrap();
+        assert_eq!(items[0].pages[0].content.as_deref(), Some("third"));
+
+        let offline = ReadCache { dir: dir.clone(), ttl: None, versions: Default::default() };
+        let err = offline
+            .read_through(
+                vec![request("itm_2")],
+                |_| async { Ok((Vec::new(), None)) },
+                || async { Ok(HashMap::new()) },
+            )
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("itm_2:1"), "{}", err);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,111 @@
+use serde::Serialize;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use crate::config;
+
+/// Env var naming the request log file when `--log-file` isn't given
+pub const LOG_FILE_ENV: &str = "CANDLEKEEP_LOG_FILE";
+
+/// Rotate the log once it grows past this many bytes (unless `[log] max_bytes` is set)
+const DEFAULT_MAX_BYTES: u64 = 10 * 1024 * 1024;
+
+struct RequestLog {
+    path: PathBuf,
+    max_bytes: u64,
+}
+
+static LOG: OnceLock<Option<RequestLog>> = OnceLock::new();
+/// Set after the first failed write so we only warn once per process
+static WARNED: AtomicBool = AtomicBool::new(false);
+
+/// One API call, written as a single JSON line
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Entry<'a> {
+    timestamp: String,
+    method: &'a str,
+    path: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    status: Option<u16>,
+    duration_ms: u128,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<&'a str>,
+}
+
+/// Enable request logging to `path` (from `--log-file`), falling back to
+/// `CANDLEKEEP_LOG_FILE`. Call once at startup; without either, logging is off.
+pub fn init(path: Option<PathBuf>) {
+    let path = path.or_else(|| {
+        std::env::var_os(LOG_FILE_ENV)
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from)
+    });
+
+    let log = path.map(|path| RequestLog {
+        path,
+        max_bytes: config::get_log_max_bytes()
+            .ok()
+            .flatten()
+            .unwrap_or(DEFAULT_MAX_BYTES),
+    });
+    let _ = LOG.set(log);
+}
+
+/// Append one API call to the log, if logging is enabled.
+/// Failures never fail the command; the first one is reported on stderr.
+pub fn record(method: &str, path: &str, status: Option<u16>, duration: Duration, error: Option<&str>) {
+    let Some(Some(log)) = LOG.get() else {
+        return;
+    };
+
+    let entry = Entry {
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        method,
+        path,
+        status,
+        duration_ms: duration.as_millis(),
+        error,
+    };
+
+    if let Err(e) = log.append(&entry) {
+        if !WARNED.swap(true, Ordering::Relaxed) {
+            eprintln!(
+                "Warning: could not write request log {}: {}",
+                log.path.display(),
+                e
+            );
+        }
+    }
+}
+
+impl RequestLog {
+    fn append(&self, entry: &Entry) -> std::io::Result<()> {
+        self.rotate_if_needed()?;
+
+        let mut line = serde_json::to_string(entry)?;
+        line.push('\n');
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        file.write_all(line.as_bytes())
+    }
+
+    /// Move a full log aside to `<path>.1`, replacing any previous one
+    fn rotate_if_needed(&self) -> std::io::Result<()> {
+        match fs::metadata(&self.path) {
+            Ok(meta) if meta.len() >= self.max_bytes => {
+                let mut rotated = self.path.clone().into_os_string();
+                rotated.push(".1");
+                fs::rename(&self.path, rotated)
+            }
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use clap::{ArgAction, Command};
+use serde::Serialize;
+
+/// Bumped whenever a field is removed or changes meaning; new fields may be
+/// added without a bump
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// The `--help-json` document
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Schema {
+    pub schema_version: u32,
+    pub version: String,
+    pub command: CommandSchema,
+}
+
+/// One command or subcommand, with its arguments and subcommands
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandSchema {
+    pub name: String,
+    pub about: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+    pub args: Vec<ArgSchema>,
+    pub subcommands: Vec<CommandSchema>,
+}
+
+/// One flag, option, or positional argument
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArgSchema {
+    pub name: String,
+    pub long: Option<String>,
+    pub short: Option<char>,
+    /// "flag" (on or off), "count", "value", or "positional"
+    pub kind: &'static str,
+    pub required: bool,
+    /// Whether it may be given more than once (or takes a list)
+    pub multiple: bool,
+    /// Inherited by every subcommand
+    pub global: bool,
+    pub value_name: Option<String>,
+    /// The accepted values, when there's a fixed set
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub possible_values: Vec<String>,
+    pub default: Option<String>,
+    pub help: Option<String>,
+}
+
+fn arg_schema(arg: &clap::Arg) -> ArgSchema {
+    let action = arg.get_action();
+    let kind = match action {
+        ArgAction::SetTrue | ArgAction::SetFalse => "flag",
+        ArgAction::Count => "count",
+        _ if arg.is_positional() => "positional",
+        _ => "value",
+    };
+    let takes_value = action.takes_values();
+    let multiple = matches!(action, ArgAction::Append)
+        || arg.get_num_args().is_some_and(|n| n.max_values() > 1)
+        || arg.get_value_delimiter().is_some();
+    let possible_values = if takes_value {
+        arg.get_possible_values().iter().map(|v| v.get_name().to_string()).collect()
+    } else {
+        Vec::new()
+    };
+    let defaults: Vec<String> =
+        arg.get_default_values().iter().map(|v| v.to_string_lossy().into_owned()).collect();
+
+    ArgSchema {
+        name: arg.get_id().to_string(),
+        long: arg.get_long().map(str::to_string),
+        short: arg.get_short(),
+        kind,
+        required: arg.is_required_set(),
+        multiple,
+        global: arg.is_global_set(),
+        value_name: arg
+            .get_value_names()
+            .filter(|_| takes_value)
+            .map(|names| names.iter().map(|n| n.as_str()).collect::<Vec<_>>().join(" ")),
+        possible_values,
+        default: (takes_value && !defaults.is_empty()).then(|| defaults.join(",")),
+        help: arg.get_long_help().or(arg.get_help()).map(|h| h.to_string()),
+    }
+}
+
+/// Describe `command` and everything under it, leaving out hidden arguments
+/// and subcommands like `--help` does
+fn command_schema(command: &Command) -> CommandSchema {
+    CommandSchema {
+        name: command.get_name().to_string(),
+        about: command.get_about().map(|a| a.to_string()),
+        aliases: command.get_visible_aliases().map(str::to_string).collect(),
+        args: command.get_arguments().filter(|a| !a.is_hide_set()).map(arg_schema).collect(),
+        subcommands: command
+            .get_subcommands()
+            .filter(|c| !c.is_hide_set())
+            .map(command_schema)
+            .collect(),
+    }
+}
+
+/// The schema for the whole CLI
+pub fn schema(command: &Command) -> Schema {
+    Schema {
+        schema_version: SCHEMA_VERSION,
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        command: command_schema(command),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Arg;
+
+    #[test]
+    fn test_schema_describes_args_and_subcommands() {
+        let command = Command::new("ck")
+            .arg(Arg::new("json").long("json").action(ArgAction::SetTrue).global(true))
+            .arg(Arg::new("session").long("session").hide(true))
+            .subcommand(
+                Command::new("read")
+                    .about("Read content")
+                    .arg(Arg::new("ids").required(true))
+                    .arg(
+                        Arg::new("format")
+                            .long("format")
+                            .value_parser(["table", "json"])
+                            .default_value("table"),
+                    )
+                    .arg(Arg::new("chapter").long("chapter").action(ArgAction::Append)),
+            );
+
+        let schema = schema(&command);
+        assert_eq!(schema.schema_version, SCHEMA_VERSION);
+        let root = &schema.command;
+        let names: Vec<&str> = root.args.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(names, vec!["json"]);
+        assert_eq!(root.args[0].kind, "flag");
+        assert!(root.args[0].global);
+
+        let read = &root.subcommands[0];
+        assert_eq!(read.about.as_deref(), Some("Read content"));
+        let ids = &read.args[0];
+        assert_eq!((ids.kind, ids.required), ("positional", true));
+        let format = &read.args[1];
+        assert_eq!(format.possible_values, vec!["table", "json"]);
+        assert_eq!(format.default.as_deref(), Some("table"));
+        assert!(read.args[2].multiple);
+    }
+}
@@ -0,0 +1,43 @@
+use indicatif::{ProgressBar, ProgressStyle};
+use std::future::Future;
+use std::io::{self, IsTerminal};
+use std::time::Duration;
+
+use crate::output::OutputFormat;
+use crate::prompt;
+
+/// Calls that finish sooner than this never show a spinner, so fast
+/// responses don't flicker
+const SHOW_AFTER: Duration = Duration::from_millis(250);
+const TICK: Duration = Duration::from_millis(100);
+
+/// Whether a spinner may be drawn: human output, stderr is a terminal,
+/// and not in non-interactive mode
+fn enabled(format: OutputFormat) -> bool {
+    !format.is_structured() && !prompt::non_interactive() && io::stderr().is_terminal()
+}
+
+/// Await `future`, showing `message` with a spinner and elapsed time on
+/// stderr if it takes a while. The spinner is cleared before this returns.
+pub async fn with_spinner<F: Future>(message: &str, format: OutputFormat, future: F) -> F::Output {
+    if !enabled(format) {
+        return future.await;
+    }
+
+    tokio::pin!(future);
+    tokio::select! {
+        output = &mut future => return output,
+        _ = tokio::time::sleep(SHOW_AFTER) => {}
+    }
+
+    let pb = ProgressBar::new_spinner();
+    if let Ok(style) = ProgressStyle::with_template("{spinner:.green} {msg} {elapsed:.dim}") {
+        pb.set_style(style);
+    }
+    pb.set_message(message.to_string());
+    pb.enable_steady_tick(TICK);
+
+    let output = future.await;
+    pb.finish_and_clear();
+    output
+}